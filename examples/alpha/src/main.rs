@@ -1,37 +1,47 @@
+use term2d::model::blend::BlendMode;
 use term2d::model::circle::Circle;
 use term2d::model::color::Color;
 use term2d::model::event::Event;
 use term2d::model::key::Key;
+use term2d::model::path::FillRule;
+use term2d::model::path::Path;
+use term2d::model::pie::Pie;
 use term2d::model::point::Point;
 use term2d::model::polygon::Polygon;
 use term2d::model::rect::Rect;
 use term2d::model::rgba::Rgba;
+use term2d::model::roundedrect::RoundedRect;
+use term2d::model::stroke::Stroke;
 use term2d::view::canvas::halfblock::HalfblockCanvas;
 use term2d::view::canvas::Canvas;
 use term2d::App;
 use term2d::AppBuilder;
 
 struct AlphaModel {
+    heart: Path,
     polygon: Polygon,
 }
 
 fn init_model(_app: &App) -> AlphaModel {
-    let mut polygon = Polygon::new_star();
-    polygon += &Point::new(120, 15);
-    AlphaModel { polygon }
+    AlphaModel {
+        heart: Path::parse(
+            "M 0 -3 C 0 -8 -8 -8 -8 -2 C -8 3 0 6 0 9 C 0 6 8 3 8 -2 C 8 -8 0 -8 0 -3 Z",
+        )
+        .unwrap(),
+        polygon: Polygon::new_star(),
+    }
 }
 
-fn event_fn(app: &App, model: &mut AlphaModel, event: Event) -> bool {
+fn event_fn(_app: &App, _model: &mut AlphaModel, event: Event) -> bool {
     match event {
-        Event::Key(key) => match key {
+        Event::Key(key) if key.is_release() => {}
+        Event::Key(key) => match key.key() {
             Key::Char('q') => return false,
             Key::Ctrl('c') => return false,
             _ => {}
         },
         Event::Resize(_) => {}
-        Event::Elapse => {
-            model.polygon.rotate(app.frame_count as f32 / -11.0);
-        }
+        _ => {}
     }
 
     true
@@ -87,6 +97,10 @@ fn view_fn(app: &App, model: &AlphaModel, canvas: &mut HalfblockCanvas) {
     // red line
     canvas.draw_line(&Point::new(10, 10), &Point::new(30, 17), &Rgba::red());
 
+    // anti-aliased line and ellipse with sub-pixel positions
+    canvas.draw_line_aa((45.0, 30.0), (75.5, 40.0), &Rgba::red());
+    canvas.draw_ellipse_aa((90.0, 38.0), 10.0, 6.5, &Rgba::orange());
+
     // filled green circle
     canvas.draw_circle_fill(
         &Circle::new(70, 15, (app.frame_count % 27) as i32),
@@ -121,8 +135,37 @@ fn view_fn(app: &App, model: &AlphaModel, canvas: &mut HalfblockCanvas) {
         &Rgba::yellow(),
     );
 
-    // rotation cyan star
+    // overlapping circles glowing with additive blending
+    canvas.with_blend_mode(BlendMode::Add, |canvas| {
+        let glow = |r, g, b| Rgba { r, g, b, a: 192 };
+        canvas.draw_circle_fill(&Circle::new(30, 38, 7), &glow(255, 0, 0));
+        canvas.draw_circle_fill(&Circle::new(38, 38, 7), &glow(0, 255, 0));
+        canvas.draw_circle_fill(&Circle::new(34, 45, 7), &glow(0, 0, 255));
+    });
+
+    // growing pie slice in a thick dashed rounded frame
+    let angle = (app.frame_count % 63) as f32 / 10.0;
+    canvas.draw_pie_fill(&Pie::new(60, 45, 6, 6, 0.0, angle), &Rgba::yellow());
+    let frame = Stroke::new(2.0).dash(vec![3.0, 2.0], app.frame_count as f32 / 4.0);
+    canvas.with_stroke(frame, |canvas| {
+        canvas.draw_rounded_rect(&RoundedRect::new(50, 36, 21, 19, 4), &Rgba::white());
+    });
+
+    // pulsing heart drawn from svg path data
+    canvas.push_transform();
+    canvas.translate(85.0, 15.0);
+    let pulse = 1.0 + 0.15 * (app.frame_count as f32 / 3.0).sin();
+    canvas.scale(pulse, pulse);
+    canvas.draw_path_fill(&model.heart, FillRule::NonZero, &Rgba::red());
+    canvas.draw_path(&model.heart, &Rgba::white());
+    canvas.pop_transform();
+
+    // rotation cyan star, the star is centered at the origin
+    canvas.push_transform();
+    canvas.translate(120.0, 15.0);
+    canvas.rotate(app.frame_count as f32 / -11.0);
     canvas.draw_polygon(&model.polygon, &Rgba::cyan());
+    canvas.pop_transform();
 
     canvas.display();
 }
@@ -131,5 +174,6 @@ fn main() {
     AppBuilder::new(init_model)
         .event(event_fn)
         .view(view_fn)
-        .run();
+        .run()
+        .unwrap();
 }
@@ -1,9 +1,9 @@
-use image::codecs::gif::GifDecoder;
-use image::AnimationDecoder;
-use image::GenericImageView;
-use std::fs::File;
-use std::path::Path;
+use std::time::Instant;
+
 use term2d::model::image::Image;
+use term2d::model::image::ScaleFilter;
+use term2d::model::imagedraw::ImageDraw;
+use term2d::model::video::LoopMode;
 use term2d::model::video::Video;
 use term2d::App;
 use term2d::AppBuilder;
@@ -22,64 +22,63 @@ struct AnimationModel {
     cat_video2: Video,
     deer_image: Image,
     walk_video: Video,
-}
-
-fn load_gif_raw<T: AsRef<Path>>(path: T) -> Vec<(u32, u32, Vec<u8>)> {
-    let file_in = File::open(path).unwrap();
-    GifDecoder::new(file_in)
-        .unwrap()
-        .into_frames()
-        .map(|frame| {
-            let frame = frame.unwrap();
-            let buffer = frame.buffer();
-            let width = buffer.width();
-            let height = buffer.height();
-            let raw = frame.into_buffer().into_raw();
-            (width, height, raw)
-        })
-        .collect()
-}
-
-fn load_image_raw<T: AsRef<Path>>(path: T) -> (u32, u32, Vec<u8>) {
-    let img = image::open(path).unwrap();
-    let (width, height) = img.dimensions();
-    let raw = img.into_bytes();
-    (width, height, raw)
+    last_elapse: Instant,
 }
 
 fn init_model(_app: &App) -> AnimationModel {
-    let cat_raw = load_gif_raw("examples/animation/data/cat.gif");
-    let walk_raw = load_gif_raw("examples/animation/data/walk.gif");
-    let deer_raw = load_image_raw("examples/animation/data/deer.png");
-
-    let cat_video = Video::from(cat_raw);
-    let walk_video = Video::from(walk_raw);
-    let deer_image = Image::from(deer_raw);
+    let cat_video = Video::load_gif("examples/animation/data/cat.gif").unwrap();
+    let walk_video = Video::load_gif("examples/animation/data/walk.gif")
+        .unwrap()
+        .scale(&Point::new(16, 16), ScaleFilter::Nearest);
+    let deer_image = Image::load("examples/animation/data/deer.png").unwrap();
 
     let mut cat_video2 = cat_video.mirror_y();
-    cat_video2.frame = 3;
+    cat_video2.seek_frame(3);
+    cat_video2.playback.loop_mode = LoopMode::PingPong;
 
     AnimationModel {
         cat_video,
         cat_video2,
         deer_image,
         walk_video,
+        last_elapse: Instant::now(),
     }
 }
 
-fn event_fn(_app: &App, model: &mut AnimationModel, event: Event) -> bool {
+fn event_fn(app: &App, model: &mut AnimationModel, event: Event) -> bool {
     match event {
-        Event::Key(key) => match key {
+        Event::Key(key) if key.is_release() => {}
+        Event::Key(key) => match key.key() {
             Key::Char('q') => return false,
+            Key::Char(' ') => {
+                for video in [
+                    &mut model.cat_video,
+                    &mut model.cat_video2,
+                    &mut model.walk_video,
+                ] {
+                    if video.is_playing() {
+                        video.pause();
+                    } else {
+                        video.play();
+                    }
+                }
+            }
             Key::Ctrl('c') => return false,
             _ => {}
         },
         Event::Resize(_) => {}
         Event::Elapse => {
-            model.cat_video.next_frame();
-            model.cat_video2.next_frame();
-            model.walk_video.next_frame();
+            let now = Instant::now();
+            let elapsed = now - model.last_elapse;
+            model.last_elapse = now;
+
+            model.cat_video.advance(elapsed);
+            model.cat_video2.advance(elapsed);
+            model.walk_video.advance(elapsed);
         }
+        Event::FocusLost => app.set_fps(2),
+        Event::FocusGained => app.set_fps(app.config.fps),
+        _ => {}
     }
 
     true
@@ -95,7 +94,7 @@ fn view_fn(_app: &App, model: &AnimationModel, canvas: &mut HalfblockCanvas) {
             bg: Rgba::transparent(),
         },
         &format!(
-            "press 'q' to quit, frame: {}, {:?}",
+            "press 'q' to quit, space to pause, frame: {}, {:?}",
             model.walk_video.frame,
             model.walk_video.images.len()
         ),
@@ -111,10 +110,13 @@ fn view_fn(_app: &App, model: &AnimationModel, canvas: &mut HalfblockCanvas) {
         },
     );
 
+    canvas.draw_video(&Point::new(2, 3), &model.walk_video);
     canvas.draw_video(&Point::new(10, 6), &model.cat_video);
     canvas.draw_video(&Point::new(19, 6), &model.cat_video2);
-    canvas.draw_video(&Point::new(2, 3), &model.walk_video);
-    canvas.draw_image(&Point::new(30, 0), &model.deer_image);
+    // the deer fills the height of the terminal
+    let side = canvas.get_size().height();
+    let deer_rect = Rect::new(30, 0, side, side);
+    canvas.draw_image_ex(&model.deer_image, &ImageDraw::new(deer_rect));
 
     canvas.display();
 }
@@ -123,5 +125,6 @@ fn main() {
     AppBuilder::new(init_model)
         .event(event_fn)
         .view(view_fn)
-        .run();
+        .run()
+        .unwrap();
 }
@@ -10,13 +10,15 @@ use term2d::AppBuilder;
 
 fn event_fn(_app: &App, _model: &mut (), event: Event) -> bool {
     match event {
-        Event::Key(key) => match key {
+        Event::Key(key) if key.is_release() => {}
+        Event::Key(key) => match key.key() {
             Key::Char('q') => return false,
             Key::Ctrl('c') => return false,
             _ => {}
         },
         Event::Resize(_) => {}
         Event::Elapse => {}
+        _ => {}
     }
 
     true
@@ -38,5 +40,6 @@ fn main() {
         .event(event_fn)
         .view(view_fn)
         .fps(20)
-        .run();
+        .run()
+        .unwrap();
 }
@@ -23,7 +23,8 @@ fn init_model(_app: &App) -> MyModel {
 
 fn event_fn(app: &App, model: &mut MyModel, event: Event) -> bool {
     match event {
-        Event::Key(key) => match key {
+        Event::Key(key) if key.is_release() => {}
+        Event::Key(key) => match key.key() {
             Key::Char('q') => return false,
             Key::Ctrl('c') => return false,
             _ => {}
@@ -33,6 +34,7 @@ fn event_fn(app: &App, model: &mut MyModel, event: Event) -> bool {
             model.pixel_point.x = 12 + (10.0 * (app.frame_count as f32 / 10.0).cos()) as i32;
             model.pixel_point.y = 12 + (10.0 * (app.frame_count as f32 / 10.0).sin()) as i32;
         }
+        _ => {}
     }
 
     true
@@ -54,5 +56,6 @@ fn main() {
         .event(event_fn)
         .view(view_fn)
         .fps(20)
-        .run();
+        .run()
+        .unwrap();
 }
@@ -1,25 +1,76 @@
+use std::str::FromStr;
+
 use crate::state::SnakeModel;
 use term2d::model::event::Event;
-use term2d::model::key::Key;
+use term2d::model::keymap::KeyMapResult;
 use term2d::App;
 
-pub fn update_model(_app: &App, model: &mut SnakeModel, event: Event) -> bool {
-    match event {
-        Event::Key(key) => match key {
-            Key::Char('q') => return false,
-            Key::Ctrl('c') => return false,
+pub const BINDINGS: &str = "
+q = quit
+ctrl-c = quit
+? = help        # toggle this help
+r = reset       # restart the game
+
+w = up
+k = up
+up = up
+s = down
+j = down
+down = down
+a = left
+h = left
+left = left
+d = right
+l = right
+right = right
+";
+
+#[derive(Clone)]
+pub enum Action {
+    Quit,
+    Help,
+    Reset,
+    Up,
+    Down,
+    Left,
+    Right,
+}
 
-            Key::Char('r') => model.reset(),
+impl FromStr for Action {
+    type Err = ();
 
-            Key::Char('w') | Key::Char('k') | Key::Up => model.go_up(),
-            Key::Char('s') | Key::Char('j') | Key::Down => model.go_down(),
-            Key::Char('a') | Key::Char('h') | Key::Left => model.go_left(),
-            Key::Char('d') | Key::Char('l') | Key::Right => model.go_right(),
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "quit" => Ok(Action::Quit),
+            "help" => Ok(Action::Help),
+            "reset" => Ok(Action::Reset),
+            "up" => Ok(Action::Up),
+            "down" => Ok(Action::Down),
+            "left" => Ok(Action::Left),
+            "right" => Ok(Action::Right),
+            _ => Err(()),
+        }
+    }
+}
 
-            _ => {}
+pub fn update_model(_app: &App, model: &mut SnakeModel, event: Event) -> bool {
+    match event {
+        Event::Key(key) => match model.keymap.feed(&key) {
+            KeyMapResult::Action(action) => match action {
+                Action::Quit => return false,
+                Action::Help => model.show_help = !model.show_help,
+                Action::Reset => model.reset(),
+                Action::Up => model.go_up(),
+                Action::Down => model.go_down(),
+                Action::Left => model.go_left(),
+                Action::Right => model.go_right(),
+            },
+            KeyMapResult::Pending | KeyMapResult::Unbound => {}
         },
         Event::Resize(size) => model.resize(&size),
-        Event::Elapse => model.update(),
+        Event::Elapse => {}
+        Event::Timer(id) if id == model.step_timer => model.update(),
+        _ => {}
     }
 
     true
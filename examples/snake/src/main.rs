@@ -1,3 +1,6 @@
+use std::env;
+use std::process::exit;
+
 use controller::update_model;
 use renderer::draw_model;
 use state::init_model;
@@ -8,10 +11,24 @@ mod random;
 mod renderer;
 mod state;
 
+// usage: snake [--record <file> | --replay <file> [--headless] | --asciicast <file>]
 fn main() {
-    AppBuilder::new(init_model)
+    let args: Vec<String> = env::args().skip(1).collect();
+    let mut builder = AppBuilder::new(init_model)
         .event(update_model)
         .view(draw_model)
-        .fps(10)
-        .run();
+        .fps(10);
+
+    match args.iter().map(String::as_str).collect::<Vec<_>>()[..] {
+        ["--record", path] => builder = builder.record(path),
+        ["--replay", path] => builder = builder.replay(path),
+        ["--replay", path, "--headless"] => builder = builder.replay(path).headless(),
+        ["--asciicast", path] => builder = builder.asciicast(path),
+        _ => {}
+    }
+
+    if let Err(error) = builder.run() {
+        eprintln!("snake: {error}");
+        exit(1);
+    }
 }
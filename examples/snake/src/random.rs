@@ -22,6 +22,10 @@ impl Random {
         }
     }
 
+    pub fn with_seed(seed: u64) -> Self {
+        Self { seed: seed % M }
+    }
+
     pub fn next(&mut self) -> u32 {
         self.seed = (A * self.seed + C) % M;
         self.seed as u32
@@ -3,6 +3,7 @@ use term2d::model::point::Point;
 use term2d::model::rgba::Rgba;
 use term2d::view::canvas::halfblock::HalfblockCanvas;
 use term2d::view::canvas::Canvas;
+use term2d::view::help::draw_keymap_help;
 use term2d::App;
 
 use crate::state::SnakeModel;
@@ -15,6 +16,7 @@ pub fn draw_model(_app: &App, model: &SnakeModel, canvas: &mut HalfblockCanvas)
     draw_food(model, canvas);
     draw_info(model, canvas);
     draw_game_over(model, canvas);
+    draw_help(model, canvas);
 
     canvas.display();
 }
@@ -26,7 +28,10 @@ fn draw_info(model: &SnakeModel, canvas: &mut HalfblockCanvas) {
             fg: Rgba::white(),
             bg: Rgba::transparent(),
         },
-        &format!("press 'q' to quit, snake length: {}", model.snake.len(),),
+        &format!(
+            "press 'q' to quit, '?' for help, snake length: {}",
+            model.snake.len(),
+        ),
     );
 }
 
@@ -35,7 +40,7 @@ fn draw_food(model: &SnakeModel, canvas: &mut HalfblockCanvas) {
 }
 
 fn draw_snake(model: &SnakeModel, canvas: &mut HalfblockCanvas) {
-    if model.snake.len() == 0 {
+    if model.snake.is_empty() {
         return;
     }
 
@@ -61,6 +66,14 @@ fn draw_snake(model: &SnakeModel, canvas: &mut HalfblockCanvas) {
     }
 }
 
+fn draw_help(model: &SnakeModel, canvas: &mut HalfblockCanvas) {
+    if !model.show_help {
+        return;
+    }
+
+    draw_keymap_help(canvas, &Point::new(4, 8), &model.keymap);
+}
+
 fn draw_game_over(model: &SnakeModel, canvas: &mut HalfblockCanvas) {
     if !model.game_over {
         return;
@@ -1,31 +1,52 @@
+use std::time::Duration;
+
+use term2d::model::keymap::KeyMap;
 use term2d::model::point::Point;
+use term2d::model::timer::TimerId;
 use term2d::App;
 
+use crate::controller::Action;
+use crate::controller::BINDINGS;
 use crate::random::Random;
 
+const RECORDING_SEED: u64 = 1;
+
 pub struct SnakeModel {
     pub food: Point,
-    pub frame: u32,
     pub game_over: bool,
+    pub keymap: KeyMap<Action>,
     pub screen_size: Point,
+    pub show_help: bool,
     pub snake: Vec<Point>,
+    pub step_timer: TimerId,
 
     boundary: (Point, Point),
     direction: Point,
     random: Random,
 }
 
-pub fn init_model(_app: &App) -> SnakeModel {
+pub fn init_model(app: &App) -> SnakeModel {
     SnakeModel {
         boundary: (Point::new(0, 0), Point::new(0, 0)),
         direction: Point::new(0, 0),
         food: Point::new(0, 0),
-        frame: 0,
         game_over: false,
-        random: Random::new(),
+        keymap: KeyMap::parse(BINDINGS).unwrap(),
+        random: init_random(app),
         screen_size: Point::new(0, 0),
+        show_help: false,
         snake: Vec::new(),
+        step_timer: app.set_repeating_timer(Duration::from_millis(100)),
+    }
+}
+
+// recorded sessions need a fixed seed to be replayed faithfully
+fn init_random(app: &App) -> Random {
+    if app.config.record.is_some() || app.config.replay.is_some() {
+        return Random::with_seed(RECORDING_SEED);
     }
+
+    Random::new()
 }
 
 impl SnakeModel {
@@ -47,7 +68,7 @@ impl SnakeModel {
     }
 
     pub fn update(&mut self) {
-        if self.snake.len() == 0 {
+        if self.snake.is_empty() {
             return;
         }
 
@@ -17,7 +17,8 @@ pub mod model;
 
 fn update_model(app: &App, model: &mut MyModel, event: Event) -> bool {
     match event {
-        Event::Key(key) => match key {
+        Event::Key(key) if key.is_release() => {}
+        Event::Key(key) => match key.key() {
             Key::Char('q') => return false,
             Key::Ctrl('c') => return false,
             _ => {}
@@ -27,6 +28,7 @@ fn update_model(app: &App, model: &mut MyModel, event: Event) -> bool {
             model.pixel_point.x = 12 + (10.0 * (app.frame_count as f32 / 10.0).cos()) as i32;
             model.pixel_point.y = 12 + (10.0 * (app.frame_count as f32 / 10.0).sin()) as i32;
         }
+        _ => {}
     }
 
     true
@@ -48,5 +50,6 @@ fn main() {
         .event(update_model)
         .view(draw_model)
         .fps(20)
-        .run();
+        .run()
+        .unwrap();
 }
@@ -20,6 +20,12 @@ pub struct MyModel {
     pub pixel_point: Point,
 }
 
+impl Default for MyModel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl MyModel {
     pub fn new() -> Self {
         let camera = Camera {
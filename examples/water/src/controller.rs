@@ -6,7 +6,8 @@ use crate::state::State;
 
 pub fn update_model(_app: &App, model: &mut State, event: Event) -> bool {
     match event {
-        Event::Key(key) => match key {
+        Event::Key(key) if key.is_release() => {}
+        Event::Key(key) => match key.key() {
             Key::Char('q') => return false,
             Key::Ctrl('c') => return false,
 
@@ -21,9 +22,9 @@ pub fn update_model(_app: &App, model: &mut State, event: Event) -> bool {
             _ => {}
         },
         Event::Resize(_) => {}
-        Event::Elapse => {
-            model.world.simulate_water();
-        }
+        Event::Elapse => {}
+        Event::Timer(id) if id == model.step_timer => model.world.simulate_water(),
+        _ => {}
     }
 
     true
@@ -15,5 +15,6 @@ fn main() {
         .event(update_model)
         .view(draw_model)
         .fps(10)
-        .run();
+        .run()
+        .unwrap();
 }
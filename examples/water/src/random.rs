@@ -11,6 +11,12 @@ pub struct Random {
     seed: u64,
 }
 
+impl Default for Random {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Random {
     pub fn new() -> Self {
         Self {
@@ -22,6 +28,7 @@ impl Random {
         }
     }
 
+    #[allow(clippy::should_implement_trait)]
     pub fn next(&mut self) -> u32 {
         self.seed = (A * self.seed + C) % M;
         self.seed as u32
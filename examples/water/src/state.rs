@@ -1,13 +1,17 @@
+use std::time::Duration;
+
 use term2d::model::point::Point;
+use term2d::model::timer::TimerId;
 use term2d::App;
 
 use crate::world::PixelType;
 use crate::world::World;
 
-pub fn init_model(_app: &App) -> State {
+pub fn init_model(app: &App) -> State {
     State {
         cursor: Point::new(50, 2),
         frame: 0,
+        step_timer: app.set_repeating_timer(Duration::from_millis(100)),
         world: World::new(&Point::new(40, 4), &Point::new(50, 25)),
     }
 }
@@ -15,6 +19,7 @@ pub fn init_model(_app: &App) -> State {
 pub struct State {
     pub cursor: Point,
     pub frame: u32,
+    pub step_timer: TimerId,
     pub world: World,
 }
 
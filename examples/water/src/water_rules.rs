@@ -136,7 +136,7 @@ pub fn setup_water_rules() -> Vec<fn(&Droplet, &mut Random) -> Droplet> {
 }
 
 fn stay_or_move_left(d: &Droplet, r: &mut Random) -> Droplet {
-    let pos = if r.next() % 2 == 0 {
+    let pos = if r.next().is_multiple_of(2) {
         d.pos.left()
     } else {
         d.pos.clone()
@@ -145,7 +145,7 @@ fn stay_or_move_left(d: &Droplet, r: &mut Random) -> Droplet {
 }
 
 fn stay_or_move_right(d: &Droplet, r: &mut Random) -> Droplet {
-    let pos = if r.next() % 2 == 0 {
+    let pos = if r.next().is_multiple_of(2) {
         d.pos.right()
     } else {
         d.pos.clone()
@@ -154,8 +154,8 @@ fn stay_or_move_right(d: &Droplet, r: &mut Random) -> Droplet {
 }
 
 fn stay_or_move_left_or_right(d: &Droplet, r: &mut Random) -> Droplet {
-    let pos = if r.next() % 20 == 0 {
-        if r.next() % 2 == 0 {
+    let pos = if r.next().is_multiple_of(20) {
+        if r.next().is_multiple_of(2) {
             d.pos.left()
         } else {
             d.pos.right()
@@ -179,7 +179,7 @@ fn fall_right(d: &Droplet, r: &mut Random) -> Droplet {
 }
 
 fn fall_left_or_right(d: &Droplet, r: &mut Random) -> Droplet {
-    let pos = if r.next() % 2 == 0 {
+    let pos = if r.next().is_multiple_of(2) {
         d.pos.down_right()
     } else {
         d.pos.down_left()
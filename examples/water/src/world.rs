@@ -12,7 +12,7 @@ pub const RANGE_EMPTY: RgbRange = RgbRange::new(30..40, 30..40, 30..40);
 pub const RANGE_WATER_STAY: RgbRange = RgbRange::new(20..30, 40..55, 130..150);
 pub const RANGE_WATER_MOVE: RgbRange = RgbRange::new(100..110, 110..125, 200..220);
 
-#[derive(Clone)]
+#[derive(Clone, Default)]
 pub struct RgbRange {
     pub r: Range<u8>,
     pub g: Range<u8>,
@@ -72,16 +72,6 @@ impl RgbRange {
     }
 }
 
-impl Default for RgbRange {
-    fn default() -> Self {
-        Self {
-            r: Default::default(),
-            g: Default::default(),
-            b: Default::default(),
-        }
-    }
-}
-
 impl From<PixelType> for RgbRange {
     fn from(pixel_type: PixelType) -> Self {
         match pixel_type {
@@ -199,9 +189,7 @@ impl World {
     }
 
     pub fn get_type(&self, p: &Point) -> Option<PixelType> {
-        let Some(index) = self.get_index(p) else {
-            return None;
-        };
+        let index = self.get_index(p)?;
         Some(self.types[index])
     }
 
@@ -1,14 +1,37 @@
 use std::io::stdin;
+use std::io::stdout;
+use std::io::Read;
+use std::io::Write;
+use std::path::Path;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
 use std::sync::mpsc::sync_channel;
 use std::sync::mpsc::SyncSender;
+use std::sync::Arc;
+use std::sync::Condvar;
+use std::sync::Mutex;
 use std::thread;
 use std::thread::sleep;
 use std::time::Duration;
-
+use std::time::Instant;
+
+use model::ansiesc::BRACKETED_PASTE_DISABLE;
+use model::ansiesc::BRACKETED_PASTE_ENABLE;
+use model::ansiesc::FOCUS_REPORTING_DISABLE;
+use model::ansiesc::FOCUS_REPORTING_ENABLE;
+use model::ansiesc::KEYBOARD_ENHANCEMENT_POP;
+use model::ansiesc::KEYBOARD_ENHANCEMENT_PUSH;
+use model::ansiesc::KEYBOARD_ENHANCEMENT_QUERY;
 use model::config::Config;
 use model::event::Event;
+use model::input::InputParser;
 use model::point::Point;
-use termion::input::TermRead;
+use model::recording::RecordedEvent;
+use model::recording::Recorder;
+use model::recording::Recording;
+use model::recording::RecordingError;
+use model::timer::TimerId;
+use model::timer::Timers;
 use view::canvas::halfblock::HalfblockCanvas;
 use view::canvas::Canvas;
 use view::screen::RawTerminalScreen;
@@ -22,23 +45,84 @@ pub type ModelFn<Model> = fn(&App) -> Model;
 pub type ViewFn<Model, Canvas = DefaultCanvas> = fn(&App, &Model, &mut Canvas);
 pub type EventFn<Model> = fn(&App, &mut Model, Event) -> bool;
 
+// state shared with the thread sending elapse and timer events
+struct Clock {
+    fps: u32,
+    timers: Timers,
+}
+
+type SharedClock = Arc<(Mutex<Clock>, Condvar)>;
+
 pub struct App {
     pub config: Config,
     pub frame_count: u64,
 
+    clock: SharedClock,
+
     // make sure App is never constructed, AppBuilder has to be used
     _private_constructor: i32,
 }
 
 impl App {
     fn new(config: Config) -> Self {
+        let clock = Clock {
+            fps: config.fps,
+            timers: Timers::new(),
+        };
+
         Self {
             config,
             frame_count: 0,
 
+            clock: Arc::new((Mutex::new(clock), Condvar::new())),
+
             _private_constructor: 0,
         }
     }
+
+    /// Schedules a one-shot timer, `Event::Timer(id)` is sent once after `duration`.
+    pub fn set_timer(&self, duration: Duration) -> TimerId {
+        self.add_timer(duration, false)
+    }
+
+    /// Schedules a repeating timer, `Event::Timer(id)` is sent every `interval`
+    /// until the timer is canceled.
+    pub fn set_repeating_timer(&self, interval: Duration) -> TimerId {
+        self.add_timer(interval, true)
+    }
+
+    /// Cancels a timer, returns false if it already fired or was canceled.
+    /// Timer events which are already queued are still delivered.
+    pub fn cancel_timer(&self, id: TimerId) -> bool {
+        let (lock, condvar) = &*self.clock;
+        let canceled = lock.lock().unwrap().timers.cancel(id);
+        condvar.notify_one();
+        canceled
+    }
+
+    /// Changes the rate of elapse events while the app is running, e.g. to
+    /// save resources while the terminal is not focused. 0 stops them.
+    pub fn set_fps(&self, fps: u32) {
+        let (lock, condvar) = &*self.clock;
+        lock.lock().unwrap().fps = fps;
+        condvar.notify_one();
+    }
+
+    pub fn fps(&self) -> u32 {
+        let (lock, _) = &*self.clock;
+        lock.lock().unwrap().fps
+    }
+
+    fn add_timer(&self, duration: Duration, repeating: bool) -> TimerId {
+        let (lock, condvar) = &*self.clock;
+        let id = lock
+            .lock()
+            .unwrap()
+            .timers
+            .add(Instant::now(), duration, repeating);
+        condvar.notify_one();
+        id
+    }
 }
 
 pub struct AppBuilder<M, C = DefaultCanvas>
@@ -65,22 +149,60 @@ impl<M> AppBuilder<M> {
 
     pub fn fps(self, fps: u32) -> Self {
         let AppBuilder {
+            canvas,
+            config,
+            model_fn,
+            view_fn,
+            event_fn,
+        } = self;
+
+        AppBuilder {
+            canvas,
+            config: Config { fps, ..config },
+            model_fn,
+            view_fn,
+            event_fn,
+        }
+    }
+
+    pub fn debug(self) -> Self {
+        let screen_drop_strings = Vec::new();
+        let AppBuilder {
+            canvas,
+            config,
+            model_fn,
+            view_fn,
+            event_fn,
+        } = self;
+
+        AppBuilder {
             canvas,
             config: Config {
                 screen_drop_strings,
-                ..
+                ..config
             },
             model_fn,
             view_fn,
             event_fn,
-            ..
+        }
+    }
+
+    /// Enables the kitty keyboard protocol if the terminal supports it, which
+    /// adds key repeat and release events and distinguishes more modifiers.
+    pub fn keyboard_enhancement(self) -> Self {
+        let AppBuilder {
+            canvas,
+            config,
+            model_fn,
+            view_fn,
+            event_fn,
         } = self;
 
         AppBuilder {
             canvas,
             config: Config {
-                fps,
-                screen_drop_strings,
+                keyboard_enhancement: true,
+                ..config
             },
             model_fn,
             view_fn,
@@ -88,22 +210,93 @@ impl<M> AppBuilder<M> {
         }
     }
 
-    pub fn debug(self) -> Self {
-        let screen_drop_strings = Vec::new();
+    /// Records everything the app draws into an asciicast v2 file, which can
+    /// be played back with asciinema.
+    pub fn asciicast<P: AsRef<Path>>(self, path: P) -> Self {
         let AppBuilder {
             canvas,
-            config: Config { fps, .. },
+            config,
             model_fn,
             view_fn,
             event_fn,
-            ..
         } = self;
 
         AppBuilder {
             canvas,
             config: Config {
-                fps,
-                screen_drop_strings,
+                asciicast: Some(path.as_ref().to_path_buf()),
+                ..config
+            },
+            model_fn,
+            view_fn,
+            event_fn,
+        }
+    }
+
+    /// Records all events passed to the event function into a file, see
+    /// `Recording` for the format.
+    pub fn record<P: AsRef<Path>>(self, path: P) -> Self {
+        let AppBuilder {
+            canvas,
+            config,
+            model_fn,
+            view_fn,
+            event_fn,
+        } = self;
+
+        AppBuilder {
+            canvas,
+            config: Config {
+                record: Some(path.as_ref().to_path_buf()),
+                ..config
+            },
+            model_fn,
+            view_fn,
+            event_fn,
+        }
+    }
+
+    /// Replays a recording instead of reading input from the terminal, the
+    /// app stops when the recording ends. Timers and fps have no effect as
+    /// their events are replayed as well, the screen keeps the recorded size
+    /// regardless of the terminal size.
+    pub fn replay<P: AsRef<Path>>(self, path: P) -> Self {
+        let AppBuilder {
+            canvas,
+            config,
+            model_fn,
+            view_fn,
+            event_fn,
+        } = self;
+
+        AppBuilder {
+            canvas,
+            config: Config {
+                replay: Some(path.as_ref().to_path_buf()),
+                ..config
+            },
+            model_fn,
+            view_fn,
+            event_fn,
+        }
+    }
+
+    /// Replays without touching the terminal and as fast as possible, the
+    /// canvas has the recorded size. Only has an effect together with `replay`.
+    pub fn headless(self) -> Self {
+        let AppBuilder {
+            canvas,
+            config,
+            model_fn,
+            view_fn,
+            event_fn,
+        } = self;
+
+        AppBuilder {
+            canvas,
+            config: Config {
+                headless: true,
+                ..config
             },
             model_fn,
             view_fn,
@@ -147,29 +340,90 @@ impl<M> AppBuilder<M> {
         }
     }
 
-    pub fn run(mut self) {
+    /// Runs the app until the event function returns false or the replay is
+    /// over. Fails if the recording to replay can not be loaded, or if the
+    /// recording or the asciicast can not be written.
+    pub fn run(mut self) -> Result<(), RecordingError> {
         let mut app = App::new(self.config.clone());
-        let screen = RawTerminalScreen::new(self.config.screen_drop_strings);
-        self.canvas.init(screen);
+        let replay = match &self.config.replay {
+            Some(path) => Some(Recording::load(path)?),
+            None => None,
+        };
+        let headless = replay.is_some() && self.config.headless;
+
+        if let (true, Some(recording)) = (headless, &replay) {
+            let screen_size = screen_size(&recording.size, &self.canvas.get_char_size());
+            let mut screen = RawTerminalScreen::headless(&screen_size);
+            if let Some(path) = &self.config.asciicast {
+                screen.record_asciicast(path)?;
+            }
+            self.canvas.init(screen);
+        } else {
+            // input modes are always reset, even when debugging
+            let mut screen_drop_strings = vec![
+                BRACKETED_PASTE_DISABLE.to_string(),
+                FOCUS_REPORTING_DISABLE.to_string(),
+            ];
+            screen_drop_strings.extend(self.config.screen_drop_strings.clone());
+
+            let asciicast = self.config.asciicast.as_deref();
+            let screen = RawTerminalScreen::new(screen_drop_strings, asciicast)?;
+            self.canvas.init(screen);
+            write_terminal(&format!("{BRACKETED_PASTE_ENABLE}{FOCUS_REPORTING_ENABLE}"));
+        }
 
-        let (sender, receiver) = sync_channel::<Event>(1024);
-        let elapse_sender = sender.clone();
-        let key_sender = sender.clone();
-        let resize_sender = sender.clone();
+        if let Some(recording) = &replay {
+            let screen_size = screen_size(&recording.size, &self.canvas.get_char_size());
+            self.canvas.get_screen_mut().set_fixed_size(&screen_size);
+            self.canvas.resize();
+        }
 
-        thread::spawn(move || send_elapse_events(elapse_sender, self.config.fps));
-        thread::spawn(move || send_key_events(key_sender));
-        thread::spawn(move || send_resize_events(resize_sender));
+        let (sender, receiver) = sync_channel::<Event>(1024);
+        let keyboard_enhancement = KeyboardEnhancement::default();
+        let replaying = replay.is_some();
+
+        let canvas_size = match replay {
+            Some(recording) => {
+                let real_time = !headless;
+                thread::spawn(move || send_recorded_events(sender, recording.events, real_time));
+                recording.size
+            }
+            None => {
+                let clock_sender = sender.clone();
+                let key_sender = sender.clone();
+                let resize_sender = sender;
+
+                let clock = app.clock.clone();
+                thread::spawn(move || send_clock_events(clock_sender, clock));
+                let enable = self.config.keyboard_enhancement;
+                let pushed = keyboard_enhancement.pushed.clone();
+                thread::spawn(move || send_key_events(key_sender, enable, pushed));
+                thread::spawn(move || send_resize_events(resize_sender));
+
+                self.canvas.resize().clone()
+            }
+        };
+
+        let mut recorder = match &self.config.record {
+            Some(path) => Some(Recorder::create(path, &canvas_size)?),
+            None => None,
+        };
 
         let mut model = (self.model_fn)(&app);
 
-        let canvas_size = self.canvas.resize().clone();
         (self.event_fn)(&app, &mut model, Event::Resize(canvas_size));
         (self.view_fn)(&app, &model, &mut self.canvas);
 
-        loop {
-            let event = receiver.recv().unwrap();
+        // ends when all senders are gone, i.e. a replay is over
+        while let Ok(event) = receiver.recv() {
             let refined_event = match event {
+                // replays keep the recorded size so that the model behaves the same
+                Event::Resize(canvas_size) if replaying => {
+                    let screen_size = screen_size(&canvas_size, &self.canvas.get_char_size());
+                    self.canvas.get_screen_mut().set_fixed_size(&screen_size);
+                    self.canvas.resize();
+                    Event::Resize(canvas_size)
+                }
                 Event::Resize(_) => {
                     let canvas_size = self.canvas.resize().clone();
                     Event::Resize(canvas_size)
@@ -177,34 +431,156 @@ impl<M> AppBuilder<M> {
                 _ => event,
             };
 
+            if let Some(recorder) = recorder.as_mut() {
+                recorder.record(&refined_event)?;
+            }
+
             if !(self.event_fn)(&app, &mut model, refined_event) {
-                return;
+                return Ok(());
             };
 
             (self.view_fn)(&app, &model, &mut self.canvas);
 
             app.frame_count += 1;
         }
+
+        Ok(())
     }
 }
 
-fn send_elapse_events(sender: SyncSender<Event>, fps: u32) {
-    if fps <= 0 {
-        return;
+// pops the kitty keyboard flags when the app ends, only if the terminal
+// confirmed them and they were pushed
+#[derive(Default)]
+struct KeyboardEnhancement {
+    pushed: Arc<AtomicBool>,
+}
+
+impl Drop for KeyboardEnhancement {
+    fn drop(&mut self) {
+        if self.pushed.load(Ordering::SeqCst) {
+            write_terminal(KEYBOARD_ENHANCEMENT_POP);
+        }
     }
+}
+
+// Sends elapse events according to fps as well as the events of all due
+// timers, sleeps until the next deadline or until the clock changes.
+fn send_clock_events(sender: SyncSender<Event>, clock: SharedClock) {
+    let (lock, condvar) = &*clock;
+    let mut fps = 0;
+    let mut tick = None;
+    let mut next_elapse = None;
 
     loop {
-        sleep(Duration::from_millis(1000 / fps as u64));
-        let _ = sender.send(Event::Elapse);
+        let now = Instant::now();
+        let mut clock = lock.lock().unwrap();
+
+        if clock.fps != fps {
+            fps = clock.fps;
+            tick = (fps > 0).then(|| Duration::from_secs(1) / fps);
+            next_elapse = tick.map(|tick| now + tick);
+        }
+
+        let timers = &mut clock.timers;
+        let mut events: Vec<Event> = timers.poll(now).into_iter().map(Event::Timer).collect();
+
+        if let (Some(tick), Some(deadline)) = (tick, next_elapse.as_mut()) {
+            if *deadline <= now {
+                events.push(Event::Elapse);
+                *deadline += tick;
+                if *deadline <= now {
+                    *deadline = now + tick;
+                }
+            }
+        }
+
+        if events.is_empty() {
+            let next_deadline = match (timers.next_deadline(), next_elapse) {
+                (Some(a), Some(b)) => Some(a.min(b)),
+                (a, b) => a.or(b),
+            };
+
+            // spurious wakeups are fine, the loop just polls again
+            match next_deadline {
+                Some(deadline) => {
+                    let timeout = deadline.saturating_duration_since(now);
+                    drop(condvar.wait_timeout(clock, timeout).unwrap());
+                }
+                None => drop(condvar.wait(clock).unwrap()),
+            }
+            continue;
+        }
+
+        // do not hold the lock while sending, the receiver might be scheduling timers
+        drop(clock);
+
+        for event in events {
+            if sender.send(event).is_err() {
+                return;
+            }
+        }
+    }
+}
+
+fn send_recorded_events(sender: SyncSender<Event>, events: Vec<RecordedEvent>, real_time: bool) {
+    let start = Instant::now();
+
+    for RecordedEvent { time, event } in events {
+        if real_time {
+            if let Some(wait) = (start + time).checked_duration_since(Instant::now()) {
+                sleep(wait);
+            }
+        }
+
+        if sender.send(event).is_err() {
+            return;
+        }
     }
 }
 
-fn send_key_events(sender: SyncSender<Event>) {
-    let stdin = stdin();
+fn screen_size(canvas_size: &Point, char_size: &Point) -> Point {
+    Point::new(
+        canvas_size.width() / char_size.width(),
+        canvas_size.height() / char_size.height(),
+    )
+}
+
+fn send_key_events(sender: SyncSender<Event>, keyboard_enhancement: bool, pushed: Arc<AtomicBool>) {
+    let mut stdin = stdin();
+    let mut parser = InputParser::new();
+    let mut buffer = [0; 1024];
 
-    for key in stdin.keys().flatten() {
-        let _ = sender.send(Event::Key(key));
+    // the flags are only pushed once the terminal answers the query,
+    // terminals without support just ignore it
+    if keyboard_enhancement {
+        write_terminal(KEYBOARD_ENHANCEMENT_QUERY);
     }
+
+    loop {
+        let len = match stdin.read(&mut buffer) {
+            Ok(0) | Err(_) => return,
+            Ok(len) => len,
+        };
+
+        for event in parser.feed(&buffer[..len]) {
+            if sender.send(event).is_err() {
+                return;
+            }
+        }
+
+        if parser.take_keyboard_enhancement_flags().is_some()
+            && keyboard_enhancement
+            && !pushed.swap(true, Ordering::SeqCst)
+        {
+            write_terminal(KEYBOARD_ENHANCEMENT_PUSH);
+        }
+    }
+}
+
+fn write_terminal(s: &str) {
+    let mut stdout = stdout();
+    let _ = stdout.write_all(s.as_bytes());
+    let _ = stdout.flush();
 }
 
 fn send_resize_events(sync_sender: SyncSender<Event>) {
@@ -1,12 +1,30 @@
+pub mod ansiart;
 pub mod ansiesc;
+pub mod arc;
+pub mod blend;
+pub mod camera;
 pub mod circle;
+pub mod codec;
 pub mod color;
 pub mod config;
+pub mod ellipse;
 pub mod event;
+pub mod filter;
 pub mod image;
+pub mod imagedraw;
+pub mod input;
 pub mod key;
+pub mod keymap;
+pub mod path;
+pub mod pie;
 pub mod point;
 pub mod polygon;
+pub mod recording;
 pub mod rect;
 pub mod rgba;
+pub mod roundedrect;
+pub mod sprite;
+pub mod stroke;
+pub mod timer;
+pub mod transform;
 pub mod video;
@@ -0,0 +1,498 @@
+use std::path::Path;
+
+use super::color::Color;
+use super::image::Image;
+use super::point::Point;
+use super::rgba::Rgba;
+
+const DEFAULT_WIDTH: i32 = 80;
+// rows further down are dropped, e.g. after bogus cursor movements
+const MAX_HEIGHT: i32 = 10_000;
+const TAB_WIDTH: i32 = 8;
+// marks the end of the art, followed by a SAUCE metadata record
+const SUB: u8 = 0x1a;
+
+// the upper half of code page 437
+const CP437: [char; 128] = [
+    'Ç', 'ü', 'é', 'â', 'ä', 'à', 'å', 'ç', 'ê', 'ë', 'è', 'ï', 'î', 'ì', 'Ä', 'Å', //
+    'É', 'æ', 'Æ', 'ô', 'ö', 'ò', 'û', 'ù', 'ÿ', 'Ö', 'Ü', '¢', '£', '¥', '₧', 'ƒ', //
+    'á', 'í', 'ó', 'ú', 'ñ', 'Ñ', 'ª', 'º', '¿', '⌐', '¬', '½', '¼', '¡', '«', '»', //
+    '░', '▒', '▓', '│', '┤', '╡', '╢', '╖', '╕', '╣', '║', '╗', '╝', '╜', '╛', '┐', //
+    '└', '┴', '┬', '├', '─', '┼', '╞', '╟', '╚', '╔', '╩', '╦', '╠', '═', '╬', '╧', //
+    '╨', '╤', '╥', '╙', '╘', '╒', '╓', '╫', '╪', '┘', '┌', '█', '▄', '▌', '▐', '▀', //
+    'α', 'ß', 'Γ', 'π', 'Σ', 'σ', 'µ', 'τ', 'Φ', 'Θ', 'Ω', 'δ', '∞', 'φ', 'ε', '∩', //
+    '≡', '±', '≥', '≤', '⌠', '⌡', '÷', '≈', '°', '∙', '·', '√', 'ⁿ', '²', '■', '\u{a0}',
+];
+
+// the VGA palette in SGR order
+const PALETTE: [Rgba; 16] = [
+    Rgba::new(0, 0, 0, 255),
+    Rgba::new(170, 0, 0, 255),
+    Rgba::new(0, 170, 0, 255),
+    Rgba::new(170, 85, 0, 255),
+    Rgba::new(0, 0, 170, 255),
+    Rgba::new(170, 0, 170, 255),
+    Rgba::new(0, 170, 170, 255),
+    Rgba::new(170, 170, 170, 255),
+    Rgba::new(85, 85, 85, 255),
+    Rgba::new(255, 85, 85, 255),
+    Rgba::new(85, 255, 85, 255),
+    Rgba::new(255, 255, 85, 255),
+    Rgba::new(85, 85, 255, 255),
+    Rgba::new(255, 85, 255, 255),
+    Rgba::new(85, 255, 255, 255),
+    Rgba::new(255, 255, 255, 255),
+];
+
+const DEFAULT_FG: AnsiColor = AnsiColor::Palette(7);
+const DEFAULT_BG: AnsiColor = AnsiColor::Palette(0);
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Encoding {
+    Cp437,
+    Utf8,
+}
+
+impl Encoding {
+    /// Art with bytes above 127 is rarely valid UTF-8 by accident, so
+    /// anything which is not is taken as CP437.
+    pub fn detect(bytes: &[u8]) -> Self {
+        if std::str::from_utf8(bytes).is_ok() {
+            Encoding::Utf8
+        } else {
+            Encoding::Cp437
+        }
+    }
+
+    fn decode(&self, bytes: &[u8]) -> String {
+        match self {
+            Encoding::Utf8 => String::from_utf8_lossy(bytes).into_owned(),
+            Encoding::Cp437 => bytes
+                .iter()
+                .map(|&b| match b {
+                    0x7f => '⌂',
+                    0x80.. => CP437[b as usize - 0x80],
+                    _ => b as char,
+                })
+                .collect(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Cell {
+    pub ch: char,
+    pub color: Color,
+}
+
+impl Default for Cell {
+    fn default() -> Self {
+        Self {
+            ch: ' ',
+            color: Color {
+                bg: DEFAULT_BG.rgba(false),
+                fg: DEFAULT_FG.rgba(false),
+            },
+        }
+    }
+}
+
+impl Cell {
+    /// The colors of the top and bottom half of the cell, block and shade
+    /// characters are rendered exactly, others are approximated.
+    pub fn halves(&self) -> (Rgba, Rgba) {
+        let Color { bg, fg } = &self.color;
+        let mix = |alpha: u8| {
+            Rgba {
+                a: alpha,
+                ..fg.clone()
+            }
+            .blend(bg)
+        };
+
+        match self.ch {
+            ' ' | '\u{a0}' => (bg.clone(), bg.clone()),
+            '█' => (fg.clone(), fg.clone()),
+            '▀' => (fg.clone(), bg.clone()),
+            '▄' => (bg.clone(), fg.clone()),
+            '░' => (mix(64), mix(64)),
+            '▓' => (mix(192), mix(192)),
+            _ => (mix(128), mix(128)),
+        }
+    }
+}
+
+/// A grid of colored characters, e.g. loaded from an `.ans` file.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AnsiArt {
+    pub size: Point,
+    pub cells: Vec<Cell>,
+}
+
+impl AnsiArt {
+    /// Interprets text with escape sequences like a terminal `width` columns
+    /// wide would, the art is as high as the lowest row written to.
+    pub fn parse(bytes: &[u8], encoding: Encoding, width: i32) -> Self {
+        let end = bytes.iter().position(|&b| b == SUB).unwrap_or(bytes.len());
+        let text = encoding.decode(&bytes[..end]);
+
+        let mut parser = Parser::new(width.max(1));
+        parser.run(&text);
+        parser.into_art()
+    }
+
+    /// Loads an art file which is 80 columns wide, the encoding is detected.
+    pub fn load<P: AsRef<Path>>(path: P) -> std::io::Result<Self> {
+        let bytes = std::fs::read(path)?;
+        Ok(Self::parse(&bytes, Encoding::detect(&bytes), DEFAULT_WIDTH))
+    }
+
+    pub fn get_cell(&self, p: &Point) -> Option<&Cell> {
+        if p.x < 0 || p.y < 0 || p.x >= self.size.width() || p.y >= self.size.height() {
+            return None;
+        }
+
+        self.cells.get((p.x + p.y * self.size.width()) as usize)
+    }
+}
+
+/// Renders block character art with two pixels per cell, the same way as
+/// `HalfblockCanvas` does.
+impl From<&AnsiArt> for Image {
+    fn from(art: &AnsiArt) -> Self {
+        let width = art.size.width();
+        let mut image = Image::new(&Point::new(width, 2 * art.size.height()));
+
+        for (i, cell) in art.cells.iter().enumerate() {
+            let x = i as i32 % width;
+            let y = i as i32 / width;
+            let (top, bottom) = cell.halves();
+            image.pixels[(x + 2 * y * width) as usize] = top;
+            image.pixels[(x + (2 * y + 1) * width) as usize] = bottom;
+        }
+
+        image
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum AnsiColor {
+    Palette(u8),
+    Rgb(u8, u8, u8),
+}
+
+impl AnsiColor {
+    // bright is bold for the foreground and blink for the background
+    fn rgba(&self, bright: bool) -> Rgba {
+        match *self {
+            AnsiColor::Palette(i) if i < 8 && bright => PALETTE[i as usize + 8].clone(),
+            AnsiColor::Palette(i) => palette_256(i),
+            AnsiColor::Rgb(r, g, b) => Rgba::new(r, g, b, 255),
+        }
+    }
+}
+
+fn palette_256(i: u8) -> Rgba {
+    const LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+    match i {
+        0..=15 => PALETTE[i as usize].clone(),
+        16..=231 => {
+            let i = i - 16;
+            let r = LEVELS[(i / 36) as usize];
+            let g = LEVELS[(i / 6 % 6) as usize];
+            let b = LEVELS[(i % 6) as usize];
+            Rgba::new(r, g, b, 255)
+        }
+        232..=255 => {
+            let gray = 8 + 10 * (i - 232);
+            Rgba::new(gray, gray, gray, 255)
+        }
+    }
+}
+
+struct Parser {
+    width: i32,
+    rows: Vec<Vec<Cell>>,
+    cursor: Point,
+    saved_cursor: Point,
+    fg: AnsiColor,
+    bg: AnsiColor,
+    bold: bool,
+    blink: bool,
+    inverse: bool,
+}
+
+impl Parser {
+    fn new(width: i32) -> Self {
+        Self {
+            width,
+            rows: Vec::new(),
+            cursor: Point::new(0, 0),
+            saved_cursor: Point::new(0, 0),
+            fg: DEFAULT_FG,
+            bg: DEFAULT_BG,
+            bold: false,
+            blink: false,
+            inverse: false,
+        }
+    }
+
+    fn run(&mut self, text: &str) {
+        let mut chars = text.chars().peekable();
+
+        while let Some(ch) = chars.next() {
+            match ch {
+                '\x1b' if chars.peek() == Some(&'[') => {
+                    chars.next();
+                    let mut params = String::new();
+                    for ch in chars.by_ref() {
+                        if ('\x40'..='\x7e').contains(&ch) {
+                            self.csi(&params, ch);
+                            break;
+                        }
+                        params.push(ch);
+                    }
+                }
+                '\r' => self.cursor.x = 0,
+                '\n' => {
+                    self.cursor.x = 0;
+                    self.cursor.y = (self.cursor.y + 1).min(MAX_HEIGHT);
+                }
+                '\t' => {
+                    self.cursor.x = ((self.cursor.x / TAB_WIDTH + 1) * TAB_WIDTH).min(self.width)
+                }
+                '\x00'..='\x1f' => {}
+                ch => self.print(ch),
+            }
+        }
+    }
+
+    fn print(&mut self, ch: char) {
+        // the cursor stays behind the last column until the next character
+        if self.cursor.x >= self.width {
+            self.cursor.x = 0;
+            self.cursor.y = (self.cursor.y + 1).min(MAX_HEIGHT);
+        }
+
+        let (mut fg, mut bg) = (self.fg.rgba(self.bold), self.bg.rgba(self.blink));
+        if self.inverse {
+            (fg, bg) = (bg, fg);
+        }
+
+        let Point { x, y } = self.cursor;
+        if self.grow(y) {
+            self.rows[y as usize][x as usize] = Cell {
+                ch,
+                color: Color { bg, fg },
+            };
+        }
+        self.cursor.x += 1;
+    }
+
+    // adds rows up to `y`, false if it is outside of the art
+    fn grow(&mut self, y: i32) -> bool {
+        if !(0..MAX_HEIGHT).contains(&y) {
+            return false;
+        }
+
+        while self.rows.len() <= y as usize {
+            self.rows.push(vec![Cell::default(); self.width as usize]);
+        }
+        true
+    }
+
+    fn csi(&mut self, params: &str, command: char) {
+        // private sequences like showing the cursor do not affect the art
+        if params.starts_with(['?', '<', '=', '>']) {
+            return;
+        }
+
+        let numbers: Vec<u32> = params.split(';').map(|p| p.parse().unwrap_or(0)).collect();
+        let count = |i: usize| {
+            let n = numbers.get(i).copied().unwrap_or(1).max(1);
+            i32::try_from(n).unwrap_or(i32::MAX)
+        };
+        let n = count(0);
+
+        match command {
+            'm' => self.sgr(&numbers),
+            'A' => self.cursor.y = self.cursor.y.saturating_sub(n).max(0),
+            'B' => self.cursor.y = self.cursor.y.saturating_add(n).min(MAX_HEIGHT),
+            'C' => self.cursor.x = self.cursor.x.saturating_add(n).min(self.width - 1),
+            'D' => self.cursor.x = (self.cursor.x.min(self.width - 1) - n).max(0),
+            'H' | 'f' => {
+                let row = (count(0) - 1).min(MAX_HEIGHT);
+                let col = (count(1) - 1).min(self.width - 1);
+                self.cursor = Point::new(col, row);
+            }
+            'J' if numbers[0] == 2 => {
+                self.rows.clear();
+                self.cursor = Point::new(0, 0);
+            }
+            'K' => self.erase_line(numbers[0]),
+            's' => self.saved_cursor = self.cursor.clone(),
+            'u' => self.cursor = self.saved_cursor.clone(),
+            _ => {}
+        }
+    }
+
+    fn erase_line(&mut self, mode: u32) {
+        let Point { x, y } = self.cursor;
+        let x = x.min(self.width) as usize;
+        let range = match mode {
+            0 => x..self.width as usize,
+            1 => 0..(x + 1).min(self.width as usize),
+            _ => 0..self.width as usize,
+        };
+
+        if !self.grow(y) {
+            return;
+        }
+        let bg = self.bg.rgba(self.blink);
+        for cell in &mut self.rows[y as usize][range] {
+            *cell = Cell::default();
+            cell.color.bg = bg.clone();
+        }
+    }
+
+    fn sgr(&mut self, numbers: &[u32]) {
+        let mut numbers = numbers.iter().copied();
+
+        while let Some(n) = numbers.next() {
+            match n {
+                0 => {
+                    self.fg = DEFAULT_FG;
+                    self.bg = DEFAULT_BG;
+                    self.bold = false;
+                    self.blink = false;
+                    self.inverse = false;
+                }
+                1 => self.bold = true,
+                5 | 6 => self.blink = true,
+                7 => self.inverse = true,
+                22 => self.bold = false,
+                25 => self.blink = false,
+                27 => self.inverse = false,
+                30..=37 => self.fg = AnsiColor::Palette((n - 30) as u8),
+                39 => self.fg = DEFAULT_FG,
+                40..=47 => self.bg = AnsiColor::Palette((n - 40) as u8),
+                49 => self.bg = DEFAULT_BG,
+                90..=97 => self.fg = AnsiColor::Palette((n - 90 + 8) as u8),
+                100..=107 => self.bg = AnsiColor::Palette((n - 100 + 8) as u8),
+                38 | 48 => {
+                    let color = match numbers.next() {
+                        Some(5) => numbers.next().map(|i| AnsiColor::Palette(i as u8)),
+                        Some(2) => {
+                            let mut channel = || numbers.next().unwrap_or(0) as u8;
+                            Some(AnsiColor::Rgb(channel(), channel(), channel()))
+                        }
+                        _ => None,
+                    };
+
+                    match (n, color) {
+                        (38, Some(color)) => self.fg = color,
+                        (48, Some(color)) => self.bg = color,
+                        _ => {}
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn into_art(self) -> AnsiArt {
+        let height = self.rows.len() as i32;
+        AnsiArt {
+            size: Point::new(self.width, height),
+            cells: self.rows.into_iter().flatten().collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn parse(text: &str, width: i32) -> AnsiArt {
+        AnsiArt::parse(text.as_bytes(), Encoding::Utf8, width)
+    }
+
+    fn chars(art: &AnsiArt) -> String {
+        art.cells.iter().map(|cell| cell.ch).collect()
+    }
+
+    #[test]
+    fn it_places_text_and_wraps_lines() {
+        let art = parse("ab\r\ncdefg\nh", 4);
+
+        assert_eq!(art.size, Point::new(4, 4));
+        assert_eq!(chars(&art), "ab  cdefg   h   ");
+    }
+
+    #[test]
+    fn it_does_not_wrap_twice_after_a_full_line() {
+        let art = parse("abcd\nef", 4);
+        assert_eq!(chars(&art), "abcdef  ");
+    }
+
+    #[test]
+    fn it_applies_sgr_colors() {
+        let art = parse(
+            "\x1b[31ma\x1b[1;44mb\x1b[0;38;2;1;2;3;48;5;196mc\x1b[7md",
+            4,
+        );
+
+        assert_eq!(art.cells[0].color.fg, PALETTE[1]);
+        assert_eq!(art.cells[0].color.bg, PALETTE[0]);
+        assert_eq!(art.cells[1].color.fg, PALETTE[9]);
+        assert_eq!(art.cells[1].color.bg, PALETTE[4]);
+        assert_eq!(art.cells[2].color.fg, Rgba::new(1, 2, 3, 255));
+        assert_eq!(art.cells[2].color.bg, Rgba::new(255, 0, 0, 255));
+        assert_eq!(art.cells[3].color.fg, Rgba::new(255, 0, 0, 255));
+        assert_eq!(art.cells[3].color.bg, Rgba::new(1, 2, 3, 255));
+    }
+
+    #[test]
+    fn it_moves_the_cursor() {
+        let art = parse(
+            "\x1b[2;3Ha\x1b[Ab\x1b[2Cc\x1b[1;1Hd\x1b[s\x1b[3Be\x1b[uf",
+            5,
+        );
+
+        assert_eq!(chars(&art), "df bc  a        e   ");
+    }
+
+    #[test]
+    fn it_ignores_oversized_cursor_movements() {
+        let art = parse("a\x1b[4294967295Cb\x1b[3000000000Hc\x1b[99999999999Kd", 3);
+
+        assert_eq!(chars(&art), "a b");
+        assert_eq!(
+            parse("\x1b[4294967295B\x1b[Ax", 3).size,
+            Point::new(3, 10_000)
+        );
+    }
+
+    #[test]
+    fn it_decodes_cp437_and_stops_at_sauce() {
+        let bytes = [0xdb, 0xdf, b'x', SUB, b'S', b'A', b'U', b'C', b'E'];
+        assert_eq!(Encoding::detect(&bytes), Encoding::Cp437);
+
+        let art = AnsiArt::parse(&bytes, Encoding::Cp437, 3);
+        assert_eq!(chars(&art), "█▀x");
+    }
+
+    #[test]
+    fn it_renders_block_art_as_image() {
+        let art = parse("\x1b[31;42m▀█ ", 3);
+        let image = Image::from(&art);
+
+        assert_eq!(image.size, Point::new(3, 2));
+        assert_eq!(image.pixels[0], PALETTE[1]);
+        assert_eq!(image.pixels[3], PALETTE[2]);
+        assert_eq!(image.pixels[1], PALETTE[1]);
+        assert_eq!(image.pixels[4], PALETTE[1]);
+        assert_eq!(image.pixels[2], PALETTE[2]);
+    }
+}
@@ -1,8 +1,23 @@
-pub const COLOR_RESET: &'static str = "\x1b[0m";
-pub const CLEAR_ALL: &'static str = "\x1b[2J";
-pub const CURSOR_GOTO_1_1: &'static str = "\x1b[1;1H";
-pub const CURSOR_SHOW: &'static str = "\x1b[?25h";
+pub const COLOR_RESET: &str = "\x1b[0m";
+pub const CLEAR_ALL: &str = "\x1b[2J";
+pub const CURSOR_GOTO_1_1: &str = "\x1b[1;1H";
+pub const CURSOR_SHOW: &str = "\x1b[?25h";
+pub const BRACKETED_PASTE_ENABLE: &str = "\x1b[?2004h";
+pub const BRACKETED_PASTE_DISABLE: &str = "\x1b[?2004l";
+pub const BRACKETED_PASTE_START: &str = "\x1b[200~";
+pub const BRACKETED_PASTE_END: &str = "\x1b[201~";
+pub const FOCUS_REPORTING_ENABLE: &str = "\x1b[?1004h";
+pub const FOCUS_REPORTING_DISABLE: &str = "\x1b[?1004l";
 
 pub fn cursor_goto(col: i32, row: i32) -> String {
     format!("\x1b[{row};{col}H")
 }
+
+// kitty keyboard protocol, see https://sw.kovidgoyal.net/kitty/keyboard-protocol/
+pub const KEYBOARD_ENHANCEMENT_QUERY: &str = "\x1b[?u";
+pub const KEYBOARD_ENHANCEMENT_POP: &str = "\x1b[<u";
+
+/// Pushes the progressive enhancement flags: disambiguate escape codes (1),
+/// report event types (2), report alternate keys (4) and report all keys as
+/// escape codes (8).
+pub const KEYBOARD_ENHANCEMENT_PUSH: &str = "\x1b[>15u";
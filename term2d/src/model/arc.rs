@@ -0,0 +1,119 @@
+use std::f32::consts::TAU;
+
+use super::ellipse::flatten_arc;
+use super::ellipse::Ellipse;
+use super::point::Point;
+
+/// A part of the outline of an ellipse. It runs clockwise from the angle
+/// `start` to `end`, in radians from the positive x axis, and is the whole
+/// outline if they are 2π or more apart.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Arc {
+    pub pos: Point,
+    pub rx: i32,
+    pub ry: i32,
+    pub start: f32,
+    pub end: f32,
+}
+
+impl Arc {
+    pub fn new(x: i32, y: i32, rx: i32, ry: i32, start: f32, end: f32) -> Self {
+        Self {
+            pos: Point::new(x, y),
+            rx,
+            ry,
+            start,
+            end,
+        }
+    }
+
+    pub fn ellipse(&self) -> Ellipse {
+        Ellipse {
+            pos: self.pos.clone(),
+            rx: self.rx,
+            ry: self.ry,
+        }
+    }
+
+    /// The angle from `start` to `end`, between 0 and 2π.
+    pub fn sweep(&self) -> f32 {
+        let sweep = self.end - self.start;
+        if sweep.abs() >= TAU {
+            TAU
+        } else {
+            sweep.rem_euclid(TAU)
+        }
+    }
+
+    /// Whether the parametric angle of `p` relative to the center is within
+    /// the arc.
+    pub fn contains_angle(&self, p: &Point) -> bool {
+        let dx = (p.x - self.pos.x) as f32 / self.rx.max(1) as f32;
+        let dy = (p.y - self.pos.y) as f32 / self.ry.max(1) as f32;
+        let angle = dy.atan2(dx);
+
+        (angle - self.start).rem_euclid(TAU) <= self.sweep() + 1e-4
+    }
+
+    /// The first and the last point of the arc.
+    pub fn end_points(&self) -> ((f32, f32), (f32, f32)) {
+        let ellipse = self.ellipse();
+        (
+            ellipse.point_at(self.start),
+            ellipse.point_at(self.start + self.sweep()),
+        )
+    }
+
+    /// The pixels of the ellipse outline within the arc.
+    pub fn outline(&self) -> Vec<Point> {
+        self.ellipse()
+            .outline()
+            .into_iter()
+            .filter(|p| self.contains_angle(p))
+            .collect()
+    }
+
+    /// The arc as connected lines from start to end, within `tolerance` of
+    /// it.
+    pub fn flatten(&self, tolerance: f32) -> Vec<(f32, f32)> {
+        let center = (self.pos.x as f32, self.pos.y as f32);
+        let (rx, ry) = (self.rx as f32, self.ry as f32);
+        flatten_arc(center, rx, ry, self.start, self.sweep(), tolerance)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::f32::consts::FRAC_PI_2;
+    use std::f32::consts::PI;
+
+    use super::*;
+
+    #[test]
+    fn it_runs_clockwise_from_start_to_end() {
+        let arc = Arc::new(0, 0, 4, 4, 0.0, FRAC_PI_2);
+        let outline = arc.outline();
+
+        assert!(outline.contains(&Point::new(4, 0)));
+        assert!(outline.contains(&Point::new(0, 4)));
+        assert!(outline.iter().all(|p| p.x >= 0 && p.y >= 0));
+
+        let arc = Arc::new(0, 0, 4, 4, 0.0, -FRAC_PI_2);
+        assert!((arc.sweep() - 3.0 * FRAC_PI_2).abs() < 1e-6);
+        assert!(arc.outline().contains(&Point::new(-4, 0)));
+        assert!(!arc.outline().contains(&Point::new(3, -3)));
+        assert_eq!(Arc::new(0, 0, 4, 4, 0.0, 2.0 * PI).sweep(), TAU);
+    }
+
+    #[test]
+    fn it_flattens_from_start_to_end() {
+        let arc = Arc::new(10, 10, 8, 4, FRAC_PI_2, PI);
+        let points = arc.flatten(0.25);
+
+        let first = points[0];
+        let last = points[points.len() - 1];
+        assert!((first.0 - 10.0).abs() < 1e-4 && (first.1 - 14.0).abs() < 1e-4);
+        assert!((last.0 - 2.0).abs() < 1e-4 && (last.1 - 10.0).abs() < 1e-4);
+        assert_eq!(arc.end_points(), (first, last));
+    }
+}
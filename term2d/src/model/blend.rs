@@ -0,0 +1,192 @@
+use super::rgba::Rgba;
+
+/// How a drawn color (source) is combined with the color already on the
+/// canvas (destination).
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum BlendMode {
+    /// Draws the source over the destination, the usual alpha blending.
+    #[default]
+    SourceOver,
+    DestinationOver,
+    SourceIn,
+    DestinationIn,
+    SourceOut,
+    DestinationOut,
+    SourceAtop,
+    DestinationAtop,
+    Xor,
+    /// Replaces the destination with the source, including its alpha.
+    Copy,
+    Clear,
+    /// Adds the colors, e.g. for glows and lights.
+    Add,
+    Multiply,
+    Screen,
+    Overlay,
+    Darken,
+    Lighten,
+    Difference,
+}
+
+impl BlendMode {
+    /// Combines `src` with `dst`, the result has the proper alpha, e.g. it is
+    /// transparent if both are transparent.
+    pub fn blend(&self, src: &Rgba, dst: &Rgba) -> Rgba {
+        match self {
+            BlendMode::SourceOver => src.blend(dst),
+            BlendMode::DestinationOver => dst.blend(src),
+            BlendMode::SourceIn => porter_duff(src, dst, |_, db| (db, 0.0)),
+            BlendMode::DestinationIn => porter_duff(src, dst, |sa, _| (0.0, sa)),
+            BlendMode::SourceOut => porter_duff(src, dst, |_, db| (1.0 - db, 0.0)),
+            BlendMode::DestinationOut => porter_duff(src, dst, |sa, _| (0.0, 1.0 - sa)),
+            BlendMode::SourceAtop => porter_duff(src, dst, |sa, db| (db, 1.0 - sa)),
+            BlendMode::DestinationAtop => porter_duff(src, dst, |sa, db| (1.0 - db, sa)),
+            BlendMode::Xor => porter_duff(src, dst, |sa, db| (1.0 - db, 1.0 - sa)),
+            BlendMode::Copy => src.clone(),
+            BlendMode::Clear => Rgba::transparent(),
+            BlendMode::Add => separable(src, dst, |s, d| (s + d).min(1.0)),
+            BlendMode::Multiply => separable(src, dst, |s, d| s * d),
+            BlendMode::Screen => separable(src, dst, |s, d| s + d - s * d),
+            BlendMode::Overlay => separable(src, dst, |s, d| {
+                if d <= 0.5 {
+                    2.0 * s * d
+                } else {
+                    1.0 - 2.0 * (1.0 - s) * (1.0 - d)
+                }
+            }),
+            BlendMode::Darken => separable(src, dst, f32::min),
+            BlendMode::Lighten => separable(src, dst, f32::max),
+            BlendMode::Difference => separable(src, dst, |s, d| (s - d).abs()),
+        }
+    }
+}
+
+// `factors` returns the Porter-Duff factors of source and destination
+// from the source and destination alpha
+fn porter_duff<F: Fn(f32, f32) -> (f32, f32)>(src: &Rgba, dst: &Rgba, factors: F) -> Rgba {
+    let sa = src.a as f32 / 255.0;
+    let da = dst.a as f32 / 255.0;
+    let (fs, fd) = factors(sa, da);
+
+    let alpha = sa * fs + da * fd;
+    let channel = |s: u8, d: u8| {
+        let premultiplied = s as f32 / 255.0 * sa * fs + d as f32 / 255.0 * da * fd;
+        unpremultiply(premultiplied, alpha)
+    };
+
+    Rgba {
+        r: channel(src.r, dst.r),
+        g: channel(src.g, dst.g),
+        b: channel(src.b, dst.b),
+        a: (alpha * 255.0).round() as u8,
+    }
+}
+
+// blends the colors with `mix` where both are covered and composites the
+// result source over the destination
+fn separable<F: Fn(f32, f32) -> f32>(src: &Rgba, dst: &Rgba, mix: F) -> Rgba {
+    let sa = src.a as f32 / 255.0;
+    let da = dst.a as f32 / 255.0;
+    let alpha = sa + da * (1.0 - sa);
+
+    let channel = |s: u8, d: u8| {
+        let s = s as f32 / 255.0;
+        let d = d as f32 / 255.0;
+        let premultiplied = s * sa * (1.0 - da) + d * da * (1.0 - sa) + sa * da * mix(s, d);
+        unpremultiply(premultiplied, alpha)
+    };
+
+    Rgba {
+        r: channel(src.r, dst.r),
+        g: channel(src.g, dst.g),
+        b: channel(src.b, dst.b),
+        a: (alpha * 255.0).round() as u8,
+    }
+}
+
+fn unpremultiply(c: f32, alpha: f32) -> u8 {
+    if alpha <= 0.0 {
+        return 0;
+    }
+
+    (c / alpha * 255.0).round().clamp(0.0, 255.0) as u8
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const HALF_RED: Rgba = Rgba::new(255, 0, 0, 128);
+
+    #[test]
+    fn it_keeps_the_alpha_of_transparent_destinations() {
+        assert_eq!(
+            BlendMode::SourceOver.blend(&HALF_RED, &Rgba::transparent()),
+            HALF_RED
+        );
+        assert_eq!(
+            BlendMode::SourceOver.blend(&HALF_RED, &Rgba::blue()),
+            Rgba::new(128, 0, 127, 255)
+        );
+    }
+
+    #[test]
+    fn it_composites_porter_duff() {
+        let blue = Rgba::blue();
+
+        assert_eq!(BlendMode::DestinationOver.blend(&HALF_RED, &blue), blue);
+        assert_eq!(BlendMode::SourceIn.blend(&Rgba::red(), &HALF_RED), HALF_RED);
+        assert_eq!(BlendMode::SourceIn.blend(&blue, &Rgba::transparent()).a, 0);
+        assert_eq!(
+            BlendMode::DestinationOut.blend(&HALF_RED, &blue),
+            Rgba::new(0, 0, 255, 127)
+        );
+        assert_eq!(
+            BlendMode::SourceAtop.blend(&Rgba::red(), &HALF_RED),
+            HALF_RED
+        );
+        assert_eq!(BlendMode::Xor.blend(&Rgba::red(), &blue).a, 0);
+        assert_eq!(BlendMode::Copy.blend(&HALF_RED, &blue), HALF_RED);
+        assert_eq!(
+            BlendMode::Clear.blend(&HALF_RED, &blue),
+            Rgba::transparent()
+        );
+    }
+
+    #[test]
+    fn it_blends_separable_modes() {
+        let gray = Rgba::new(128, 128, 128, 255);
+        let color = Rgba::new(200, 100, 50, 255);
+
+        assert_eq!(
+            BlendMode::Add.blend(&gray, &color),
+            Rgba::new(255, 228, 178, 255)
+        );
+        assert_eq!(BlendMode::Multiply.blend(&Rgba::white(), &color), color);
+        assert_eq!(BlendMode::Screen.blend(&Rgba::black(), &color), color);
+        assert_eq!(
+            BlendMode::Overlay.blend(&gray, &color),
+            Rgba::new(200, 100, 50, 255)
+        );
+        assert_eq!(
+            BlendMode::Darken.blend(&gray, &color),
+            Rgba::new(128, 100, 50, 255)
+        );
+        assert_eq!(
+            BlendMode::Lighten.blend(&gray, &color),
+            Rgba::new(200, 128, 128, 255)
+        );
+        assert_eq!(BlendMode::Difference.blend(&color, &color), Rgba::black());
+    }
+
+    #[test]
+    fn it_adds_with_partial_alpha() {
+        let glow = Rgba::new(100, 100, 100, 128);
+
+        assert_eq!(
+            BlendMode::Add.blend(&glow, &Rgba::new(100, 0, 0, 255)),
+            Rgba::new(150, 50, 50, 255)
+        );
+        assert_eq!(BlendMode::Add.blend(&glow, &Rgba::transparent()), glow);
+    }
+}
@@ -0,0 +1,202 @@
+use std::time::Duration;
+
+use super::point::Point;
+use super::rect::Rect;
+use super::transform::Transform;
+
+/// Shows a part of a world which may be larger than the canvas, use it with
+/// `Canvas::with_camera`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Camera {
+    /// The world position shown in the center of the viewport.
+    pub x: f32,
+    pub y: f32,
+    /// Canvas pixels per world unit.
+    pub zoom: f32,
+    /// The size of the canvas area showing the world.
+    pub viewport: Point,
+    /// The camera does not move beyond the world, worlds smaller than the
+    /// viewport are centered.
+    pub bounds: Option<Rect>,
+    /// The time `follow` needs for about two thirds of the way to the target,
+    /// zero jumps to the target at once.
+    pub smoothing: Duration,
+}
+
+impl Camera {
+    /// A camera showing the world as it is, i.e. world coordinates are canvas
+    /// coordinates.
+    pub fn new(viewport: &Point) -> Self {
+        Self {
+            x: viewport.width() as f32 / 2.0,
+            y: viewport.height() as f32 / 2.0,
+            zoom: 1.0,
+            viewport: viewport.clone(),
+            bounds: None,
+            smoothing: Duration::ZERO,
+        }
+    }
+
+    pub fn with_zoom(self, zoom: f32) -> Self {
+        let mut camera = Self { zoom, ..self };
+        camera.clamp();
+        camera
+    }
+
+    pub fn with_bounds(self, bounds: Rect) -> Self {
+        let mut camera = Self {
+            bounds: Some(bounds),
+            ..self
+        };
+        camera.clamp();
+        camera
+    }
+
+    pub fn with_smoothing(self, smoothing: Duration) -> Self {
+        Self { smoothing, ..self }
+    }
+
+    /// Adapts to a resized canvas, the center stays the same.
+    pub fn set_viewport(&mut self, viewport: &Point) {
+        self.viewport = viewport.clone();
+        self.clamp();
+    }
+
+    pub fn set_zoom(&mut self, zoom: f32) {
+        self.zoom = zoom;
+        self.clamp();
+    }
+
+    /// Centers the camera on `target` at once.
+    pub fn look_at(&mut self, target: &Point) {
+        self.x = target.x as f32;
+        self.y = target.y as f32;
+        self.clamp();
+    }
+
+    /// Moves the camera towards `target`, slowed down by `smoothing`.
+    pub fn follow(&mut self, target: &Point, elapsed: Duration) {
+        let t = if self.smoothing.is_zero() {
+            1.0
+        } else {
+            1.0 - (-elapsed.as_secs_f32() / self.smoothing.as_secs_f32()).exp()
+        };
+
+        self.x += (target.x as f32 - self.x) * t;
+        self.y += (target.y as f32 - self.y) * t;
+        self.clamp();
+    }
+
+    /// Maps world coordinates to canvas coordinates.
+    pub fn transform(&self) -> Transform {
+        Transform::translation(
+            self.viewport.width() as f32 / 2.0,
+            self.viewport.height() as f32 / 2.0,
+        )
+        .scale(self.zoom, self.zoom)
+        .translate(-self.x, -self.y)
+    }
+
+    pub fn world_to_screen(&self, p: &Point) -> Point {
+        self.transform().apply_point(p)
+    }
+
+    /// The world position under a canvas pixel, e.g. under the mouse.
+    pub fn screen_to_world(&self, p: &Point) -> Point {
+        let Some(inverse) = self.transform().inverse() else {
+            return Point::new(self.x as i32, self.y as i32);
+        };
+
+        let (x, y) = inverse.apply(p.x as f32 + 0.5, p.y as f32 + 0.5);
+        Point::new(x.floor() as i32, y.floor() as i32)
+    }
+
+    /// The world area shown, e.g. to skip drawing what is not visible.
+    pub fn visible_rect(&self) -> Rect {
+        let (half_width, half_height) = self.half_extent();
+        let x0 = (self.x - half_width).floor() as i32;
+        let y0 = (self.y - half_height).floor() as i32;
+        let x1 = (self.x + half_width).ceil() as i32;
+        let y1 = (self.y + half_height).ceil() as i32;
+
+        Rect::new(x0, y0, x1 - x0, y1 - y0)
+    }
+
+    // half of the visible world size
+    fn half_extent(&self) -> (f32, f32) {
+        (
+            self.viewport.width() as f32 / (2.0 * self.zoom),
+            self.viewport.height() as f32 / (2.0 * self.zoom),
+        )
+    }
+
+    fn clamp(&mut self) {
+        let Some(bounds) = &self.bounds else {
+            return;
+        };
+
+        let (half_width, half_height) = self.half_extent();
+        self.x = clamp_axis(self.x, half_width, bounds.pos.x, bounds.size.width());
+        self.y = clamp_axis(self.y, half_height, bounds.pos.y, bounds.size.height());
+    }
+}
+
+fn clamp_axis(center: f32, half_extent: f32, start: i32, length: i32) -> f32 {
+    let start = start as f32;
+    let length = length as f32;
+
+    if length <= 2.0 * half_extent {
+        return start + length / 2.0;
+    }
+
+    center.clamp(start + half_extent, start + length - half_extent)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn it_maps_between_world_and_screen() {
+        let mut camera = Camera::new(&Point::new(20, 10)).with_zoom(2.0);
+        camera.look_at(&Point::new(100, 50));
+
+        assert_eq!(
+            camera.world_to_screen(&Point::new(100, 50)),
+            Point::new(10, 5)
+        );
+        assert_eq!(
+            camera.world_to_screen(&Point::new(101, 49)),
+            Point::new(12, 3)
+        );
+        assert_eq!(
+            camera.screen_to_world(&Point::new(12, 3)),
+            Point::new(101, 49)
+        );
+        assert_eq!(camera.visible_rect(), Rect::new(95, 47, 10, 6));
+    }
+
+    #[test]
+    fn it_stays_inside_of_the_bounds() {
+        let mut camera = Camera::new(&Point::new(20, 10)).with_bounds(Rect::new(0, 0, 100, 8));
+
+        camera.look_at(&Point::new(-50, 0));
+        assert_eq!((camera.x, camera.y), (10.0, 4.0));
+
+        camera.look_at(&Point::new(500, 0));
+        assert_eq!(camera.visible_rect(), Rect::new(80, -1, 20, 10));
+    }
+
+    #[test]
+    fn it_follows_smoothly() {
+        let mut camera =
+            Camera::new(&Point::new(10, 10)).with_smoothing(Duration::from_millis(100));
+        camera.look_at(&Point::new(0, 0));
+
+        camera.follow(&Point::new(100, 0), Duration::from_millis(100));
+        assert!((camera.x - 63.2).abs() < 0.1);
+
+        camera.follow(&Point::new(100, 0), Duration::from_secs(10));
+        assert!((camera.x - 100.0).abs() < 0.01);
+    }
+}
@@ -0,0 +1,521 @@
+use std::fmt::Display;
+#[cfg(feature = "gif")]
+use std::time::Duration;
+
+use super::image::Image;
+use super::point::Point;
+use super::rgba::Rgba;
+#[cfg(feature = "gif")]
+use super::video::Video;
+
+const QOI_MAGIC: &[u8] = b"qoif";
+
+#[derive(Debug)]
+pub enum ImageError {
+    Io(std::io::Error),
+    /// The format is unknown or its cargo feature is not enabled.
+    Unsupported(String),
+    Decode(String),
+}
+
+impl Display for ImageError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ImageError::Io(error) => write!(f, "{error}"),
+            ImageError::Unsupported(message) => write!(f, "unsupported image: {message}"),
+            ImageError::Decode(message) => write!(f, "invalid image: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for ImageError {}
+
+impl From<std::io::Error> for ImageError {
+    fn from(error: std::io::Error) -> Self {
+        ImageError::Io(error)
+    }
+}
+
+fn decode_error<T>(message: &str) -> Result<T, ImageError> {
+    Err(ImageError::Decode(message.to_string()))
+}
+
+/// Decodes an image, the format is detected by its magic bytes. Only the
+/// first frame of a GIF is returned.
+pub fn decode(bytes: &[u8]) -> Result<Image, ImageError> {
+    match bytes {
+        [b'P', b'2' | b'3' | b'5' | b'6' | b'7', ..] => decode_pnm(bytes),
+        [b'q', b'o', b'i', b'f', ..] => decode_qoi(bytes),
+        [0x89, b'P', b'N', b'G', ..] => decode_png(bytes),
+        [b'G', b'I', b'F', b'8', ..] => decode_gif_image(bytes),
+        _ => Err(ImageError::Unsupported("unknown format".to_string())),
+    }
+}
+
+// the number of pixels, None if the size does not fit into a `Point`
+fn image_len(width: usize, height: usize) -> Option<usize> {
+    if i32::try_from(width).is_err() || i32::try_from(height).is_err() {
+        return None;
+    }
+
+    width.checked_mul(height)
+}
+
+/// Decodes the PNM formats PGM (P2, P5), PPM (P3, P6) and PAM (P7).
+pub fn decode_pnm(bytes: &[u8]) -> Result<Image, ImageError> {
+    let mut header = PnmHeader::new(bytes);
+    let magic = header.token().unwrap_or_default();
+
+    let (width, height, depth, maxval) = if magic == "P7" {
+        let mut width = 0;
+        let mut height = 0;
+        let mut depth = 0;
+        let mut maxval = 0;
+
+        loop {
+            let Some(key) = header.token() else {
+                return decode_error("missing ENDHDR");
+            };
+
+            match key.as_str() {
+                "WIDTH" => width = header.number()?,
+                "HEIGHT" => height = header.number()?,
+                "DEPTH" => depth = header.number()?,
+                "MAXVAL" => maxval = header.number()?,
+                "TUPLTYPE" => {
+                    header.token();
+                }
+                "ENDHDR" => break,
+                _ => return decode_error(&format!("unknown PAM header '{key}'")),
+            }
+        }
+
+        (width, height, depth, maxval)
+    } else {
+        let depth = if matches!(magic.as_str(), "P2" | "P5") {
+            1
+        } else {
+            3
+        };
+        let width = header.number()?;
+        let height = header.number()?;
+        let maxval = header.number()?;
+        (width, height, depth, maxval)
+    };
+
+    if !(1..=4).contains(&depth) || !(1..=65535).contains(&maxval) {
+        return decode_error("invalid depth or maxval");
+    }
+
+    let Some(len) = image_len(width, height).and_then(|len| len.checked_mul(depth)) else {
+        return decode_error("image too large");
+    };
+    let samples: Vec<u32> = if matches!(magic.as_str(), "P2" | "P3") {
+        (0..len)
+            .map(|_| header.number())
+            .collect::<Result<_, _>>()?
+    } else {
+        // a single whitespace separates the header from the binary data
+        let data = &bytes[(header.pos + 1).min(bytes.len())..];
+        let sample_size = if maxval > 255 { 2 } else { 1 };
+        if len
+            .checked_mul(sample_size)
+            .is_none_or(|size| data.len() < size)
+        {
+            return decode_error("truncated data");
+        }
+
+        data.chunks_exact(sample_size)
+            .take(len)
+            .map(|s| s.iter().fold(0, |acc, &b| (acc << 8) | b as u32))
+            .collect()
+    };
+
+    let scale = |sample: u32| (sample.min(maxval) * 255 / maxval) as u8;
+    let pixels = samples
+        .chunks_exact(depth)
+        .map(|tuple| match *tuple {
+            [v] => Rgba::new(scale(v), scale(v), scale(v), 255),
+            [v, a] => Rgba::new(scale(v), scale(v), scale(v), scale(a)),
+            [r, g, b] => Rgba::new(scale(r), scale(g), scale(b), 255),
+            [r, g, b, a] => Rgba::new(scale(r), scale(g), scale(b), scale(a)),
+            _ => unreachable!(),
+        })
+        .collect();
+
+    Ok(Image {
+        pixels,
+        size: Point::new(width as i32, height as i32),
+    })
+}
+
+struct PnmHeader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> PnmHeader<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, pos: 0 }
+    }
+
+    // skips whitespace and comments, stops right behind the token
+    fn token(&mut self) -> Option<String> {
+        loop {
+            match self.bytes.get(self.pos)? {
+                b'#' => {
+                    while self.bytes.get(self.pos).is_some_and(|&b| b != b'\n') {
+                        self.pos += 1;
+                    }
+                }
+                b if b.is_ascii_whitespace() => self.pos += 1,
+                _ => break,
+            }
+        }
+
+        let start = self.pos;
+        while self
+            .bytes
+            .get(self.pos)
+            .is_some_and(|b| !b.is_ascii_whitespace())
+        {
+            self.pos += 1;
+        }
+
+        Some(String::from_utf8_lossy(&self.bytes[start..self.pos]).into_owned())
+    }
+
+    fn number<T: std::str::FromStr>(&mut self) -> Result<T, ImageError> {
+        let token = self.token().unwrap_or_default();
+        token
+            .parse()
+            .map_err(|_| ImageError::Decode(format!("invalid number '{token}'")))
+    }
+}
+
+/// Decodes the Quite OK Image format, see https://qoiformat.org/
+pub fn decode_qoi(bytes: &[u8]) -> Result<Image, ImageError> {
+    if bytes.len() < 14 || &bytes[..4] != QOI_MAGIC {
+        return decode_error("missing QOI header");
+    }
+
+    let width = u32::from_be_bytes(bytes[4..8].try_into().unwrap()) as usize;
+    let height = u32::from_be_bytes(bytes[8..12].try_into().unwrap()) as usize;
+    let Some(len) = image_len(width, height) else {
+        return decode_error("image too large");
+    };
+
+    // the data is checked while decoding, a header may claim any size
+    let mut pixels = Vec::new();
+    let mut index = vec![Rgba::transparent(); 64];
+    let mut rgba = Rgba::black();
+    let mut data = bytes[14..].iter().copied();
+    let mut next = || {
+        data.next()
+            .ok_or(ImageError::Decode("truncated data".to_string()))
+    };
+
+    while pixels.len() < len {
+        let op = next()?;
+
+        match op {
+            0xfe => {
+                rgba.r = next()?;
+                rgba.g = next()?;
+                rgba.b = next()?;
+            }
+            0xff => {
+                rgba.r = next()?;
+                rgba.g = next()?;
+                rgba.b = next()?;
+                rgba.a = next()?;
+            }
+            _ => match op >> 6 {
+                0 => rgba = index[op as usize].clone(),
+                1 => {
+                    rgba.r = rgba.r.wrapping_add((op >> 4 & 3).wrapping_sub(2));
+                    rgba.g = rgba.g.wrapping_add((op >> 2 & 3).wrapping_sub(2));
+                    rgba.b = rgba.b.wrapping_add((op & 3).wrapping_sub(2));
+                }
+                2 => {
+                    let dg = (op & 0x3f).wrapping_sub(32);
+                    let drb = next()?;
+                    rgba.r = rgba
+                        .r
+                        .wrapping_add(dg.wrapping_add(drb >> 4).wrapping_sub(8));
+                    rgba.g = rgba.g.wrapping_add(dg);
+                    rgba.b = rgba
+                        .b
+                        .wrapping_add(dg.wrapping_add(drb & 0xf).wrapping_sub(8));
+                }
+                _ => {
+                    let run = (op & 0x3f) as usize + 1;
+                    pixels.extend(std::iter::repeat_n(
+                        rgba.clone(),
+                        run.min(len - pixels.len()),
+                    ));
+                    continue;
+                }
+            },
+        }
+
+        let hash = (rgba.r as usize * 3
+            + rgba.g as usize * 5
+            + rgba.b as usize * 7
+            + rgba.a as usize * 11)
+            % 64;
+        index[hash] = rgba.clone();
+        pixels.push(rgba.clone());
+    }
+
+    Ok(Image {
+        pixels,
+        size: Point::new(width as i32, height as i32),
+    })
+}
+
+#[cfg(feature = "png")]
+pub fn decode_png(bytes: &[u8]) -> Result<Image, ImageError> {
+    let png_error = |error: png::DecodingError| ImageError::Decode(error.to_string());
+
+    let mut decoder = png::Decoder::new(bytes);
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+    let mut reader = decoder.read_info().map_err(png_error)?;
+    let mut buffer = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut buffer).map_err(png_error)?;
+    let data = &buffer[..info.buffer_size()];
+
+    let pixels = match info.color_type {
+        png::ColorType::Rgba => data
+            .chunks_exact(4)
+            .map(|p| Rgba::new(p[0], p[1], p[2], p[3]))
+            .collect(),
+        png::ColorType::Rgb => data
+            .chunks_exact(3)
+            .map(|p| Rgba::new(p[0], p[1], p[2], 255))
+            .collect(),
+        png::ColorType::GrayscaleAlpha => data
+            .chunks_exact(2)
+            .map(|p| Rgba::new(p[0], p[0], p[0], p[1]))
+            .collect(),
+        png::ColorType::Grayscale => data.iter().map(|&v| Rgba::new(v, v, v, 255)).collect(),
+        png::ColorType::Indexed => return decode_error("unexpected indexed PNG"),
+    };
+
+    Ok(Image {
+        pixels,
+        size: Point::new(info.width as i32, info.height as i32),
+    })
+}
+
+#[cfg(not(feature = "png"))]
+pub fn decode_png(_bytes: &[u8]) -> Result<Image, ImageError> {
+    Err(ImageError::Unsupported(
+        "PNG requires the 'png' feature".to_string(),
+    ))
+}
+
+#[cfg(feature = "gif")]
+fn decode_gif_image(bytes: &[u8]) -> Result<Image, ImageError> {
+    match decode_gif(bytes)?.images.into_iter().next() {
+        Some(image) => Ok(image),
+        None => decode_error("GIF without frames"),
+    }
+}
+
+#[cfg(not(feature = "gif"))]
+fn decode_gif_image(_bytes: &[u8]) -> Result<Image, ImageError> {
+    Err(ImageError::Unsupported(
+        "GIF requires the 'gif' feature".to_string(),
+    ))
+}
+
+/// Decodes all frames of a GIF, composed onto the full logical screen, and
+/// their delays.
+#[cfg(feature = "gif")]
+pub fn decode_gif(bytes: &[u8]) -> Result<Video, ImageError> {
+    let gif_error = |error: gif::DecodingError| ImageError::Decode(error.to_string());
+
+    let mut options = gif::DecodeOptions::new();
+    options.set_color_output(gif::ColorOutput::RGBA);
+    let mut decoder = options.read_info(bytes).map_err(gif_error)?;
+
+    let width = decoder.width() as usize;
+    let height = decoder.height() as usize;
+    let mut screen = vec![Rgba::transparent(); width * height];
+    let mut images = Vec::new();
+    let mut durations = Vec::new();
+
+    while let Some(frame) = decoder.read_next_frame().map_err(gif_error)? {
+        let previous = screen.clone();
+        let left = frame.left as usize;
+        let top = frame.top as usize;
+        let frame_width = frame.width as usize;
+
+        let frame_pixels = frame.buffer.chunks_exact(4).enumerate();
+        for (i, p) in frame_pixels {
+            let x = left + i % frame_width;
+            let y = top + i / frame_width;
+            // transparent pixels keep what was drawn before
+            if x < width && y < height && p[3] != 0 {
+                screen[x + y * width] = Rgba::new(p[0], p[1], p[2], p[3]);
+            }
+        }
+
+        images.push(Image {
+            pixels: screen.clone(),
+            size: Point::new(width as i32, height as i32),
+        });
+        durations.push(match frame.delay {
+            // like browsers, treat tiny delays as the default
+            0 | 1 => Video::DEFAULT_FRAME_DURATION,
+            delay => Duration::from_millis(10 * delay as u64),
+        });
+
+        match frame.dispose {
+            gif::DisposalMethod::Background => {
+                let frame_height = frame.height as usize;
+                for y in top..(top + frame_height).min(height) {
+                    for x in left..(left + frame_width).min(width) {
+                        screen[x + y * width] = Rgba::transparent();
+                    }
+                }
+            }
+            gif::DisposalMethod::Previous => screen = previous,
+            _ => {}
+        }
+    }
+
+    Ok(Video::new(images, durations))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const QOI_END: [u8; 8] = [0, 0, 0, 0, 0, 0, 0, 1];
+
+    #[test]
+    fn it_decodes_binary_ppm() {
+        let mut bytes = b"P6\n# a comment\n2 1\n255\n".to_vec();
+        bytes.extend([255, 0, 0, 1, 2, 3]);
+
+        let image = decode(&bytes).unwrap();
+
+        assert_eq!(image.size, Point::new(2, 1));
+        assert_eq!(image.pixels, vec![Rgba::red(), Rgba::new(1, 2, 3, 255)]);
+    }
+
+    #[test]
+    fn it_decodes_what_it_encodes() {
+        let image = Image {
+            pixels: vec![Rgba::red(), Rgba::new(1, 2, 3, 4)],
+            size: Point::new(1, 2),
+        };
+
+        assert_eq!(decode(&image.to_pam()).unwrap().pixels, image.pixels);
+    }
+
+    #[test]
+    fn it_decodes_ascii_pgm_with_maxval() {
+        let image = decode(b"P2 2 1 15 0 15").unwrap();
+        assert_eq!(image.pixels, vec![Rgba::black(), Rgba::white()]);
+    }
+
+    #[test]
+    fn it_decodes_qoi() {
+        let mut bytes = b"qoif".to_vec();
+        bytes.extend(4u32.to_be_bytes());
+        bytes.extend(1u32.to_be_bytes());
+        bytes.extend([4, 0]);
+        bytes.extend([0xfe, 10, 20, 30]); // rgb
+        bytes.extend([0x40 | 3 << 4 | 1 << 2 | 2]); // diff +1 -1 0
+        bytes.extend([0xc0]); // run of 1
+        let hash = (10 * 3 + 20 * 5 + 30 * 7 + 255 * 11) % 64;
+        bytes.extend([hash as u8]); // index
+        bytes.extend(QOI_END);
+
+        let image = decode(&bytes).unwrap();
+
+        assert_eq!(
+            image.pixels,
+            vec![
+                Rgba::new(10, 20, 30, 255),
+                Rgba::new(11, 19, 30, 255),
+                Rgba::new(11, 19, 30, 255),
+                Rgba::new(10, 20, 30, 255),
+            ]
+        );
+    }
+
+    #[test]
+    fn it_reports_errors_instead_of_panicking() {
+        assert!(matches!(decode(b"BM.."), Err(ImageError::Unsupported(_))));
+        assert!(matches!(
+            decode(b"P6 2 2 255 \x00"),
+            Err(ImageError::Decode(_))
+        ));
+        assert!(matches!(decode(b"qoif\x00"), Err(ImageError::Decode(_))));
+    }
+
+    #[test]
+    fn it_rejects_oversized_headers() {
+        assert!(matches!(
+            decode(b"P5 4294967296 4294967296 255 \x00"),
+            Err(ImageError::Decode(_))
+        ));
+        assert!(matches!(
+            decode(b"P7 WIDTH 2147483647 HEIGHT 2147483647 DEPTH 4 MAXVAL 65535 ENDHDR \x00"),
+            Err(ImageError::Decode(_))
+        ));
+
+        let mut qoi = b"qoif".to_vec();
+        qoi.extend([0xff; 8]);
+        qoi.extend([4, 0, 0xfd, 0xfd]);
+        assert!(matches!(decode(&qoi), Err(ImageError::Decode(_))));
+        qoi[4..12].copy_from_slice(&[0x7f, 0xff, 0xff, 0xff, 0x7f, 0xff, 0xff, 0xff]);
+        assert!(matches!(decode(&qoi), Err(ImageError::Decode(_))));
+    }
+
+    #[cfg(feature = "png")]
+    #[test]
+    fn it_decodes_png() {
+        let image = Image {
+            pixels: vec![Rgba::red(), Rgba::new(1, 2, 3, 4)],
+            size: Point::new(2, 1),
+        };
+
+        assert_eq!(
+            decode(&image.to_png().unwrap()).unwrap().pixels,
+            image.pixels
+        );
+    }
+
+    #[cfg(feature = "gif")]
+    #[test]
+    fn it_decodes_gif_frames_with_delays() {
+        let palette = [255, 0, 0, 0, 0, 255];
+        let mut bytes = Vec::new();
+        {
+            let mut encoder = gif::Encoder::new(&mut bytes, 2, 1, &palette).unwrap();
+
+            let mut first = gif::Frame::from_indexed_pixels(2, 1, vec![0, 0], None);
+            first.delay = 25;
+            encoder.write_frame(&first).unwrap();
+
+            // the transparent left pixel keeps the red of the first frame
+            let mut second = gif::Frame::from_indexed_pixels(2, 1, vec![0, 1], Some(0));
+            second.delay = 0;
+            encoder.write_frame(&second).unwrap();
+        }
+
+        let video = decode_gif(&bytes).unwrap();
+
+        assert_eq!(
+            video.durations,
+            vec![Duration::from_millis(250), Video::DEFAULT_FRAME_DURATION]
+        );
+        assert_eq!(video.images[0].pixels, vec![Rgba::red(), Rgba::red()]);
+        assert_eq!(video.images[1].pixels, vec![Rgba::red(), Rgba::blue()]);
+    }
+}
@@ -2,21 +2,12 @@ use std::fmt::Display;
 
 use super::rgba::Rgba;
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, Default, PartialEq)]
 pub struct Color {
     pub bg: Rgba,
     pub fg: Rgba,
 }
 
-impl Default for Color {
-    fn default() -> Self {
-        Self {
-            bg: Default::default(),
-            fg: Default::default(),
-        }
-    }
-}
-
 impl Color {
     pub fn text() -> Self {
         Self {
@@ -1,3 +1,5 @@
+use std::path::PathBuf;
+
 use super::ansiesc::CLEAR_ALL;
 use super::ansiesc::COLOR_RESET;
 use super::ansiesc::CURSOR_GOTO_1_1;
@@ -5,14 +7,24 @@ use super::ansiesc::CURSOR_SHOW;
 
 #[derive(Clone)]
 pub struct Config {
+    pub asciicast: Option<PathBuf>,
     pub fps: u32,
+    pub headless: bool,
+    pub keyboard_enhancement: bool,
+    pub record: Option<PathBuf>,
+    pub replay: Option<PathBuf>,
     pub screen_drop_strings: Vec<String>,
 }
 
 impl Default for Config {
     fn default() -> Self {
         Self {
+            asciicast: None,
             fps: 10,
+            headless: false,
+            keyboard_enhancement: false,
+            record: None,
+            replay: None,
             screen_drop_strings: vec![
                 COLOR_RESET.to_string(),
                 CLEAR_ALL.to_string(),
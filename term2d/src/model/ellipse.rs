@@ -0,0 +1,210 @@
+use std::f32::consts::TAU;
+
+use super::point::Point;
+use super::rect::Rect;
+
+/// An ellipse with the horizontal radius `rx` and the vertical radius `ry`,
+/// e.g. for round circles on fullblock canvases with `ry = rx / 2`.
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Ellipse {
+    pub pos: Point,
+    pub rx: i32,
+    pub ry: i32,
+}
+
+impl Ellipse {
+    pub fn new(x: i32, y: i32, rx: i32, ry: i32) -> Self {
+        Self {
+            pos: Point::new(x, y),
+            rx,
+            ry,
+        }
+    }
+
+    pub fn boundary(&self) -> Rect {
+        Rect::new(
+            self.pos.x - self.rx,
+            self.pos.y - self.ry,
+            2 * self.rx + 1,
+            2 * self.ry + 1,
+        )
+    }
+
+    pub fn contains(&self, p: &Point) -> bool {
+        let dx = (p.x - self.pos.x) as i64;
+        let dy = (p.y - self.pos.y) as i64;
+        let rx2 = (self.rx as i64).pow(2);
+        let ry2 = (self.ry as i64).pow(2);
+
+        self.boundary().contains(p) && dx * dx * ry2 + dy * dy * rx2 <= rx2 * ry2
+    }
+
+    /// The point on the outline at the parametric `angle`, in radians
+    /// clockwise from the positive x axis.
+    pub fn point_at(&self, angle: f32) -> (f32, f32) {
+        (
+            self.pos.x as f32 + self.rx as f32 * angle.cos(),
+            self.pos.y as f32 + self.ry as f32 * angle.sin(),
+        )
+    }
+
+    /// The pixels of the outline, calculated with the midpoint algorithm.
+    pub fn outline(&self) -> Vec<Point> {
+        let mut points = Vec::new();
+        if self.rx <= 0 || self.ry <= 0 {
+            let boundary = self.boundary();
+            for y in 0..boundary.size.height() {
+                for x in 0..boundary.size.width() {
+                    points.push(&boundary.pos + &Point::new(x, y));
+                }
+            }
+            return points;
+        }
+
+        let rx2 = (self.rx as i64).pow(2);
+        let ry2 = (self.ry as i64).pow(2);
+        let mut plot = |x: i64, y: i64| {
+            let (x, y) = (x as i32, y as i32);
+            for (sx, sy) in [(1, 1), (-1, 1), (1, -1), (-1, -1)] {
+                points.push(Point::new(self.pos.x + sx * x, self.pos.y + sy * y));
+            }
+        };
+
+        // the decision variables are multiplied by 4 to stay integers
+        let mut x = 0;
+        let mut y = self.ry as i64;
+        let mut d = 4 * ry2 - 4 * rx2 * y + rx2;
+        while ry2 * x < rx2 * y {
+            plot(x, y);
+            x += 1;
+            if d < 0 {
+                d += 4 * (2 * ry2 * x + ry2);
+            } else {
+                y -= 1;
+                d += 4 * (2 * ry2 * x - 2 * rx2 * y + ry2);
+            }
+        }
+
+        let mut d = ry2 * (2 * x + 1).pow(2) + 4 * rx2 * (y - 1).pow(2) - 4 * rx2 * ry2;
+        while y >= 0 {
+            plot(x, y);
+            y -= 1;
+            if d > 0 {
+                d += 4 * (rx2 - 2 * rx2 * y);
+            } else {
+                x += 1;
+                d += 4 * (2 * ry2 * x - 2 * rx2 * y + rx2);
+            }
+        }
+
+        points.sort();
+        points.dedup();
+        points
+    }
+
+    /// The outline as a polygon within `tolerance` of it, e.g. for
+    /// transformed or stroked ellipses.
+    pub fn flatten(&self, tolerance: f32) -> Vec<(f32, f32)> {
+        self.flatten_with(0.0, tolerance)
+    }
+
+    /// The outline along the outer edges of the pixels as a polygon, e.g. for
+    /// transformed fills.
+    pub fn flatten_edges(&self, tolerance: f32) -> Vec<(f32, f32)> {
+        self.flatten_with(0.5, tolerance)
+    }
+
+    fn flatten_with(&self, margin: f32, tolerance: f32) -> Vec<(f32, f32)> {
+        let center = (self.pos.x as f32, self.pos.y as f32);
+        let rx = self.rx.max(0) as f32 + margin;
+        let ry = self.ry.max(0) as f32 + margin;
+        let mut points = flatten_arc(center, rx, ry, 0.0, TAU, tolerance);
+        points.pop();
+        points
+    }
+}
+
+// arcs are split into at most this many lines, like curves in paths
+const MAX_SEGMENTS: usize = 1 << 16;
+
+// the number of lines for `sweep` radians of an arc with the larger `radius`
+// so that they stay within `tolerance` of it, e.g. huge radii give a step of
+// 0 in f32 and tiny or invalid ones a single line
+pub(crate) fn arc_segments(radius: f32, tolerance: f32, sweep: f32) -> usize {
+    if radius.is_nan() || radius <= tolerance {
+        return 1;
+    }
+
+    let step = 2.0 * (1.0 - tolerance / radius).acos();
+    let segments = (sweep.abs() / step).ceil().min(MAX_SEGMENTS as f32);
+    (segments as usize).max(1)
+}
+
+// the points from `start` along `sweep` radians of the outline of the ellipse
+// around `center`, close enough that the lines between them stay within
+// `tolerance` of it
+pub(crate) fn flatten_arc(
+    center: (f32, f32),
+    rx: f32,
+    ry: f32,
+    start: f32,
+    sweep: f32,
+    tolerance: f32,
+) -> Vec<(f32, f32)> {
+    let segments = arc_segments(rx.max(ry), tolerance, sweep);
+
+    (0..=segments)
+        .map(|i| {
+            let angle = start + sweep * i as f32 / segments as f32;
+            (center.0 + rx * angle.cos(), center.1 + ry * angle.sin())
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn it_outlines_without_gaps() {
+        let ellipse = Ellipse::new(0, 0, 6, 2);
+        let outline = ellipse.outline();
+
+        assert!(outline.contains(&Point::new(6, 0)));
+        assert!(outline.contains(&Point::new(0, -2)));
+        assert!(outline.contains(&Point::new(-6, 0)));
+        // every pixel has a neighbor on both sides
+        for p in &outline {
+            let neighbors = outline
+                .iter()
+                .filter(|q| *q != p && (q.x - p.x).abs() <= 1 && (q.y - p.y).abs() <= 1)
+                .count();
+            assert!(neighbors >= 2);
+        }
+    }
+
+    #[test]
+    fn it_contains_the_inside() {
+        let ellipse = Ellipse::new(10, 10, 4, 2);
+
+        assert!(ellipse.contains(&Point::new(14, 10)));
+        assert!(ellipse.contains(&Point::new(10, 8)));
+        assert!(!ellipse.contains(&Point::new(14, 11)));
+        assert_eq!(ellipse.boundary(), Rect::new(6, 8, 9, 5));
+    }
+
+    #[test]
+    fn it_flattens_within_the_tolerance() {
+        let ellipse = Ellipse::new(0, 0, 10, 10);
+
+        for tolerance in [0.25, 0.025] {
+            let points = ellipse.flatten(tolerance);
+            for (i, a) in points.iter().enumerate() {
+                let b = points[(i + 1) % points.len()];
+                let middle = ((a.0 + b.0) / 2.0, (a.1 + b.1) / 2.0);
+                let distance = (middle.0.powi(2) + middle.1.powi(2)).sqrt();
+                assert!(10.0 - distance <= tolerance + 1e-4);
+            }
+        }
+    }
+}
@@ -1,9 +1,15 @@
-use super::key::Key;
+use super::key::KeyEvent;
 use super::point::Point;
+use super::timer::TimerId;
 
 #[derive(Clone, Debug, PartialEq)]
 pub enum Event {
-    Key(Key),
+    Key(KeyEvent),
     Resize(Point),
     Elapse,
+    Timer(TimerId),
+    /// Text pasted into the terminal, delivered at once instead of as keys.
+    Paste(String),
+    FocusGained,
+    FocusLost,
 }
@@ -0,0 +1,353 @@
+use super::image::Image;
+use super::point::Point;
+use super::rgba::Rgba;
+
+/// A convolution kernel, the weights are given row by row and the center is
+/// the pixel being filtered.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Kernel {
+    pub size: Point,
+    pub weights: Vec<f32>,
+}
+
+impl Kernel {
+    pub fn new(width: i32, height: i32, weights: Vec<f32>) -> Self {
+        assert_eq!(weights.len(), (width * height) as usize);
+        Self {
+            size: Point::new(width, height),
+            weights,
+        }
+    }
+
+    /// Scales the weights so they sum up to 1, kernels summing up to 0 are
+    /// kept as they are.
+    pub fn normalize(self) -> Self {
+        let sum: f32 = self.weights.iter().sum();
+        if sum == 0.0 {
+            return self;
+        }
+
+        Self {
+            weights: self.weights.iter().map(|w| w / sum).collect(),
+            ..self
+        }
+    }
+
+    pub fn sharpen() -> Self {
+        #[rustfmt::skip]
+        let weights = vec![
+            0.0, -1.0, 0.0,
+            -1.0, 5.0, -1.0,
+            0.0, -1.0, 0.0,
+        ];
+        Self::new(3, 3, weights)
+    }
+
+    pub fn edge_detect() -> Self {
+        #[rustfmt::skip]
+        let weights = vec![
+            -1.0, -1.0, -1.0,
+            -1.0, 8.0, -1.0,
+            -1.0, -1.0, -1.0,
+        ];
+        Self::new(3, 3, weights)
+    }
+
+    fn horizontal(weights: Vec<f32>) -> Self {
+        Self::new(weights.len() as i32, 1, weights)
+    }
+
+    fn vertical(weights: Vec<f32>) -> Self {
+        Self::new(1, weights.len() as i32, weights)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Filter {
+    Grayscale,
+    /// Adds to every color channel, -1.0 is black and 1.0 is white.
+    Brightness(f32),
+    /// Scales the distance to middle gray, 1.0 keeps the image as it is.
+    Contrast(f32),
+    /// Values above 1.0 brighten the dark parts.
+    Gamma(f32),
+    /// Rotates the hue by degrees.
+    HueShift(f32),
+    Invert,
+    /// Reduces each color channel to the number of levels.
+    Posterize(u8),
+    /// Averages the pixels in the given radius.
+    BoxBlur(u32),
+    /// Blurs with the standard deviation sigma.
+    GaussianBlur(f32),
+    Sharpen,
+    EdgeDetect,
+    Convolve(Kernel),
+}
+
+impl Filter {
+    /// True for filters which change each color on its own, so they also
+    /// work on fg and bg colors of text.
+    pub fn is_per_pixel(&self) -> bool {
+        matches!(
+            self,
+            Filter::Grayscale
+                | Filter::Brightness(_)
+                | Filter::Contrast(_)
+                | Filter::Gamma(_)
+                | Filter::HueShift(_)
+                | Filter::Invert
+                | Filter::Posterize(_)
+        )
+    }
+
+    /// Applies a per pixel filter to one color, other filters return it
+    /// unchanged.
+    pub fn apply_rgba(&self, rgba: &Rgba) -> Rgba {
+        let map = |f: &dyn Fn(f32) -> f32| Rgba {
+            r: f(rgba.r as f32).round().clamp(0.0, 255.0) as u8,
+            g: f(rgba.g as f32).round().clamp(0.0, 255.0) as u8,
+            b: f(rgba.b as f32).round().clamp(0.0, 255.0) as u8,
+            a: rgba.a,
+        };
+
+        match *self {
+            Filter::Grayscale => {
+                let luma = 0.299 * rgba.r as f32 + 0.587 * rgba.g as f32 + 0.114 * rgba.b as f32;
+                map(&|_| luma)
+            }
+            Filter::Brightness(delta) => map(&|c| c + 255.0 * delta),
+            Filter::Contrast(factor) => map(&|c| (c - 128.0) * factor + 128.0),
+            Filter::Gamma(gamma) => map(&|c| 255.0 * (c / 255.0).powf(1.0 / gamma)),
+            Filter::HueShift(degrees) => hue_shift(rgba, degrees),
+            Filter::Invert => map(&|c| 255.0 - c),
+            Filter::Posterize(levels) => {
+                let steps = levels.max(2) as f32 - 1.0;
+                map(&|c| (c / 255.0 * steps).round() * 255.0 / steps)
+            }
+            _ => rgba.clone(),
+        }
+    }
+}
+
+impl Image {
+    pub fn filter(&self, filter: &Filter) -> Self {
+        match filter {
+            Filter::BoxBlur(radius) => {
+                let weights = vec![1.0; 2 * *radius as usize + 1];
+                self.blur(weights)
+            }
+            Filter::GaussianBlur(sigma) => self.blur(gaussian_weights(*sigma)),
+            Filter::Sharpen => self.convolve(&Kernel::sharpen()),
+            Filter::EdgeDetect => self.convolve(&Kernel::edge_detect()),
+            Filter::Convolve(kernel) => self.convolve(kernel),
+            _ => Self {
+                pixels: self.pixels.iter().map(|p| filter.apply_rgba(p)).collect(),
+                size: self.size.clone(),
+            },
+        }
+    }
+
+    /// Convolves the color channels, the alpha channel is kept. Pixels
+    /// outside of the image repeat the edge.
+    pub fn convolve(&self, kernel: &Kernel) -> Self {
+        self.convolve_channels(kernel, false)
+    }
+
+    // blurs are separable, so two passes with 1d kernels are enough
+    fn blur(&self, weights: Vec<f32>) -> Self {
+        let horizontal = Kernel::horizontal(weights.clone()).normalize();
+        let vertical = Kernel::vertical(weights).normalize();
+
+        self.convolve_channels(&horizontal, true)
+            .convolve_channels(&vertical, true)
+    }
+
+    // with alpha all channels are convolved with premultiplied alpha, so
+    // transparent pixels do not darken their neighbors
+    fn convolve_channels(&self, kernel: &Kernel, with_alpha: bool) -> Self {
+        let center_x = kernel.size.width() / 2;
+        let center_y = kernel.size.height() / 2;
+        let max = Point::new(self.size.width() - 1, self.size.height() - 1);
+
+        Image::from_fn(&self.size, |x, y| {
+            let mut sum = [0.0f32; 4];
+
+            for ky in 0..kernel.size.height() {
+                for kx in 0..kernel.size.width() {
+                    let weight = kernel.weights[(kx + ky * kernel.size.width()) as usize];
+                    let sx = (x + kx - center_x).clamp(0, max.x);
+                    let sy = (y + ky - center_y).clamp(0, max.y);
+                    let rgba = &self.pixels[(sx + sy * self.size.width()) as usize];
+
+                    let alpha = if with_alpha {
+                        rgba.a as f32 / 255.0
+                    } else {
+                        1.0
+                    };
+                    sum[0] += weight * rgba.r as f32 * alpha;
+                    sum[1] += weight * rgba.g as f32 * alpha;
+                    sum[2] += weight * rgba.b as f32 * alpha;
+                    sum[3] += weight * rgba.a as f32;
+                }
+            }
+
+            let (alpha, unpremultiply) = if with_alpha {
+                let alpha = sum[3].clamp(0.0, 255.0);
+                (alpha, if alpha > 0.0 { 255.0 / alpha } else { 0.0 })
+            } else {
+                (
+                    self.pixels[(x + y * self.size.width()) as usize].a as f32,
+                    1.0,
+                )
+            };
+
+            let channel = |c: f32| (c * unpremultiply).round().clamp(0.0, 255.0) as u8;
+            Rgba {
+                r: channel(sum[0]),
+                g: channel(sum[1]),
+                b: channel(sum[2]),
+                a: alpha.round() as u8,
+            }
+        })
+    }
+}
+
+fn gaussian_weights(sigma: f32) -> Vec<f32> {
+    let sigma = sigma.max(0.1);
+    let radius = (3.0 * sigma).ceil() as i32;
+
+    (-radius..=radius)
+        .map(|i| (-((i * i) as f32) / (2.0 * sigma * sigma)).exp())
+        .collect()
+}
+
+fn hue_shift(rgba: &Rgba, degrees: f32) -> Rgba {
+    let r = rgba.r as f32 / 255.0;
+    let g = rgba.g as f32 / 255.0;
+    let b = rgba.b as f32 / 255.0;
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let delta = max - min;
+
+    if delta == 0.0 {
+        return rgba.clone();
+    }
+
+    let hue = if max == r {
+        60.0 * ((g - b) / delta)
+    } else if max == g {
+        60.0 * ((b - r) / delta + 2.0)
+    } else {
+        60.0 * ((r - g) / delta + 4.0)
+    };
+    let hue = (hue + degrees).rem_euclid(360.0);
+
+    // back from hsv with the same value and saturation
+    let x = delta * (1.0 - ((hue / 60.0) % 2.0 - 1.0).abs());
+    let (r, g, b) = match (hue / 60.0) as u32 {
+        0 => (delta, x, 0.0),
+        1 => (x, delta, 0.0),
+        2 => (0.0, delta, x),
+        3 => (0.0, x, delta),
+        4 => (x, 0.0, delta),
+        _ => (delta, 0.0, x),
+    };
+
+    let channel = |c: f32| ((c + min) * 255.0).round() as u8;
+    Rgba {
+        r: channel(r),
+        g: channel(g),
+        b: channel(b),
+        a: rgba.a,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn gray(v: u8) -> Rgba {
+        Rgba::new(v, v, v, 255)
+    }
+
+    #[test]
+    fn it_adjusts_colors() {
+        let rgba = Rgba::new(200, 100, 0, 77);
+
+        assert_eq!(
+            Filter::Grayscale.apply_rgba(&rgba),
+            Rgba::new(119, 119, 119, 77)
+        );
+        assert_eq!(
+            Filter::Invert.apply_rgba(&rgba),
+            Rgba::new(55, 155, 255, 77)
+        );
+        assert_eq!(
+            Filter::Brightness(0.2).apply_rgba(&rgba),
+            Rgba::new(251, 151, 51, 77)
+        );
+        assert_eq!(
+            Filter::Contrast(2.0).apply_rgba(&rgba),
+            Rgba::new(255, 72, 0, 77)
+        );
+        assert_eq!(
+            Filter::Posterize(2).apply_rgba(&rgba),
+            Rgba::new(255, 0, 0, 77)
+        );
+        assert_eq!(Filter::Gamma(1.0).apply_rgba(&rgba), rgba);
+    }
+
+    #[test]
+    fn it_shifts_the_hue() {
+        assert_eq!(
+            Filter::HueShift(120.0).apply_rgba(&Rgba::red()),
+            Rgba::green()
+        );
+        assert_eq!(
+            Filter::HueShift(-120.0).apply_rgba(&Rgba::red()),
+            Rgba::blue()
+        );
+        assert_eq!(Filter::HueShift(90.0).apply_rgba(&gray(80)), gray(80));
+    }
+
+    #[test]
+    fn it_blurs() {
+        let image = Image::from_fn(&Point::new(3, 1), |x, _| gray(if x == 1 { 255 } else { 0 }));
+
+        let blurred = image.filter(&Filter::BoxBlur(1));
+        assert_eq!(blurred.pixels, vec![gray(85), gray(85), gray(85)]);
+
+        let blurred = image.filter(&Filter::GaussianBlur(1.0));
+        assert!(blurred.pixels[1].r < 255 && blurred.pixels[0].r > 0);
+        assert_eq!(blurred.pixels[0], blurred.pixels[2]);
+    }
+
+    #[test]
+    fn it_blurs_without_darkening_transparent_neighbors() {
+        let image = Image::from_fn(&Point::new(2, 1), |x, _| {
+            [Rgba::red(), Rgba::transparent()][x as usize].clone()
+        });
+
+        let blurred = image.filter(&Filter::BoxBlur(1));
+
+        assert_eq!(blurred.pixels[1].r, 255);
+        assert!(blurred.pixels[1].a > 0 && blurred.pixels[1].a < 255);
+    }
+
+    #[test]
+    fn it_convolves_and_keeps_alpha() {
+        let image = Image::from_fn(&Point::new(3, 3), |_, _| Rgba::new(10, 20, 30, 200));
+
+        let edges = image.filter(&Filter::EdgeDetect);
+        assert!(edges.pixels.iter().all(|p| *p == Rgba::new(0, 0, 0, 200)));
+
+        let sharpened = image.filter(&Filter::Sharpen);
+        assert_eq!(sharpened.pixels, image.pixels);
+
+        let shift = Kernel::new(3, 1, vec![1.0, 0.0, 0.0]);
+        let gradient = Image::from_fn(&Point::new(3, 1), |x, _| gray(10 * x as u8));
+        let shifted = gradient.filter(&Filter::Convolve(shift));
+        assert_eq!(shifted.pixels, vec![gray(0), gray(0), gray(10)]);
+    }
+}
@@ -1,6 +1,18 @@
+use std::path::Path;
+
+use super::codec;
+use super::codec::ImageError;
 use super::point::Point;
+use super::rect::Rect;
 use super::rgba::Rgba;
 
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum ScaleFilter {
+    #[default]
+    Nearest,
+    Bilinear,
+}
+
 #[derive(Clone)]
 pub struct Image {
     pub pixels: Vec<Rgba>,
@@ -31,20 +43,362 @@ impl From<(u32, u32, Vec<u8>)> for Image {
 }
 
 impl Image {
-    pub fn mirror_y(&self) -> Self {
-        let mut mirrored_image = Self {
-            pixels: Vec::new(),
-            size: self.size.clone(),
+    pub fn new(size: &Point) -> Self {
+        let len = (size.width() * size.height()) as usize;
+        Self {
+            pixels: vec![Rgba::transparent(); len],
+            size: size.clone(),
+        }
+    }
+
+    /// Loads a PPM, PGM, PAM or QOI image, PNG and GIF need the `png` and
+    /// `gif` features. The format is detected from the content.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, ImageError> {
+        Self::decode(&std::fs::read(path)?)
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self, ImageError> {
+        codec::decode(bytes)
+    }
+
+    /// Encodes the image as binary PPM (P6), alpha is dropped.
+    pub fn to_ppm(&self) -> Vec<u8> {
+        let mut bytes =
+            format!("P6\n{} {}\n255\n", self.size.width(), self.size.height()).into_bytes();
+
+        for rgba in &self.pixels {
+            bytes.extend([rgba.r, rgba.g, rgba.b]);
+        }
+
+        bytes
+    }
+
+    /// Encodes the image as PAM (P7) with an alpha channel.
+    pub fn to_pam(&self) -> Vec<u8> {
+        let mut bytes = format!(
+            "P7\nWIDTH {}\nHEIGHT {}\nDEPTH 4\nMAXVAL 255\nTUPLTYPE RGB_ALPHA\nENDHDR\n",
+            self.size.width(),
+            self.size.height()
+        )
+        .into_bytes();
+
+        for rgba in &self.pixels {
+            bytes.extend([rgba.r, rgba.g, rgba.b, rgba.a]);
+        }
+
+        bytes
+    }
+
+    #[cfg(feature = "png")]
+    pub fn to_png(&self) -> std::io::Result<Vec<u8>> {
+        let mut bytes = Vec::new();
+        let mut encoder = png::Encoder::new(
+            &mut bytes,
+            self.size.width() as u32,
+            self.size.height() as u32,
+        );
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+
+        let data: Vec<u8> = self
+            .pixels
+            .iter()
+            .flat_map(|rgba| [rgba.r, rgba.g, rgba.b, rgba.a])
+            .collect();
+
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&data)?;
+        writer.finish()?;
+
+        Ok(bytes)
+    }
+
+    /// Saves the image in the format given by the file extension: `ppm`,
+    /// `pam` or, with the `png` feature, `png`.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> std::io::Result<()> {
+        let path = path.as_ref();
+        let extension = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_ascii_lowercase());
+
+        let bytes = match extension.as_deref() {
+            Some("ppm") => self.to_ppm(),
+            Some("pam") => self.to_pam(),
+            #[cfg(feature = "png")]
+            Some("png") => self.to_png()?,
+            _ => {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    format!("unsupported image format '{}'", path.display()),
+                ))
+            }
         };
 
-        for y in 0..self.size.height() {
-            for x in 0..self.size.width() {
-                let index = ((self.size.width() - 1 - x) + y * self.size.width()) as usize;
-                let rgba = self.pixels[index].clone();
-                mirrored_image.pixels.push(rgba);
+        std::fs::write(path, bytes)
+    }
+
+    /// Same as `flip_horizontal`.
+    pub fn mirror_y(&self) -> Self {
+        self.flip_horizontal()
+    }
+
+    /// Builds an image by calling `f` with the coordinates of each pixel.
+    pub fn from_fn<F: FnMut(i32, i32) -> Rgba>(size: &Point, mut f: F) -> Self {
+        let mut pixels = Vec::with_capacity((size.width() * size.height()).max(0) as usize);
+
+        for y in 0..size.height() {
+            for x in 0..size.width() {
+                pixels.push(f(x, y));
+            }
+        }
+
+        Self {
+            pixels,
+            size: size.clone(),
+        }
+    }
+
+    pub fn get_pixel(&self, p: &Point) -> Option<&Rgba> {
+        if !p.is_contained(&self.size) {
+            return None;
+        }
+
+        self.pixels.get((p.x + p.y * self.size.width()) as usize)
+    }
+
+    // the pixel at x, y or transparent outside of the image
+    fn pixel(&self, x: i32, y: i32) -> Rgba {
+        self.get_pixel(&Point::new(x, y))
+            .cloned()
+            .unwrap_or(Rgba::transparent())
+    }
+
+    /// Cuts out `rect`, parts of it outside of the image are transparent.
+    pub fn crop(&self, rect: &Rect) -> Self {
+        Self::from_fn(&rect.size, |x, y| {
+            self.pixel(rect.pos.x + x, rect.pos.y + y)
+        })
+    }
+
+    pub fn flip_horizontal(&self) -> Self {
+        let width = self.size.width();
+        Self::from_fn(&self.size, |x, y| self.pixel(width - 1 - x, y))
+    }
+
+    pub fn flip_vertical(&self) -> Self {
+        let height = self.size.height();
+        Self::from_fn(&self.size, |x, y| self.pixel(x, height - 1 - y))
+    }
+
+    /// Rotates by 90 degrees clockwise.
+    pub fn rotate90(&self) -> Self {
+        let height = self.size.height();
+        let size = Point::new(height, self.size.width());
+        Self::from_fn(&size, |x, y| self.pixel(y, height - 1 - x))
+    }
+
+    pub fn rotate180(&self) -> Self {
+        let Point {
+            x: width,
+            y: height,
+        } = self.size;
+        Self::from_fn(&self.size, |x, y| self.pixel(width - 1 - x, height - 1 - y))
+    }
+
+    /// Rotates by 270 degrees clockwise, i.e. 90 degrees counterclockwise.
+    pub fn rotate270(&self) -> Self {
+        let width = self.size.width();
+        let size = Point::new(self.size.height(), width);
+        Self::from_fn(&size, |x, y| self.pixel(width - 1 - y, x))
+    }
+
+    /// Rotates around the center by `angle` radians, clockwise on screen like
+    /// `Point::rotate`. The image grows to fit the rotated corners, uncovered
+    /// pixels are transparent.
+    pub fn rotate(&self, angle: f32) -> Self {
+        let (sin, cos) = angle.sin_cos();
+        let width = self.size.width() as f32;
+        let height = self.size.height() as f32;
+        // the epsilon keeps rounding errors from adding a column or row
+        let new_width = ((width * cos).abs() + (height * sin).abs() - 1e-3).ceil();
+        let new_height = ((width * sin).abs() + (height * cos).abs() - 1e-3).ceil();
+        let size = Point::new(new_width as i32, new_height as i32);
+
+        Self::from_fn(&size, |x, y| {
+            let dx = x as f32 + 0.5 - new_width / 2.0;
+            let dy = y as f32 + 0.5 - new_height / 2.0;
+            let sx = dx * cos + dy * sin + width / 2.0;
+            let sy = -dx * sin + dy * cos + height / 2.0;
+            self.pixel(sx.floor() as i32, sy.floor() as i32)
+        })
+    }
+
+    pub fn scale(&self, size: &Point, filter: ScaleFilter) -> Self {
+        if self.pixels.is_empty() {
+            return Self::new(size);
+        }
+
+        let scale_x = self.size.width() as f32 / size.width() as f32;
+        let scale_y = self.size.height() as f32 / size.height() as f32;
+
+        Self::from_fn(size, |x, y| {
+            let sx = (x as f32 + 0.5) * scale_x;
+            let sy = (y as f32 + 0.5) * scale_y;
+
+            match filter {
+                ScaleFilter::Nearest => self.pixel(sx as i32, sy as i32),
+                ScaleFilter::Bilinear => self.bilinear(sx - 0.5, sy - 0.5),
             }
+        })
+    }
+
+    // interpolates with premultiplied alpha, so transparent pixels do not
+    // darken their neighbors
+    fn bilinear(&self, x: f32, y: f32) -> Rgba {
+        let max_x = self.size.width() - 1;
+        let max_y = self.size.height() - 1;
+        let x = x.clamp(0.0, max_x as f32);
+        let y = y.clamp(0.0, max_y as f32);
+        let x0 = x.floor() as i32;
+        let y0 = y.floor() as i32;
+        let tx = x - x0 as f32;
+        let ty = y - y0 as f32;
+
+        let mut sum = [0.0f32; 4];
+        for (px, py, weight) in [
+            (x0, y0, (1.0 - tx) * (1.0 - ty)),
+            ((x0 + 1).min(max_x), y0, tx * (1.0 - ty)),
+            (x0, (y0 + 1).min(max_y), (1.0 - tx) * ty),
+            ((x0 + 1).min(max_x), (y0 + 1).min(max_y), tx * ty),
+        ] {
+            let rgba = self.pixel(px, py);
+            let alpha = rgba.a as f32 * weight;
+            sum[0] += rgba.r as f32 * alpha;
+            sum[1] += rgba.g as f32 * alpha;
+            sum[2] += rgba.b as f32 * alpha;
+            sum[3] += alpha;
+        }
+
+        if sum[3] == 0.0 {
+            return Rgba::transparent();
         }
 
-        mirrored_image
+        Rgba {
+            r: (sum[0] / sum[3]).round() as u8,
+            g: (sum[1] / sum[3]).round() as u8,
+            b: (sum[2] / sum[3]).round() as u8,
+            a: sum[3].round() as u8,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn image() -> Image {
+        Image {
+            pixels: vec![Rgba::red(), Rgba::new(1, 2, 3, 4)],
+            size: Point::new(2, 1),
+        }
+    }
+
+    #[test]
+    fn it_encodes_ppm() {
+        let mut expected = b"P6\n2 1\n255\n".to_vec();
+        expected.extend([255, 0, 0, 1, 2, 3]);
+        assert_eq!(image().to_ppm(), expected);
+    }
+
+    #[test]
+    fn it_encodes_pam() {
+        let mut expected =
+            b"P7\nWIDTH 2\nHEIGHT 1\nDEPTH 4\nMAXVAL 255\nTUPLTYPE RGB_ALPHA\nENDHDR\n".to_vec();
+        expected.extend([255, 0, 0, 255, 1, 2, 3, 4]);
+        assert_eq!(image().to_pam(), expected);
+    }
+
+    #[test]
+    fn it_rejects_unknown_formats() {
+        let path = std::env::temp_dir().join("term2d-image-test.bmp");
+        let error = image().save(&path).unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+    }
+
+    #[cfg(feature = "png")]
+    #[test]
+    fn it_encodes_png() {
+        let png = image().to_png().unwrap();
+        assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+    }
+
+    fn reds(image: &Image) -> Vec<u8> {
+        image.pixels.iter().map(|rgba| rgba.r).collect()
+    }
+
+    // 1 2 3
+    // 4 5 6
+    fn numbered() -> Image {
+        Image::from_fn(&Point::new(3, 2), |x, y| {
+            Rgba::new((1 + x + 3 * y) as u8, 0, 0, 255)
+        })
+    }
+
+    #[test]
+    fn it_crops() {
+        let image = numbered().crop(&Rect::new(1, 1, 3, 1));
+
+        assert_eq!(image.size, Point::new(3, 1));
+        assert_eq!(reds(&image), vec![5, 6, 0]);
+        assert_eq!(image.pixels[2], Rgba::transparent());
+    }
+
+    #[test]
+    fn it_flips() {
+        assert_eq!(reds(&numbered().flip_horizontal()), vec![3, 2, 1, 6, 5, 4]);
+        assert_eq!(reds(&numbered().flip_vertical()), vec![4, 5, 6, 1, 2, 3]);
+    }
+
+    #[test]
+    fn it_rotates_by_right_angles() {
+        let image = numbered().rotate90();
+        assert_eq!(image.size, Point::new(2, 3));
+        assert_eq!(reds(&image), vec![4, 1, 5, 2, 6, 3]);
+
+        assert_eq!(reds(&numbered().rotate180()), vec![6, 5, 4, 3, 2, 1]);
+        assert_eq!(reds(&numbered().rotate270()), vec![3, 6, 2, 5, 1, 4]);
+    }
+
+    #[test]
+    fn it_rotates_by_any_angle() {
+        let image = numbered().rotate(std::f32::consts::FRAC_PI_2);
+        assert_eq!(image.size, Point::new(2, 3));
+        assert_eq!(reds(&image), reds(&numbered().rotate90()));
+
+        let image = numbered().rotate(std::f32::consts::FRAC_PI_4);
+        assert_eq!(image.size, Point::new(4, 4));
+        assert_eq!(image.pixels[0], Rgba::transparent());
+    }
+
+    #[test]
+    fn it_scales_with_nearest_neighbor() {
+        let image = numbered().scale(&Point::new(6, 2), ScaleFilter::Nearest);
+        assert_eq!(reds(&image), vec![1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6]);
+
+        let image = numbered().scale(&Point::new(3, 1), ScaleFilter::Nearest);
+        assert_eq!(reds(&image), vec![4, 5, 6]);
+    }
+
+    #[test]
+    fn it_scales_bilinear_without_darkening_transparent_edges() {
+        let image = Image {
+            pixels: vec![Rgba::new(200, 0, 0, 255), Rgba::transparent()],
+            size: Point::new(2, 1),
+        };
+
+        let scaled = image.scale(&Point::new(1, 1), ScaleFilter::Bilinear);
+
+        assert_eq!(scaled.pixels, vec![Rgba::new(200, 0, 0, 128)]);
     }
 }
@@ -0,0 +1,228 @@
+use super::point::Point;
+use super::rect::Rect;
+use super::rgba::Rgba;
+
+/// How `Canvas::draw_image_ex` maps an image onto the canvas.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ImageDraw {
+    /// Where the image is drawn, it is scaled to fit.
+    pub dest: Rect,
+    /// The part of the image to draw, None for the whole image.
+    pub src: Option<Rect>,
+    /// Clockwise rotation in radians.
+    pub angle: f32,
+    /// The rotation center relative to `dest.pos`, None for the center of
+    /// `dest`.
+    pub pivot: Option<Point>,
+    pub flip_x: bool,
+    pub flip_y: bool,
+    /// Multiplies each pixel, e.g. a white tint with alpha 128 draws the
+    /// image half transparent.
+    pub tint: Rgba,
+}
+
+impl ImageDraw {
+    pub fn new(dest: Rect) -> Self {
+        Self {
+            dest,
+            src: None,
+            angle: 0.0,
+            pivot: None,
+            flip_x: false,
+            flip_y: false,
+            tint: Rgba::white(),
+        }
+    }
+
+    /// Draws into `size` pixels with the top left corner at `p`, the image is
+    /// unscaled if `size` is its size.
+    pub fn at(p: &Point, size: &Point) -> Self {
+        Self::new(Rect {
+            pos: p.clone(),
+            size: size.clone(),
+        })
+    }
+
+    pub fn src(self, src: Rect) -> Self {
+        Self {
+            src: Some(src),
+            ..self
+        }
+    }
+
+    pub fn rotate(self, angle: f32) -> Self {
+        Self { angle, ..self }
+    }
+
+    pub fn pivot(self, pivot: Point) -> Self {
+        Self {
+            pivot: Some(pivot),
+            ..self
+        }
+    }
+
+    pub fn flip(self, flip_x: bool, flip_y: bool) -> Self {
+        Self {
+            flip_x,
+            flip_y,
+            ..self
+        }
+    }
+
+    pub fn tint(self, tint: Rgba) -> Self {
+        Self { tint, ..self }
+    }
+
+    pub fn alpha(self, alpha: u8) -> Self {
+        Self {
+            tint: Rgba {
+                a: alpha,
+                ..self.tint.clone()
+            },
+            ..self
+        }
+    }
+
+    // the pivot in canvas coordinates
+    fn pivot_point(&self) -> (f32, f32) {
+        match &self.pivot {
+            Some(pivot) => (
+                (self.dest.pos.x + pivot.x) as f32,
+                (self.dest.pos.y + pivot.y) as f32,
+            ),
+            None => (
+                self.dest.pos.x as f32 + self.dest.size.width() as f32 / 2.0,
+                self.dest.pos.y as f32 + self.dest.size.height() as f32 / 2.0,
+            ),
+        }
+    }
+
+    /// The canvas pixels which the rotated destination rect may cover.
+    pub fn bounds(&self) -> Rect {
+        let (px, py) = self.pivot_point();
+        let (sin, cos) = self.angle.sin_cos();
+        let x0 = self.dest.pos.x as f32;
+        let y0 = self.dest.pos.y as f32;
+        let x1 = x0 + self.dest.size.width() as f32;
+        let y1 = y0 + self.dest.size.height() as f32;
+
+        let corners = [(x0, y0), (x1, y0), (x0, y1), (x1, y1)].map(|(x, y)| {
+            let (dx, dy) = (x - px, y - py);
+            (px + dx * cos - dy * sin, py + dx * sin + dy * cos)
+        });
+
+        let (min_x, max_x) = pixel_range(corners.map(|c| c.0));
+        let (min_y, max_y) = pixel_range(corners.map(|c| c.1));
+
+        Rect::new(min_x, min_y, max_x - min_x, max_y - min_y)
+    }
+
+    /// The source pixel shown at canvas pixel `p`, None if `p` is outside of
+    /// the destination.
+    pub fn source_point(&self, p: &Point, image_size: &Point) -> Option<Point> {
+        let dest_width = self.dest.size.width() as f32;
+        let dest_height = self.dest.size.height() as f32;
+        if dest_width <= 0.0 || dest_height <= 0.0 {
+            return None;
+        }
+
+        // rotate the pixel center back into the unrotated destination rect
+        let (px, py) = self.pivot_point();
+        let (sin, cos) = self.angle.sin_cos();
+        let dx = p.x as f32 + 0.5 - px;
+        let dy = p.y as f32 + 0.5 - py;
+        let u = px + dx * cos + dy * sin - self.dest.pos.x as f32;
+        let v = py - dx * sin + dy * cos - self.dest.pos.y as f32;
+
+        if u < 0.0 || v < 0.0 || u >= dest_width || v >= dest_height {
+            return None;
+        }
+
+        let u = if self.flip_x { dest_width - u } else { u };
+        let v = if self.flip_y { dest_height - v } else { v };
+
+        let src = self.src.clone().unwrap_or(Rect::from(image_size));
+        let sx = src.pos.x + (u * src.size.width() as f32 / dest_width) as i32;
+        let sy = src.pos.y + (v * src.size.height() as f32 / dest_height) as i32;
+        let sx = sx.min(src.pos.x + src.size.width() - 1);
+        let sy = sy.min(src.pos.y + src.size.height() - 1);
+
+        Some(Point::new(sx, sy))
+    }
+
+    pub fn apply_tint(&self, rgba: &Rgba) -> Rgba {
+        let multiply = |a: u8, b: u8| ((a as u32 * b as u32 + 127) / 255) as u8;
+
+        Rgba {
+            r: multiply(rgba.r, self.tint.r),
+            g: multiply(rgba.g, self.tint.g),
+            b: multiply(rgba.b, self.tint.b),
+            a: multiply(rgba.a, self.tint.a),
+        }
+    }
+}
+
+// the epsilon keeps rounding errors from adding a column or row
+fn pixel_range(values: [f32; 4]) -> (i32, i32) {
+    let min = values.into_iter().fold(f32::MAX, f32::min);
+    let max = values.into_iter().fold(f32::MIN, f32::max);
+    ((min + 1e-3).floor() as i32, (max - 1e-3).ceil() as i32)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn it_maps_scaled_and_flipped_destinations() {
+        let size = Point::new(2, 2);
+        let draw = ImageDraw::new(Rect::new(10, 10, 4, 4));
+
+        assert_eq!(draw.source_point(&Point::new(9, 10), &size), None);
+        assert_eq!(
+            draw.source_point(&Point::new(11, 12), &size),
+            Some(Point::new(0, 1))
+        );
+
+        let draw = draw.flip(true, false);
+        assert_eq!(
+            draw.source_point(&Point::new(11, 12), &size),
+            Some(Point::new(1, 1))
+        );
+    }
+
+    #[test]
+    fn it_maps_source_rects() {
+        let draw = ImageDraw::new(Rect::new(0, 0, 2, 1)).src(Rect::new(4, 2, 2, 1));
+        assert_eq!(
+            draw.source_point(&Point::new(1, 0), &Point::new(8, 8)),
+            Some(Point::new(5, 2))
+        );
+    }
+
+    #[test]
+    fn it_rotates_around_the_pivot() {
+        let size = Point::new(2, 1);
+        let draw = ImageDraw::new(Rect::new(0, 0, 2, 1))
+            .pivot(Point::new(0, 0))
+            .rotate(std::f32::consts::FRAC_PI_2);
+
+        assert_eq!(draw.bounds(), Rect::new(-1, 0, 1, 2));
+        assert_eq!(
+            draw.source_point(&Point::new(-1, 1), &size),
+            Some(Point::new(1, 0))
+        );
+    }
+
+    #[test]
+    fn it_tints() {
+        let draw = ImageDraw::new(Rect::new(0, 0, 1, 1))
+            .tint(Rgba::new(255, 0, 128, 255))
+            .alpha(128);
+
+        assert_eq!(
+            draw.apply_tint(&Rgba::new(200, 200, 200, 255)),
+            Rgba::new(200, 0, 100, 128)
+        );
+    }
+}
@@ -0,0 +1,481 @@
+use super::ansiesc::BRACKETED_PASTE_END;
+use super::ansiesc::BRACKETED_PASTE_START;
+use super::event::Event;
+use super::key::KeyCode;
+use super::key::KeyEvent;
+use super::key::KeyKind;
+use super::key::Modifiers;
+
+const ESC: u8 = 0x1b;
+
+// incomplete sequences longer than this are garbage and get dropped
+const MAX_SEQUENCE_LEN: usize = 64;
+
+/// Turns raw terminal input into events. Understands legacy control
+/// characters, the common CSI/SS3 key sequences including xterm style
+/// modifiers, the kitty keyboard protocol, bracketed paste and focus
+/// reporting.
+#[derive(Default)]
+pub struct InputParser {
+    buffer: Vec<u8>,
+    keyboard_enhancement_flags: Option<u8>,
+    paste: Option<Vec<u8>>,
+}
+
+enum Parsed {
+    Event(Event),
+    Ignored,
+    Incomplete,
+}
+
+impl InputParser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the flags reported by the terminal in response to a keyboard
+    /// enhancement query, once, after they were received.
+    pub fn take_keyboard_enhancement_flags(&mut self) -> Option<u8> {
+        self.keyboard_enhancement_flags.take()
+    }
+
+    /// Parses the bytes of one read from the terminal. Incomplete sequences
+    /// are kept until the next call, except for a single trailing escape
+    /// byte which is reported as the escape key.
+    pub fn feed(&mut self, bytes: &[u8]) -> Vec<Event> {
+        self.buffer.extend_from_slice(bytes);
+
+        let mut events = Vec::new();
+        let mut start = 0;
+
+        while start < self.buffer.len() {
+            if let Some(paste) = self.paste.as_mut() {
+                let rest = &self.buffer[start..];
+                let end = BRACKETED_PASTE_END.as_bytes();
+
+                match rest.windows(end.len()).position(|window| window == end) {
+                    Some(i) => {
+                        paste.extend_from_slice(&rest[..i]);
+                        start += i + end.len();
+                        events.push(Event::Paste(paste_text(paste)));
+                        self.paste = None;
+                        continue;
+                    }
+                    None => {
+                        // keep what could be the beginning of the end marker
+                        let len = rest.len().saturating_sub(end.len() - 1);
+                        paste.extend_from_slice(&rest[..len]);
+                        start += len;
+                        break;
+                    }
+                }
+            }
+
+            let (parsed, len) = self.parse(start);
+
+            match parsed {
+                Parsed::Event(event) => events.push(event),
+                Parsed::Ignored => {}
+                Parsed::Incomplete => {
+                    let rest = &self.buffer[start..];
+                    if rest == [ESC] {
+                        events.push(Event::Key(KeyEvent::from(KeyCode::Esc)));
+                        start += 1;
+                    } else if rest.len() > MAX_SEQUENCE_LEN {
+                        start += 1;
+                    }
+                    break;
+                }
+            }
+
+            start += len;
+        }
+
+        self.buffer.drain(..start.min(self.buffer.len()));
+
+        events
+    }
+
+    fn parse(&mut self, start: usize) -> (Parsed, usize) {
+        let bytes = &self.buffer[start..];
+
+        if bytes[0] != ESC {
+            return match parse_char(bytes) {
+                Some((event, len)) => (Parsed::Event(Event::Key(event)), len),
+                None => (Parsed::Incomplete, 0),
+            };
+        }
+
+        match bytes.get(1) {
+            None => (Parsed::Incomplete, 0),
+            Some(b'[') => self.parse_csi(start),
+            Some(b'O') => match bytes.get(2) {
+                None => (Parsed::Incomplete, 0),
+                Some(&b) => (key_parsed(parse_ss3(b)), 3),
+            },
+            Some(_) => match parse_char(&bytes[1..]) {
+                Some((event, len)) => {
+                    let modifiers = event.modifiers | Modifiers::ALT;
+                    let event = KeyEvent::new(event.code, modifiers);
+                    (Parsed::Event(Event::Key(event)), len + 1)
+                }
+                None => (Parsed::Incomplete, 0),
+            },
+        }
+    }
+
+    fn parse_csi(&mut self, start: usize) -> (Parsed, usize) {
+        let bytes = &self.buffer[start + 2..];
+
+        let Some(final_index) = bytes.iter().position(|b| (0x40..=0x7e).contains(b)) else {
+            return (Parsed::Incomplete, 0);
+        };
+
+        let len = final_index + 3;
+        let final_byte = bytes[final_index];
+        let Ok(params) = std::str::from_utf8(&bytes[..final_index]) else {
+            return (Parsed::Ignored, len);
+        };
+
+        if self.buffer[start..start + len] == *BRACKETED_PASTE_START.as_bytes() {
+            self.paste = Some(Vec::new());
+            return (Parsed::Ignored, len);
+        }
+
+        match (params, final_byte) {
+            ("", b'I') => return (Parsed::Event(Event::FocusGained), len),
+            ("", b'O') => return (Parsed::Event(Event::FocusLost), len),
+            _ => {}
+        }
+
+        if let Some(flags) = params.strip_prefix('?') {
+            if final_byte == b'u' {
+                self.keyboard_enhancement_flags = Some(flags.parse().unwrap_or(0));
+            }
+            return (Parsed::Ignored, len);
+        }
+
+        (key_parsed(parse_csi_key(params, final_byte)), len)
+    }
+}
+
+// terminals send carriage returns for line breaks
+fn paste_text(bytes: &[u8]) -> String {
+    String::from_utf8_lossy(bytes)
+        .replace("\r\n", "\n")
+        .replace('\r', "\n")
+}
+
+fn key_parsed(event: Option<KeyEvent>) -> Parsed {
+    match event {
+        Some(event) => Parsed::Event(Event::Key(event)),
+        None => Parsed::Ignored,
+    }
+}
+
+fn parse_char(bytes: &[u8]) -> Option<(KeyEvent, usize)> {
+    let event = |code: KeyCode, modifiers: Modifiers| KeyEvent::new(code, modifiers);
+
+    let key = match bytes[0] {
+        b'\r' | b'\n' => event(KeyCode::Enter, Modifiers::NONE),
+        b'\t' => event(KeyCode::Tab, Modifiers::NONE),
+        0x7f | 0x08 => event(KeyCode::Backspace, Modifiers::NONE),
+        ESC => event(KeyCode::Esc, Modifiers::NONE),
+        0x00 => event(KeyCode::Char(' '), Modifiers::CTRL),
+        b @ 0x01..=0x1a => event(KeyCode::Char((b'a' + b - 1) as char), Modifiers::CTRL),
+        b @ 0x1c..=0x1f => event(KeyCode::Char((b'4' + b - 0x1c) as char), Modifiers::CTRL),
+        _ => {
+            let len = utf8_len(bytes[0]);
+            if bytes.len() < len {
+                return None;
+            }
+
+            let ch = std::str::from_utf8(&bytes[..len])
+                .ok()
+                .and_then(|s| s.chars().next())
+                .unwrap_or(char::REPLACEMENT_CHARACTER);
+
+            return Some((event(KeyCode::Char(ch), Modifiers::NONE), len));
+        }
+    };
+
+    Some((key, 1))
+}
+
+fn utf8_len(lead: u8) -> usize {
+    match lead {
+        0xf0..=0xf7 => 4,
+        0xe0..=0xef => 3,
+        0xc0..=0xdf => 2,
+        _ => 1,
+    }
+}
+
+fn parse_ss3(b: u8) -> Option<KeyEvent> {
+    let code = match b {
+        b'A' => KeyCode::Up,
+        b'B' => KeyCode::Down,
+        b'C' => KeyCode::Right,
+        b'D' => KeyCode::Left,
+        b'H' => KeyCode::Home,
+        b'F' => KeyCode::End,
+        b'P' => KeyCode::F(1),
+        b'Q' => KeyCode::F(2),
+        b'R' => KeyCode::F(3),
+        b'S' => KeyCode::F(4),
+        _ => return None,
+    };
+
+    Some(KeyEvent::from(code))
+}
+
+// Parameters are separated by ';', sub-parameters by ':'.
+fn param(params: &str, index: usize, sub_index: usize) -> Option<u32> {
+    params
+        .split(';')
+        .nth(index)?
+        .split(':')
+        .nth(sub_index)?
+        .parse()
+        .ok()
+}
+
+// The modifier parameter is encoded as 1 + modifier bits, optionally
+// followed by the event type as sub-parameter.
+fn parse_modifiers_and_kind(params: &str) -> (Modifiers, KeyKind) {
+    let bits = param(params, 1, 0).unwrap_or(1).saturating_sub(1);
+    let modifiers = Modifiers::from_bits(bits as u8);
+
+    let kind = match param(params, 1, 1) {
+        Some(2) => KeyKind::Repeat,
+        Some(3) => KeyKind::Release,
+        _ => KeyKind::Press,
+    };
+
+    (modifiers, kind)
+}
+
+fn parse_csi_key(params: &str, final_byte: u8) -> Option<KeyEvent> {
+    let (modifiers, kind) = parse_modifiers_and_kind(params);
+
+    let code = match final_byte {
+        b'A' => KeyCode::Up,
+        b'B' => KeyCode::Down,
+        b'C' => KeyCode::Right,
+        b'D' => KeyCode::Left,
+        b'H' => KeyCode::Home,
+        b'F' => KeyCode::End,
+        b'P' => KeyCode::F(1),
+        b'Q' => KeyCode::F(2),
+        b'S' => KeyCode::F(4),
+        b'Z' => {
+            let modifiers = modifiers | Modifiers::SHIFT;
+            return Some(KeyEvent::new(KeyCode::Tab, modifiers).with_kind(kind));
+        }
+        b'~' => match param(params, 0, 0)? {
+            1 | 7 => KeyCode::Home,
+            2 => KeyCode::Insert,
+            3 => KeyCode::Delete,
+            4 | 8 => KeyCode::End,
+            5 => KeyCode::PageUp,
+            6 => KeyCode::PageDown,
+            n @ 11..=15 => KeyCode::F(n as u8 - 10),
+            n @ 17..=21 => KeyCode::F(n as u8 - 11),
+            n @ 23..=24 => KeyCode::F(n as u8 - 12),
+            _ => return None,
+        },
+        b'u' => return parse_kitty_key(params, modifiers, kind),
+        _ => return None,
+    };
+
+    Some(KeyEvent::new(code, modifiers).with_kind(kind))
+}
+
+// CSI unicode-key-code:shifted-key:base-layout-key ; modifiers:event-type u
+fn parse_kitty_key(params: &str, modifiers: Modifiers, kind: KeyKind) -> Option<KeyEvent> {
+    let key_code = param(params, 0, 0)?;
+
+    let code = match key_code {
+        9 => KeyCode::Tab,
+        13 => KeyCode::Enter,
+        27 => KeyCode::Esc,
+        127 => KeyCode::Backspace,
+        n @ 57376..=57398 => KeyCode::F((n - 57376 + 13) as u8),
+        n @ 57399..=57408 => KeyCode::Char(char::from_digit(n - 57399, 10)?),
+        57409 => KeyCode::Char('.'),
+        57410 => KeyCode::Char('/'),
+        57411 => KeyCode::Char('*'),
+        57412 => KeyCode::Char('-'),
+        57413 => KeyCode::Char('+'),
+        57414 => KeyCode::Enter,
+        57415 => KeyCode::Char('='),
+        57417 => KeyCode::Left,
+        57418 => KeyCode::Right,
+        57419 => KeyCode::Up,
+        57420 => KeyCode::Down,
+        57421 => KeyCode::PageUp,
+        57422 => KeyCode::PageDown,
+        57423 => KeyCode::Home,
+        57424 => KeyCode::End,
+        57425 => KeyCode::Insert,
+        57426 => KeyCode::Delete,
+        // remaining private use codes are lock and modifier keys
+        57344..=63743 => return None,
+        n => {
+            let shifted = param(params, 0, 1).and_then(char::from_u32);
+            let ch = char::from_u32(n)?;
+
+            if !modifiers.contains(Modifiers::SHIFT) {
+                KeyCode::Char(ch)
+            } else if let Some(shifted) = shifted {
+                let modifiers = modifiers.without(Modifiers::SHIFT);
+                return Some(KeyEvent::new(KeyCode::Char(shifted), modifiers).with_kind(kind));
+            } else if ch.is_ascii_lowercase() {
+                let modifiers = modifiers.without(Modifiers::SHIFT);
+                let upper = ch.to_ascii_uppercase();
+                return Some(KeyEvent::new(KeyCode::Char(upper), modifiers).with_kind(kind));
+            } else {
+                KeyCode::Char(ch)
+            }
+        }
+    };
+
+    Some(KeyEvent::new(code, modifiers).with_kind(kind))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn key(code: KeyCode, modifiers: Modifiers) -> Event {
+        Event::Key(KeyEvent::new(code, modifiers))
+    }
+
+    fn keys(input: &[u8]) -> Vec<Event> {
+        InputParser::new().feed(input)
+    }
+
+    #[test]
+    fn it_parses_plain_and_control_characters() {
+        assert_eq!(
+            keys(b"a\r\x03\x7f"),
+            vec![
+                key(KeyCode::Char('a'), Modifiers::NONE),
+                key(KeyCode::Enter, Modifiers::NONE),
+                key(KeyCode::Char('c'), Modifiers::CTRL),
+                key(KeyCode::Backspace, Modifiers::NONE),
+            ]
+        );
+    }
+
+    #[test]
+    fn it_parses_utf8_split_across_reads() {
+        let mut parser = InputParser::new();
+        let bytes = "ä".as_bytes();
+
+        assert_eq!(parser.feed(&bytes[..1]), vec![]);
+        assert_eq!(
+            parser.feed(&bytes[1..]),
+            vec![key(KeyCode::Char('ä'), Modifiers::NONE)]
+        );
+    }
+
+    #[test]
+    fn it_parses_alt_and_a_trailing_escape() {
+        assert_eq!(
+            keys(b"\x1bx\x1b"),
+            vec![
+                key(KeyCode::Char('x'), Modifiers::ALT),
+                key(KeyCode::Esc, Modifiers::NONE),
+            ]
+        );
+    }
+
+    #[test]
+    fn it_keeps_incomplete_sequences_until_the_next_read() {
+        let mut parser = InputParser::new();
+
+        assert_eq!(parser.feed(b"\x1b[1;"), vec![]);
+        assert_eq!(parser.feed(b"2A"), vec![key(KeyCode::Up, Modifiers::SHIFT)]);
+    }
+
+    #[test]
+    fn it_parses_legacy_sequences_with_modifiers() {
+        assert_eq!(
+            keys(b"\x1b[A\x1bOP\x1b[1;5D\x1b[3~\x1b[5;3~\x1b[Z\x1b[24~"),
+            vec![
+                key(KeyCode::Up, Modifiers::NONE),
+                key(KeyCode::F(1), Modifiers::NONE),
+                key(KeyCode::Left, Modifiers::CTRL),
+                key(KeyCode::Delete, Modifiers::NONE),
+                key(KeyCode::PageUp, Modifiers::ALT),
+                key(KeyCode::Tab, Modifiers::SHIFT),
+                key(KeyCode::F(12), Modifiers::NONE),
+            ]
+        );
+    }
+
+    #[test]
+    fn it_parses_kitty_key_events() {
+        let release =
+            KeyEvent::new(KeyCode::Char('w'), Modifiers::NONE).with_kind(KeyKind::Release);
+        let repeat = KeyEvent::new(KeyCode::Up, Modifiers::NONE).with_kind(KeyKind::Repeat);
+
+        assert_eq!(
+            keys(
+                b"\x1b[119u\x1b[119;1:3u\x1b[97:65;2u\x1b[97;6u\x1b[1;1:2A\x1b[115;9u\x1b[57441;2u"
+            ),
+            vec![
+                key(KeyCode::Char('w'), Modifiers::NONE),
+                Event::Key(release),
+                key(KeyCode::Char('A'), Modifiers::NONE),
+                key(KeyCode::Char('A'), Modifiers::CTRL),
+                Event::Key(repeat),
+                key(KeyCode::Char('s'), Modifiers::SUPER),
+            ]
+        );
+    }
+
+    #[test]
+    fn it_reports_keyboard_enhancement_flags() {
+        let mut parser = InputParser::new();
+
+        assert_eq!(parser.feed(b"\x1b[?15u"), vec![]);
+        assert_eq!(parser.take_keyboard_enhancement_flags(), Some(15));
+        assert_eq!(parser.take_keyboard_enhancement_flags(), None);
+    }
+
+    #[test]
+    fn it_parses_bracketed_paste_split_across_reads() {
+        let mut parser = InputParser::new();
+
+        assert_eq!(
+            parser.feed(b"x\x1b[200~q\x1b[A"),
+            vec![key(KeyCode::Char('x'), Modifiers::NONE)]
+        );
+        assert_eq!(parser.feed(b"line\r\n\x1b[20"), vec![]);
+        assert_eq!(
+            parser.feed(b"1~y"),
+            vec![
+                Event::Paste("q\x1b[Aline\n".to_string()),
+                key(KeyCode::Char('y'), Modifiers::NONE),
+            ]
+        );
+    }
+
+    #[test]
+    fn it_parses_focus_events() {
+        assert_eq!(
+            keys(b"\x1b[O\x1b[I"),
+            vec![Event::FocusLost, Event::FocusGained]
+        );
+    }
+
+    #[test]
+    fn it_ignores_unknown_sequences() {
+        assert_eq!(
+            keys(b"\x1b[?62;22c\x1b[12;40Rq"),
+            vec![key(KeyCode::Char('q'), Modifiers::NONE)]
+        );
+    }
+}
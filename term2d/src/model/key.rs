@@ -1 +1,253 @@
-pub type Key = termion::event::Key;
+use std::ops::BitOr;
+
+/// A simplified view of a key press, convenient for pattern matching.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Key {
+    Backspace,
+    Left,
+    Right,
+    Up,
+    Down,
+    Home,
+    End,
+    PageUp,
+    PageDown,
+    BackTab,
+    Delete,
+    Insert,
+    F(u8),
+    Char(char),
+    Alt(char),
+    Ctrl(char),
+    Null,
+    Esc,
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum KeyCode {
+    Char(char),
+    F(u8),
+    Backspace,
+    Enter,
+    Tab,
+    Esc,
+    Left,
+    Right,
+    Up,
+    Down,
+    Home,
+    End,
+    PageUp,
+    PageDown,
+    Insert,
+    Delete,
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum KeyKind {
+    #[default]
+    Press,
+    Repeat,
+    Release,
+}
+
+/// Modifier bits as used by the kitty keyboard protocol.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct Modifiers(u8);
+
+impl Modifiers {
+    pub const NONE: Self = Self(0);
+    pub const SHIFT: Self = Self(1);
+    pub const ALT: Self = Self(2);
+    pub const CTRL: Self = Self(4);
+    pub const SUPER: Self = Self(8);
+    pub const HYPER: Self = Self(16);
+    pub const META: Self = Self(32);
+
+    const ALL: u8 = 63;
+
+    /// Ignores bits which are not modifiers like caps lock and num lock.
+    pub const fn from_bits(bits: u8) -> Self {
+        Self(bits & Self::ALL)
+    }
+
+    pub const fn bits(&self) -> u8 {
+        self.0
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    pub const fn contains(&self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    pub const fn without(&self, other: Self) -> Self {
+        Self(self.0 & !other.0)
+    }
+}
+
+impl BitOr for Modifiers {
+    type Output = Modifiers;
+
+    fn bitor(self, rhs: Self) -> Self::Output {
+        Self(self.0 | rhs.0)
+    }
+}
+
+/// A key event as reported by the terminal. Shift is folded into `Char`
+/// codes, i.e. shift+a is reported as `Char('A')` without modifiers.
+/// Repeat and release events are only reported if the terminal supports the
+/// kitty keyboard protocol and it was enabled with
+/// `AppBuilder::keyboard_enhancement`.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct KeyEvent {
+    pub code: KeyCode,
+    pub modifiers: Modifiers,
+    pub kind: KeyKind,
+}
+
+impl KeyEvent {
+    pub const fn new(code: KeyCode, modifiers: Modifiers) -> Self {
+        Self {
+            code,
+            modifiers,
+            kind: KeyKind::Press,
+        }
+    }
+
+    pub const fn with_kind(&self, kind: KeyKind) -> Self {
+        Self {
+            code: self.code,
+            modifiers: self.modifiers,
+            kind,
+        }
+    }
+
+    pub fn is_press(&self) -> bool {
+        self.kind == KeyKind::Press
+    }
+
+    pub fn is_repeat(&self) -> bool {
+        self.kind == KeyKind::Repeat
+    }
+
+    pub fn is_release(&self) -> bool {
+        self.kind == KeyKind::Release
+    }
+
+    /// Returns the simplified key, modifiers which it cannot express are
+    /// dropped.
+    pub fn key(&self) -> Key {
+        let ctrl = self.modifiers.contains(Modifiers::CTRL);
+        let alt = self.modifiers.contains(Modifiers::ALT);
+        let shift = self.modifiers.contains(Modifiers::SHIFT);
+
+        let ch = match self.code {
+            KeyCode::Char(ch) => ch,
+            KeyCode::Enter => '\n',
+            KeyCode::Tab if shift => return Key::BackTab,
+            KeyCode::Tab => '\t',
+            KeyCode::F(n) => return Key::F(n),
+            KeyCode::Backspace => return Key::Backspace,
+            KeyCode::Esc => return Key::Esc,
+            KeyCode::Left => return Key::Left,
+            KeyCode::Right => return Key::Right,
+            KeyCode::Up => return Key::Up,
+            KeyCode::Down => return Key::Down,
+            KeyCode::Home => return Key::Home,
+            KeyCode::End => return Key::End,
+            KeyCode::PageUp => return Key::PageUp,
+            KeyCode::PageDown => return Key::PageDown,
+            KeyCode::Insert => return Key::Insert,
+            KeyCode::Delete => return Key::Delete,
+        };
+
+        if ctrl {
+            Key::Ctrl(ch)
+        } else if alt {
+            Key::Alt(ch)
+        } else {
+            Key::Char(ch)
+        }
+    }
+}
+
+impl From<KeyCode> for KeyEvent {
+    fn from(code: KeyCode) -> Self {
+        Self::new(code, Modifiers::NONE)
+    }
+}
+
+impl From<Key> for KeyEvent {
+    fn from(key: Key) -> Self {
+        let code = match key {
+            Key::Backspace => KeyCode::Backspace,
+            Key::Left => KeyCode::Left,
+            Key::Right => KeyCode::Right,
+            Key::Up => KeyCode::Up,
+            Key::Down => KeyCode::Down,
+            Key::Home => KeyCode::Home,
+            Key::End => KeyCode::End,
+            Key::PageUp => KeyCode::PageUp,
+            Key::PageDown => KeyCode::PageDown,
+            Key::BackTab => return Self::new(KeyCode::Tab, Modifiers::SHIFT),
+            Key::Delete => KeyCode::Delete,
+            Key::Insert => KeyCode::Insert,
+            Key::F(n) => KeyCode::F(n),
+            Key::Char('\n') => KeyCode::Enter,
+            Key::Char('\t') => KeyCode::Tab,
+            Key::Char(ch) => KeyCode::Char(ch),
+            Key::Alt(ch) => return Self::new(KeyCode::Char(ch), Modifiers::ALT),
+            Key::Ctrl(ch) => return Self::new(KeyCode::Char(ch), Modifiers::CTRL),
+            Key::Null => return Self::new(KeyCode::Char(' '), Modifiers::CTRL),
+            Key::Esc => KeyCode::Esc,
+        };
+
+        Self::from(code)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn it_converts_key_events_to_simple_keys() {
+        let ctrl_c = KeyEvent::new(KeyCode::Char('c'), Modifiers::CTRL);
+        let alt_x = KeyEvent::new(KeyCode::Char('x'), Modifiers::ALT);
+        let shift_tab = KeyEvent::new(KeyCode::Tab, Modifiers::SHIFT);
+        let shift_up = KeyEvent::new(KeyCode::Up, Modifiers::SHIFT);
+
+        assert_eq!(ctrl_c.key(), Key::Ctrl('c'));
+        assert_eq!(alt_x.key(), Key::Alt('x'));
+        assert_eq!(shift_tab.key(), Key::BackTab);
+        assert_eq!(shift_up.key(), Key::Up);
+        assert_eq!(KeyEvent::from(KeyCode::Enter).key(), Key::Char('\n'));
+    }
+
+    #[test]
+    fn it_converts_simple_keys_to_key_events() {
+        for key in [
+            Key::Ctrl('c'),
+            Key::Alt('x'),
+            Key::BackTab,
+            Key::Char('\n'),
+            Key::Char('A'),
+            Key::F(5),
+            Key::Esc,
+        ] {
+            assert_eq!(KeyEvent::from(key).key(), key);
+        }
+    }
+
+    #[test]
+    fn it_combines_modifiers() {
+        let modifiers = Modifiers::CTRL | Modifiers::SHIFT;
+        assert!(modifiers.contains(Modifiers::CTRL));
+        assert!(!modifiers.contains(Modifiers::ALT));
+        assert_eq!(modifiers.without(Modifiers::SHIFT), Modifiers::CTRL);
+        assert_eq!(Modifiers::from_bits(64 + 4), Modifiers::CTRL);
+    }
+}
@@ -0,0 +1,597 @@
+use std::fmt::Display;
+use std::fs::read_to_string;
+use std::path::Path;
+use std::str::FromStr;
+
+use super::key::KeyCode;
+use super::key::KeyEvent;
+use super::key::KeyKind;
+use super::key::Modifiers;
+
+/// Bindings of this mode are active in every mode.
+pub const GLOBAL_MODE: &str = "global";
+
+const MODIFIER_NAMES: [(&str, Modifiers); 6] = [
+    ("ctrl", Modifiers::CTRL),
+    ("alt", Modifiers::ALT),
+    ("shift", Modifiers::SHIFT),
+    ("super", Modifiers::SUPER),
+    ("hyper", Modifiers::HYPER),
+    ("meta", Modifiers::META),
+];
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Binding<A> {
+    pub keys: Vec<KeyEvent>,
+    pub action: A,
+    pub description: String,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum KeyMapResult<A> {
+    /// A binding matched, the pending keys are consumed.
+    Action(A),
+    /// The keys fed so far are the beginning of a longer binding.
+    Pending,
+    /// No binding matched.
+    Unbound,
+}
+
+#[derive(Debug)]
+pub enum KeyMapError {
+    Io(std::io::Error),
+    Parse { line: usize, message: String },
+}
+
+impl Display for KeyMapError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            KeyMapError::Io(error) => write!(f, "{error}"),
+            KeyMapError::Parse { line, message } => write!(f, "line {line}: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for KeyMapError {}
+
+impl From<std::io::Error> for KeyMapError {
+    fn from(error: std::io::Error) -> Self {
+        KeyMapError::Io(error)
+    }
+}
+
+/// Maps keys and key sequences to app-defined actions, grouped by modes.
+///
+/// Keys are fed one at a time with `feed`, which keeps track of partially
+/// typed sequences like `g g`.
+#[derive(Clone, Debug)]
+pub struct KeyMap<A> {
+    mode: String,
+    modes: Vec<(String, Vec<Binding<A>>)>,
+    pending: Vec<KeyEvent>,
+}
+
+impl<A> Default for KeyMap<A> {
+    fn default() -> Self {
+        Self {
+            mode: GLOBAL_MODE.to_string(),
+            modes: Vec::new(),
+            pending: Vec::new(),
+        }
+    }
+}
+
+impl<A: Clone> KeyMap<A> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn mode(&self) -> &str {
+        &self.mode
+    }
+
+    /// Switches the active mode and discards pending keys.
+    pub fn set_mode(&mut self, mode: &str) {
+        self.mode = mode.to_string();
+        self.pending.clear();
+    }
+
+    pub fn pending(&self) -> &[KeyEvent] {
+        &self.pending
+    }
+
+    pub fn bind(&mut self, mode: &str, keys: Vec<KeyEvent>, action: A, description: &str) {
+        let binding = Binding {
+            keys: keys
+                .iter()
+                .map(|key| key.with_kind(KeyKind::Press))
+                .collect(),
+            action,
+            description: description.to_string(),
+        };
+
+        match self.modes.iter_mut().find(|(name, _)| name == mode) {
+            Some((_, bindings)) => {
+                bindings.retain(|b| b.keys != binding.keys);
+                bindings.push(binding);
+            }
+            None => self.modes.push((mode.to_string(), vec![binding])),
+        }
+    }
+
+    /// Binds a key sequence in the notation of `parse_keys`, e.g. `"ctrl-w h"`.
+    pub fn bind_str(
+        &mut self,
+        mode: &str,
+        keys: &str,
+        action: A,
+        description: &str,
+    ) -> Result<(), KeyMapError> {
+        let keys = parse_keys(keys).map_err(|message| KeyMapError::Parse { line: 0, message })?;
+        self.bind(mode, keys, action, description);
+        Ok(())
+    }
+
+    /// Bindings of the active mode followed by the global bindings which are
+    /// not shadowed by them.
+    pub fn active_bindings(&self) -> Vec<&Binding<A>> {
+        let mut active: Vec<&Binding<A>> = self.bindings(&self.mode).iter().collect();
+
+        if self.mode != GLOBAL_MODE {
+            for binding in self.bindings(GLOBAL_MODE) {
+                if active.iter().all(|b| b.keys != binding.keys) {
+                    active.push(binding);
+                }
+            }
+        }
+
+        active
+    }
+
+    /// Looks up a complete key sequence in the active mode without touching
+    /// the pending keys. The kind of the key events is ignored.
+    pub fn lookup(&self, keys: &[KeyEvent]) -> KeyMapResult<A> {
+        let active = self.active_bindings();
+        let keys: Vec<KeyEvent> = keys
+            .iter()
+            .map(|key| key.with_kind(KeyKind::Press))
+            .collect();
+        let keys = keys.as_slice();
+
+        if let Some(binding) = active.iter().find(|b| b.keys == keys) {
+            return KeyMapResult::Action(binding.action.clone());
+        }
+
+        if active
+            .iter()
+            .any(|b| b.keys.len() > keys.len() && b.keys.starts_with(keys))
+        {
+            return KeyMapResult::Pending;
+        }
+
+        KeyMapResult::Unbound
+    }
+
+    /// Feeds the next key. If a pending sequence cannot be completed by the
+    /// key, the sequence is dropped and the key is looked up on its own.
+    /// Key releases are ignored, repeats count as presses.
+    pub fn feed(&mut self, key: &KeyEvent) -> KeyMapResult<A> {
+        if key.is_release() {
+            return KeyMapResult::Unbound;
+        }
+
+        let key = &key.with_kind(KeyKind::Press);
+        self.pending.push(*key);
+
+        let mut result = self.lookup(&self.pending);
+        if matches!(result, KeyMapResult::Unbound) && self.pending.len() > 1 {
+            self.pending = vec![*key];
+            result = self.lookup(&self.pending);
+        }
+
+        if !matches!(result, KeyMapResult::Pending) {
+            self.pending.clear();
+        }
+
+        result
+    }
+
+    fn bindings(&self, mode: &str) -> &[Binding<A>] {
+        self.modes
+            .iter()
+            .find(|(name, _)| name == mode)
+            .map(|(_, bindings)| bindings.as_slice())
+            .unwrap_or_default()
+    }
+}
+
+impl<A: Clone + FromStr> KeyMap<A> {
+    /// Parses bindings from a simple line based format:
+    ///
+    /// ```text
+    /// # comment
+    /// q = quit            # bindings before any section are global
+    /// ctrl-c = quit
+    ///
+    /// [normal]
+    /// g g = top           # go to the top
+    /// i = insert_mode
+    /// ```
+    ///
+    /// Actions are parsed with `FromStr`, trailing comments become the
+    /// description of a binding.
+    pub fn parse(text: &str) -> Result<Self, KeyMapError> {
+        let mut keymap = Self::new();
+        let mut mode = GLOBAL_MODE.to_string();
+
+        for (i, raw_line) in text.lines().enumerate() {
+            let line_number = i + 1;
+            let parse_error = |message: String| KeyMapError::Parse {
+                line: line_number,
+                message,
+            };
+
+            let (line, comment) = split_comment(raw_line);
+            if line.is_empty() {
+                continue;
+            }
+
+            if let Some(section) = line.strip_prefix('[') {
+                let Some(section) = section.strip_suffix(']') else {
+                    return Err(parse_error(format!("unterminated section '{line}'")));
+                };
+                mode = section.trim().to_string();
+                continue;
+            }
+
+            let Some((keys, action)) = line.rsplit_once('=') else {
+                return Err(parse_error(format!(
+                    "expected 'keys = action', got '{line}'"
+                )));
+            };
+
+            let action_name = action.trim();
+            let Ok(action) = A::from_str(action_name) else {
+                return Err(parse_error(format!("unknown action '{action_name}'")));
+            };
+
+            let keys = parse_keys(keys).map_err(parse_error)?;
+            let description = comment.unwrap_or(action_name);
+            keymap.bind(&mode, keys, action, description);
+        }
+
+        Ok(keymap)
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, KeyMapError> {
+        Self::parse(&read_to_string(path)?)
+    }
+}
+
+// A '#' only starts a comment at the beginning of a line or after whitespace,
+// so that it can still be bound as a key.
+fn split_comment(line: &str) -> (&str, Option<&str>) {
+    let bytes = line.as_bytes();
+    for (i, &b) in bytes.iter().enumerate() {
+        if b == b'#' && (i == 0 || bytes[i - 1].is_ascii_whitespace()) {
+            let is_key = line[i + 1..].trim_start().starts_with('=');
+            if !is_key {
+                let comment = line[i + 1..].trim();
+                let comment = if comment.is_empty() {
+                    None
+                } else {
+                    Some(comment)
+                };
+                return (line[..i].trim(), comment);
+            }
+        }
+    }
+
+    (line.trim(), None)
+}
+
+/// Parses a whitespace separated key sequence, e.g. `"g g"` or `"ctrl-x ctrl-s"`.
+pub fn parse_keys(keys: &str) -> Result<Vec<KeyEvent>, String> {
+    let keys: Vec<KeyEvent> = keys
+        .split_whitespace()
+        .map(parse_key)
+        .collect::<Result<_, _>>()?;
+
+    if keys.is_empty() {
+        return Err("empty key sequence".to_string());
+    }
+
+    Ok(keys)
+}
+
+/// Parses a single key like `a`, `enter`, `f5`, `ctrl-c` or `ctrl-shift-up`.
+/// Modifiers are `ctrl`, `alt`, `shift`, `super`, `hyper` and `meta`.
+pub fn parse_key(key: &str) -> Result<KeyEvent, String> {
+    let mut modifiers = Modifiers::NONE;
+    let mut name = key;
+
+    'prefixes: loop {
+        for (prefix, modifier) in MODIFIER_NAMES {
+            let has_prefix = name
+                .get(..prefix.len() + 1)
+                .is_some_and(|p| p.eq_ignore_ascii_case(&format!("{prefix}-")));
+
+            if has_prefix && name.len() > prefix.len() + 1 {
+                modifiers = modifiers | modifier;
+                name = &name[prefix.len() + 1..];
+                continue 'prefixes;
+            }
+        }
+        break;
+    }
+
+    let mut chars = name.chars();
+    if let (Some(ch), None) = (chars.next(), chars.next()) {
+        let shift = modifiers.contains(Modifiers::SHIFT);
+        let ch = if shift { ch.to_ascii_uppercase() } else { ch };
+        if shift && ch.is_ascii_uppercase() {
+            modifiers = modifiers.without(Modifiers::SHIFT);
+        }
+        return Ok(KeyEvent::new(KeyCode::Char(ch), modifiers));
+    }
+
+    let code = match name.to_lowercase().as_str() {
+        "space" => KeyCode::Char(' '),
+        "enter" => KeyCode::Enter,
+        "tab" => KeyCode::Tab,
+        "backtab" => {
+            modifiers = modifiers | Modifiers::SHIFT;
+            KeyCode::Tab
+        }
+        "esc" => KeyCode::Esc,
+        "backspace" => KeyCode::Backspace,
+        "delete" => KeyCode::Delete,
+        "insert" => KeyCode::Insert,
+        "home" => KeyCode::Home,
+        "end" => KeyCode::End,
+        "pageup" => KeyCode::PageUp,
+        "pagedown" => KeyCode::PageDown,
+        "up" => KeyCode::Up,
+        "down" => KeyCode::Down,
+        "left" => KeyCode::Left,
+        "right" => KeyCode::Right,
+        lower => match lower.strip_prefix('f').map(u8::from_str) {
+            Some(Ok(n)) if (1..=35).contains(&n) => KeyCode::F(n),
+            _ => return Err(format!("unknown key '{key}'")),
+        },
+    };
+
+    Ok(KeyEvent::new(code, modifiers))
+}
+
+/// The notation accepted by `parse_key`.
+pub fn key_name(key: &KeyEvent) -> String {
+    let mut name = String::new();
+
+    for (prefix, modifier) in MODIFIER_NAMES {
+        if key.modifiers.contains(modifier) {
+            name.push_str(prefix);
+            name.push('-');
+        }
+    }
+
+    match key.code {
+        KeyCode::Char(' ') => name.push_str("space"),
+        KeyCode::Char(ch) => name.push(ch),
+        KeyCode::F(n) => name.push_str(&format!("f{n}")),
+        KeyCode::Backspace => name.push_str("backspace"),
+        KeyCode::Enter => name.push_str("enter"),
+        KeyCode::Tab => name.push_str("tab"),
+        KeyCode::Esc => name.push_str("esc"),
+        KeyCode::Left => name.push_str("left"),
+        KeyCode::Right => name.push_str("right"),
+        KeyCode::Up => name.push_str("up"),
+        KeyCode::Down => name.push_str("down"),
+        KeyCode::Home => name.push_str("home"),
+        KeyCode::End => name.push_str("end"),
+        KeyCode::PageUp => name.push_str("pageup"),
+        KeyCode::PageDown => name.push_str("pagedown"),
+        KeyCode::Insert => name.push_str("insert"),
+        KeyCode::Delete => name.push_str("delete"),
+    }
+
+    name
+}
+
+pub fn keys_name(keys: &[KeyEvent]) -> String {
+    keys.iter().map(key_name).collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[derive(Clone, Debug, PartialEq)]
+    enum Action {
+        Quit,
+        Top,
+        Down,
+        Insert,
+        Normal,
+    }
+
+    impl FromStr for Action {
+        type Err = ();
+
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            match s {
+                "quit" => Ok(Action::Quit),
+                "top" => Ok(Action::Top),
+                "down" => Ok(Action::Down),
+                "insert" => Ok(Action::Insert),
+                "normal" => Ok(Action::Normal),
+                _ => Err(()),
+            }
+        }
+    }
+
+    const BINDINGS: &str = "
+        # global bindings
+        q = quit
+        ctrl-c = quit
+
+        [normal]
+        g g = top  # go to the top
+        j = down
+        i = insert
+
+        [insert]
+        esc = normal
+        q = insert
+    ";
+
+    fn char(ch: char) -> KeyEvent {
+        KeyEvent::from(KeyCode::Char(ch))
+    }
+
+    fn ctrl(ch: char) -> KeyEvent {
+        KeyEvent::new(KeyCode::Char(ch), Modifiers::CTRL)
+    }
+
+    #[test]
+    fn it_parses_keys() {
+        let shift_up = KeyEvent::new(KeyCode::Up, Modifiers::SHIFT);
+        let ctrl_super_left = KeyEvent::new(KeyCode::Left, Modifiers::CTRL | Modifiers::SUPER);
+
+        assert_eq!(parse_key("a"), Ok(char('a')));
+        assert_eq!(parse_key("A"), Ok(char('A')));
+        assert_eq!(parse_key("shift-a"), Ok(char('A')));
+        assert_eq!(parse_key("ctrl-c"), Ok(ctrl('c')));
+        assert_eq!(parse_key("ctrl--"), Ok(ctrl('-')));
+        assert_eq!(
+            parse_key("Alt-x"),
+            Ok(KeyEvent::new(KeyCode::Char('x'), Modifiers::ALT))
+        );
+        assert_eq!(parse_key("shift-up"), Ok(shift_up));
+        assert_eq!(parse_key("ctrl-super-left"), Ok(ctrl_super_left));
+        assert_eq!(parse_key("space"), Ok(char(' ')));
+        assert_eq!(parse_key("F12"), Ok(KeyEvent::from(KeyCode::F(12))));
+        assert_eq!(parse_key("pagedown"), Ok(KeyEvent::from(KeyCode::PageDown)));
+        assert!(parse_key("f36").is_err());
+        assert!(parse_key("foo-a").is_err());
+    }
+
+    #[test]
+    fn it_names_keys_in_the_parsable_notation() {
+        let keys = vec![
+            ctrl('w'),
+            char(' '),
+            KeyEvent::from(KeyCode::F(3)),
+            char('='),
+            KeyEvent::new(KeyCode::Tab, Modifiers::SHIFT | Modifiers::HYPER),
+        ];
+        assert_eq!(keys_name(&keys), "ctrl-w space f3 = shift-hyper-tab");
+        assert_eq!(parse_keys(&keys_name(&keys)), Ok(keys));
+    }
+
+    #[test]
+    fn it_parses_bindings_with_modes_and_descriptions() {
+        let keymap = KeyMap::<Action>::parse(BINDINGS).unwrap();
+        let mut keymap = keymap;
+        keymap.set_mode("normal");
+
+        let active = keymap.active_bindings();
+        let names: Vec<String> = active.iter().map(|b| keys_name(&b.keys)).collect();
+        assert_eq!(names, vec!["g g", "j", "i", "q", "ctrl-c"]);
+        assert_eq!(active[0].description, "go to the top");
+        assert_eq!(active[1].description, "down");
+    }
+
+    #[test]
+    fn it_reports_parse_errors_with_line_numbers() {
+        let error = KeyMap::<Action>::parse("q = quit\nx = fly").unwrap_err();
+        assert_eq!(error.to_string(), "line 2: unknown action 'fly'");
+
+        let error = KeyMap::<Action>::parse("[normal\n").unwrap_err();
+        assert_eq!(error.to_string(), "line 1: unterminated section '[normal'");
+
+        let error = KeyMap::<Action>::parse("foo = quit").unwrap_err();
+        assert_eq!(error.to_string(), "line 1: unknown key 'foo'");
+    }
+
+    #[test]
+    fn it_ignores_key_releases_and_treats_repeats_as_presses() {
+        let mut keymap = KeyMap::<Action>::parse(BINDINGS).unwrap();
+        keymap.set_mode("normal");
+
+        let g = char('g');
+        assert_eq!(keymap.feed(&g), KeyMapResult::Pending);
+        assert_eq!(
+            keymap.feed(&g.with_kind(KeyKind::Release)),
+            KeyMapResult::Unbound
+        );
+        assert_eq!(
+            keymap.feed(&g.with_kind(KeyKind::Repeat)),
+            KeyMapResult::Action(Action::Top)
+        );
+    }
+
+    #[test]
+    fn it_binds_hash_and_equals_keys() {
+        let keymap = KeyMap::<Action>::parse("# = quit\n= = top # to the top").unwrap();
+        assert_eq!(
+            keymap.lookup(&[char('#')]),
+            KeyMapResult::Action(Action::Quit)
+        );
+        assert_eq!(
+            keymap.lookup(&[char('=')]),
+            KeyMapResult::Action(Action::Top)
+        );
+    }
+
+    #[test]
+    fn it_feeds_key_sequences() {
+        let mut keymap = KeyMap::<Action>::parse(BINDINGS).unwrap();
+        keymap.set_mode("normal");
+
+        assert_eq!(keymap.feed(&char('g')), KeyMapResult::Pending);
+        assert_eq!(keymap.pending(), &[char('g')]);
+        assert_eq!(keymap.feed(&char('g')), KeyMapResult::Action(Action::Top));
+        assert!(keymap.pending().is_empty());
+    }
+
+    #[test]
+    fn it_restarts_with_the_last_key_after_a_broken_sequence() {
+        let mut keymap = KeyMap::<Action>::parse(BINDINGS).unwrap();
+        keymap.set_mode("normal");
+
+        assert_eq!(keymap.feed(&char('g')), KeyMapResult::Pending);
+        assert_eq!(keymap.feed(&char('j')), KeyMapResult::Action(Action::Down));
+        assert_eq!(keymap.feed(&char('g')), KeyMapResult::Pending);
+        assert_eq!(keymap.feed(&char('x')), KeyMapResult::Unbound);
+        assert!(keymap.pending().is_empty());
+    }
+
+    #[test]
+    fn it_prefers_mode_bindings_over_global_bindings() {
+        let mut keymap = KeyMap::<Action>::parse(BINDINGS).unwrap();
+
+        assert_eq!(keymap.feed(&char('q')), KeyMapResult::Action(Action::Quit));
+        assert_eq!(keymap.feed(&char('j')), KeyMapResult::Unbound);
+
+        keymap.set_mode("insert");
+        assert_eq!(
+            keymap.feed(&char('q')),
+            KeyMapResult::Action(Action::Insert)
+        );
+        assert_eq!(keymap.feed(&ctrl('c')), KeyMapResult::Action(Action::Quit));
+    }
+
+    #[test]
+    fn it_replaces_existing_bindings() {
+        let mut keymap = KeyMap::new();
+        keymap.bind_str(GLOBAL_MODE, "x", Action::Quit, "").unwrap();
+        keymap.bind_str(GLOBAL_MODE, "x", Action::Top, "").unwrap();
+
+        assert_eq!(keymap.active_bindings().len(), 1);
+        assert_eq!(
+            keymap.lookup(&[char('x')]),
+            KeyMapResult::Action(Action::Top)
+        );
+    }
+}
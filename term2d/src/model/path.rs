@@ -0,0 +1,650 @@
+use std::f32::consts::TAU;
+use std::fmt::Display;
+use std::str::FromStr;
+
+use super::ellipse::arc_segments;
+use super::polygon::Polygon;
+
+// curves are subdivided at most this often, i.e. into 2^16 lines
+const MAX_DEPTH: u32 = 16;
+
+/// A path command in absolute coordinates.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PathCommand {
+    /// Starts a new subpath.
+    MoveTo(f32, f32),
+    LineTo(f32, f32),
+    /// A quadratic Bezier curve with a control point and the end point.
+    QuadTo(f32, f32, f32, f32),
+    /// A cubic Bezier curve with two control points and the end point.
+    CubicTo(f32, f32, f32, f32, f32, f32),
+    /// An elliptical arc to (x, y) like in SVG. The ellipse has the radii
+    /// `rx` and `ry` and is rotated by `rotation` radians, the flags choose
+    /// one of the four arcs between the points.
+    ArcTo {
+        rx: f32,
+        ry: f32,
+        rotation: f32,
+        large_arc: bool,
+        sweep: bool,
+        x: f32,
+        y: f32,
+    },
+    /// Connects the subpath to its start.
+    Close,
+}
+
+/// Which parts of overlapping or self-intersecting paths are filled.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum FillRule {
+    /// Inside are points the outline winds around, e.g. holes need the
+    /// opposite direction.
+    #[default]
+    NonZero,
+    /// Inside are points the outline winds around an odd number of times.
+    EvenOdd,
+}
+
+impl FillRule {
+    pub fn is_inside(&self, winding: i32) -> bool {
+        match self {
+            FillRule::NonZero => winding != 0,
+            FillRule::EvenOdd => winding % 2 != 0,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct PathError {
+    /// The byte position in the path data.
+    pub position: usize,
+    pub message: String,
+}
+
+impl Display for PathError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "position {}: {}", self.position, self.message)
+    }
+}
+
+impl std::error::Error for PathError {}
+
+/// Lines, curves and arcs, e.g. for icons and smooth graphs. Draw it with
+/// `Canvas::draw_path` and `Canvas::draw_path_fill`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Path {
+    pub commands: Vec<PathCommand>,
+}
+
+impl Path {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn move_to(self, x: f32, y: f32) -> Self {
+        self.with(PathCommand::MoveTo(x, y))
+    }
+
+    pub fn line_to(self, x: f32, y: f32) -> Self {
+        self.with(PathCommand::LineTo(x, y))
+    }
+
+    pub fn quad_to(self, cx: f32, cy: f32, x: f32, y: f32) -> Self {
+        self.with(PathCommand::QuadTo(cx, cy, x, y))
+    }
+
+    pub fn cubic_to(self, c1x: f32, c1y: f32, c2x: f32, c2y: f32, x: f32, y: f32) -> Self {
+        self.with(PathCommand::CubicTo(c1x, c1y, c2x, c2y, x, y))
+    }
+
+    /// See `PathCommand::ArcTo`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn arc_to(
+        self,
+        rx: f32,
+        ry: f32,
+        rotation: f32,
+        large_arc: bool,
+        sweep: bool,
+        x: f32,
+        y: f32,
+    ) -> Self {
+        self.with(PathCommand::ArcTo {
+            rx,
+            ry,
+            rotation,
+            large_arc,
+            sweep,
+            x,
+            y,
+        })
+    }
+
+    pub fn close(self) -> Self {
+        self.with(PathCommand::Close)
+    }
+
+    fn with(mut self, command: PathCommand) -> Self {
+        self.commands.push(command);
+        self
+    }
+
+    /// Parses SVG path data like `M 10 10 h 5 q 2 0 2 2 z`, with absolute
+    /// and relative commands. Arc rotations are in degrees as in SVG.
+    pub fn parse(data: &str) -> Result<Self, PathError> {
+        let mut parser = Parser {
+            bytes: data.as_bytes(),
+            position: 0,
+        };
+        let mut path = Path::new();
+        let mut current = (0.0, 0.0);
+        let mut start = (0.0, 0.0);
+        // the last control points of cubic and quadratic curves, reflected
+        // by the smooth curve commands
+        let mut last_cubic: Option<(f32, f32)> = None;
+        let mut last_quad: Option<(f32, f32)> = None;
+        let mut command: Option<u8> = None;
+
+        loop {
+            parser.skip_separators();
+            let Some(next) = parser.peek() else {
+                break;
+            };
+            if next.is_ascii_alphabetic() {
+                command = Some(next);
+                parser.position += 1;
+            }
+            let Some(letter) = command else {
+                return Err(parser.error("expected a command"));
+            };
+
+            let relative = letter.is_ascii_lowercase();
+            let base = if relative { current } else { (0.0, 0.0) };
+            let reflect = |control: Option<(f32, f32)>| match control {
+                Some((x, y)) => (2.0 * current.0 - x, 2.0 * current.1 - y),
+                None => current,
+            };
+            let (mut cubic, mut quad) = (None, None);
+
+            match letter.to_ascii_uppercase() {
+                b'M' => {
+                    current = parser.point(base)?;
+                    start = current;
+                    path = path.move_to(current.0, current.1);
+                    // further coordinate pairs are lines
+                    command = Some(if relative { b'l' } else { b'L' });
+                }
+                b'L' => {
+                    current = parser.point(base)?;
+                    path = path.line_to(current.0, current.1);
+                }
+                b'H' => {
+                    current.0 = parser.number()? + base.0;
+                    path = path.line_to(current.0, current.1);
+                }
+                b'V' => {
+                    current.1 = parser.number()? + base.1;
+                    path = path.line_to(current.0, current.1);
+                }
+                b'C' | b'S' => {
+                    let c1 = if letter.eq_ignore_ascii_case(&b'C') {
+                        parser.point(base)?
+                    } else {
+                        reflect(last_cubic)
+                    };
+                    let c2 = parser.point(base)?;
+                    current = parser.point(base)?;
+                    path = path.cubic_to(c1.0, c1.1, c2.0, c2.1, current.0, current.1);
+                    cubic = Some(c2);
+                }
+                b'Q' | b'T' => {
+                    let c = if letter.eq_ignore_ascii_case(&b'Q') {
+                        parser.point(base)?
+                    } else {
+                        reflect(last_quad)
+                    };
+                    current = parser.point(base)?;
+                    path = path.quad_to(c.0, c.1, current.0, current.1);
+                    quad = Some(c);
+                }
+                b'A' => {
+                    let rx = parser.number()?;
+                    let ry = parser.number()?;
+                    let rotation = parser.number()?.to_radians();
+                    let large_arc = parser.flag()?;
+                    let sweep = parser.flag()?;
+                    current = parser.point(base)?;
+                    path = path.arc_to(rx, ry, rotation, large_arc, sweep, current.0, current.1);
+                }
+                b'Z' => {
+                    current = start;
+                    path = path.close();
+                    command = None;
+                }
+                _ => {
+                    parser.position -= 1;
+                    return Err(parser.error(&format!("unknown command '{}'", letter as char)));
+                }
+            }
+
+            last_cubic = cubic;
+            last_quad = quad;
+        }
+
+        Ok(path)
+    }
+
+    /// The subpaths as connected points and whether they are closed. Curves
+    /// deviate at most `tolerance` from the lines between their points.
+    pub fn flatten(&self, tolerance: f32) -> Vec<(Vec<(f32, f32)>, bool)> {
+        let tolerance = tolerance.max(1e-3);
+        let mut subpaths = Vec::new();
+        let mut points = vec![(0.0, 0.0)];
+
+        let mut finish = |points: &mut Vec<(f32, f32)>, closed: bool| {
+            let start = points[0];
+            if points.len() > 1 || closed {
+                subpaths.push((std::mem::take(points), closed));
+            }
+            *points = vec![start];
+        };
+
+        for command in &self.commands {
+            let current = points[points.len() - 1];
+            match *command {
+                PathCommand::MoveTo(x, y) => {
+                    finish(&mut points, false);
+                    points = vec![(x, y)];
+                }
+                PathCommand::LineTo(x, y) => points.push((x, y)),
+                PathCommand::QuadTo(cx, cy, x, y) => {
+                    // the same curve as a cubic one
+                    let c1 = (
+                        current.0 + 2.0 / 3.0 * (cx - current.0),
+                        current.1 + 2.0 / 3.0 * (cy - current.1),
+                    );
+                    let c2 = (x + 2.0 / 3.0 * (cx - x), y + 2.0 / 3.0 * (cy - y));
+                    flatten_cubic([current, c1, c2, (x, y)], tolerance, 0, &mut points);
+                }
+                PathCommand::CubicTo(c1x, c1y, c2x, c2y, x, y) => {
+                    let curve = [current, (c1x, c1y), (c2x, c2y), (x, y)];
+                    flatten_cubic(curve, tolerance, 0, &mut points);
+                }
+                PathCommand::ArcTo {
+                    rx,
+                    ry,
+                    rotation,
+                    large_arc,
+                    sweep,
+                    x,
+                    y,
+                } => {
+                    let arc = ArcTo {
+                        rx,
+                        ry,
+                        rotation,
+                        large_arc,
+                        sweep,
+                    };
+                    arc.flatten(current, (x, y), tolerance, &mut points);
+                }
+                PathCommand::Close => finish(&mut points, true),
+            }
+        }
+        finish(&mut points, false);
+
+        subpaths
+    }
+}
+
+impl FromStr for Path {
+    type Err = PathError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Path::parse(s)
+    }
+}
+
+impl From<&Polygon> for Path {
+    fn from(polygon: &Polygon) -> Self {
+        let mut path = Path::new();
+        for (i, vertex) in polygon.vertices().iter().enumerate() {
+            let (x, y) = (vertex.x as f32, vertex.y as f32);
+            path = if i == 0 {
+                path.move_to(x, y)
+            } else {
+                path.line_to(x, y)
+            };
+        }
+        path.close()
+    }
+}
+
+/// How often the closed `polygon` winds around `p`, clockwise on the screen
+/// counts positive.
+pub fn winding_number(polygon: &[(f32, f32)], p: (f32, f32)) -> i32 {
+    let mut winding = 0;
+
+    for (i, a) in polygon.iter().enumerate() {
+        let b = polygon[(i + 1) % polygon.len()];
+        let side = (b.0 - a.0) * (p.1 - a.1) - (p.0 - a.0) * (b.1 - a.1);
+        // counts the edges crossing the ray to the right of `p`
+        if a.1 <= p.1 && b.1 > p.1 && side > 0.0 {
+            winding += 1;
+        } else if a.1 > p.1 && b.1 <= p.1 && side < 0.0 {
+            winding -= 1;
+        }
+    }
+
+    winding
+}
+
+// splits the curve in halves until its control points are close enough to
+// the line between its end points
+fn flatten_cubic(curve: [(f32, f32); 4], tolerance: f32, depth: u32, points: &mut Vec<(f32, f32)>) {
+    let [p0, p1, p2, p3] = curve;
+    let flat = distance_to_line(p1, p0, p3).max(distance_to_line(p2, p0, p3)) <= tolerance;
+    if flat || depth >= MAX_DEPTH {
+        points.push(p3);
+        return;
+    }
+
+    let mid = |a: (f32, f32), b: (f32, f32)| ((a.0 + b.0) / 2.0, (a.1 + b.1) / 2.0);
+    let p01 = mid(p0, p1);
+    let p12 = mid(p1, p2);
+    let p23 = mid(p2, p3);
+    let p012 = mid(p01, p12);
+    let p123 = mid(p12, p23);
+    let p0123 = mid(p012, p123);
+
+    flatten_cubic([p0, p01, p012, p0123], tolerance, depth + 1, points);
+    flatten_cubic([p0123, p123, p23, p3], tolerance, depth + 1, points);
+}
+
+fn distance_to_line(p: (f32, f32), a: (f32, f32), b: (f32, f32)) -> f32 {
+    let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+    let length = (dx * dx + dy * dy).sqrt();
+    if length == 0.0 {
+        return ((p.0 - a.0).powi(2) + (p.1 - a.1).powi(2)).sqrt();
+    }
+
+    ((p.0 - a.0) * dy - (p.1 - a.1) * dx).abs() / length
+}
+
+struct ArcTo {
+    rx: f32,
+    ry: f32,
+    rotation: f32,
+    large_arc: bool,
+    sweep: bool,
+}
+
+impl ArcTo {
+    // converts the end points to the center of the ellipse and its angles,
+    // see the implementation notes of the SVG specification
+    fn flatten(
+        &self,
+        from: (f32, f32),
+        to: (f32, f32),
+        tolerance: f32,
+        points: &mut Vec<(f32, f32)>,
+    ) {
+        let mut rx = self.rx.abs();
+        let mut ry = self.ry.abs();
+        if rx == 0.0 || ry == 0.0 || from == to {
+            points.push(to);
+            return;
+        }
+
+        let (sin, cos) = self.rotation.sin_cos();
+        let dx = (from.0 - to.0) / 2.0;
+        let dy = (from.1 - to.1) / 2.0;
+        let x1 = cos * dx + sin * dy;
+        let y1 = -sin * dx + cos * dy;
+
+        // radii too small to reach the end point are scaled up
+        let lambda = (x1 / rx).powi(2) + (y1 / ry).powi(2);
+        if lambda > 1.0 {
+            rx *= lambda.sqrt();
+            ry *= lambda.sqrt();
+        }
+
+        let numerator = (rx * ry).powi(2) - (rx * y1).powi(2) - (ry * x1).powi(2);
+        let denominator = (rx * y1).powi(2) + (ry * x1).powi(2);
+        let sign = if self.large_arc == self.sweep {
+            -1.0
+        } else {
+            1.0
+        };
+        let coefficient = sign * (numerator / denominator).max(0.0).sqrt();
+        let cx1 = coefficient * rx * y1 / ry;
+        let cy1 = -coefficient * ry * x1 / rx;
+        let cx = cos * cx1 - sin * cy1 + (from.0 + to.0) / 2.0;
+        let cy = sin * cx1 + cos * cy1 + (from.1 + to.1) / 2.0;
+
+        let angle =
+            |ux: f32, uy: f32, vx: f32, vy: f32| (ux * vy - uy * vx).atan2(ux * vx + uy * vy);
+        let start = angle(1.0, 0.0, (x1 - cx1) / rx, (y1 - cy1) / ry);
+        let mut sweep = angle(
+            (x1 - cx1) / rx,
+            (y1 - cy1) / ry,
+            (-x1 - cx1) / rx,
+            (-y1 - cy1) / ry,
+        );
+        if self.sweep && sweep < 0.0 {
+            sweep += TAU;
+        } else if !self.sweep && sweep > 0.0 {
+            sweep -= TAU;
+        }
+
+        let segments = arc_segments(rx.max(ry), tolerance, sweep);
+
+        for i in 1..segments {
+            let (sin_t, cos_t) = (start + sweep * i as f32 / segments as f32).sin_cos();
+            points.push((
+                cx + rx * cos_t * cos - ry * sin_t * sin,
+                cy + rx * cos_t * sin + ry * sin_t * cos,
+            ));
+        }
+        points.push(to);
+    }
+}
+
+struct Parser<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<u8> {
+        self.bytes.get(self.position).copied()
+    }
+
+    fn error(&self, message: &str) -> PathError {
+        PathError {
+            position: self.position,
+            message: message.to_string(),
+        }
+    }
+
+    fn skip_separators(&mut self) {
+        while matches!(self.peek(), Some(b) if b.is_ascii_whitespace() || b == b',') {
+            self.position += 1;
+        }
+    }
+
+    fn skip_digits(&mut self) -> usize {
+        let start = self.position;
+        while matches!(self.peek(), Some(b) if b.is_ascii_digit()) {
+            self.position += 1;
+        }
+        self.position - start
+    }
+
+    // numbers may follow each other without separators, e.g. `1-2.5.5`
+    fn number(&mut self) -> Result<f32, PathError> {
+        self.skip_separators();
+        let start = self.position;
+
+        if matches!(self.peek(), Some(b'+' | b'-')) {
+            self.position += 1;
+        }
+        let mut digits = self.skip_digits();
+        if self.peek() == Some(b'.') {
+            self.position += 1;
+            digits += self.skip_digits();
+        }
+        if digits == 0 {
+            self.position = start;
+            return Err(self.error("expected a number"));
+        }
+
+        if matches!(self.peek(), Some(b'e' | b'E')) {
+            let mantissa_end = self.position;
+            self.position += 1;
+            if matches!(self.peek(), Some(b'+' | b'-')) {
+                self.position += 1;
+            }
+            if self.skip_digits() == 0 {
+                self.position = mantissa_end;
+            }
+        }
+
+        let text = std::str::from_utf8(&self.bytes[start..self.position]).unwrap_or_default();
+        text.parse().map_err(|_| PathError {
+            position: start,
+            message: format!("invalid number '{text}'"),
+        })
+    }
+
+    fn point(&mut self, base: (f32, f32)) -> Result<(f32, f32), PathError> {
+        let x = self.number()?;
+        let y = self.number()?;
+        Ok((base.0 + x, base.1 + y))
+    }
+
+    // arc flags are single digits, also without separators like `a 1 1 0 01 2 2`
+    fn flag(&mut self) -> Result<bool, PathError> {
+        self.skip_separators();
+        let flag = match self.peek() {
+            Some(b'0') => false,
+            Some(b'1') => true,
+            _ => return Err(self.error("expected a flag")),
+        };
+        self.position += 1;
+        Ok(flag)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn it_parses_absolute_and_relative_commands() {
+        let path = Path::parse("M10,10 h5 v-5 L 0 0 l1-2.5.5.5 z m 1 1 2 2").unwrap();
+
+        assert_eq!(
+            path,
+            Path::new()
+                .move_to(10.0, 10.0)
+                .line_to(15.0, 10.0)
+                .line_to(15.0, 5.0)
+                .line_to(0.0, 0.0)
+                .line_to(1.0, -2.5)
+                .line_to(1.5, -2.0)
+                .close()
+                .move_to(11.0, 11.0)
+                .line_to(13.0, 13.0)
+        );
+    }
+
+    #[test]
+    fn it_parses_curves_and_arcs() {
+        let path: Path = "M0 0 Q 2 0 2 2 T 2 6 C 0 0 1e1 0 10 10 s 1 1 2 2 a 4 2 90 01 5 5"
+            .parse()
+            .unwrap();
+
+        assert_eq!(path.commands[2], PathCommand::QuadTo(2.0, 4.0, 2.0, 6.0));
+        assert_eq!(
+            path.commands[4],
+            PathCommand::CubicTo(10.0, 20.0, 11.0, 11.0, 12.0, 12.0)
+        );
+        let PathCommand::ArcTo {
+            rotation,
+            large_arc,
+            sweep,
+            x,
+            y,
+            ..
+        } = path.commands[5]
+        else {
+            panic!("expected an arc");
+        };
+        assert!((rotation - std::f32::consts::FRAC_PI_2).abs() < 1e-6);
+        assert_eq!((large_arc, sweep, x, y), (false, true, 17.0, 17.0));
+    }
+
+    #[test]
+    fn it_reports_invalid_data() {
+        let error = Path::parse("M 1 1 L 2 x").unwrap_err();
+        assert_eq!(error.position, 10);
+        assert_eq!(error.to_string(), "position 10: expected a number");
+
+        assert_eq!(Path::parse("10 10").unwrap_err().position, 0);
+        assert_eq!(Path::parse("M 0 0 K 1 1").unwrap_err().position, 6);
+        assert_eq!(Path::parse("M 0 0 z 1").unwrap_err().position, 8);
+    }
+
+    #[test]
+    fn it_flattens_curves_adaptively() {
+        let path = Path::new()
+            .move_to(0.0, 0.0)
+            .cubic_to(0.0, 40.0, 40.0, 40.0, 40.0, 0.0);
+
+        let coarse = &path.flatten(1.0)[0].0;
+        let fine = &path.flatten(0.1)[0].0;
+        assert!(coarse.len() < fine.len());
+        assert_eq!(coarse[coarse.len() - 1], (40.0, 0.0));
+        // the top of the curve is at y = 30
+        let max_y = fine.iter().map(|p| p.1).fold(0.0, f32::max);
+        assert!((max_y - 30.0).abs() < 0.1);
+    }
+
+    #[test]
+    fn it_flattens_arcs_through_the_end_points() {
+        let half_circle = Path::new()
+            .move_to(0.0, 0.0)
+            .arc_to(5.0, 5.0, 0.0, false, true, 10.0, 0.0);
+
+        let (points, closed) = &half_circle.flatten(0.1)[0];
+        assert!(!closed);
+        assert_eq!(points[points.len() - 1], (10.0, 0.0));
+        // sweeping clockwise on the screen passes above the line, as y points down
+        let middle = points[points.len() / 2];
+        assert!((middle.0 - 5.0).abs() < 0.5 && (middle.1 + 5.0).abs() < 0.1);
+    }
+
+    #[test]
+    fn it_limits_the_lines_of_huge_arcs() {
+        let path = Path::new()
+            .move_to(0.0, 0.0)
+            .arc_to(1e7, 1e7, 0.0, false, true, 2e7, 0.0);
+        let (points, _) = &path.flatten(0.25)[0];
+
+        assert!(points.len() <= (1 << 16) + 1);
+        assert_eq!(points[points.len() - 1], (2e7, 0.0));
+    }
+
+    #[test]
+    fn it_winds_around_points() {
+        let square = [(0.0, 0.0), (4.0, 0.0), (4.0, 4.0), (0.0, 4.0)];
+        let reversed: Vec<_> = square.iter().rev().copied().collect();
+
+        assert_eq!(winding_number(&square, (2.0, 2.0)), 1);
+        assert_eq!(winding_number(&reversed, (2.0, 2.0)), -1);
+        assert_eq!(winding_number(&square, (5.0, 2.0)), 0);
+        assert!(FillRule::NonZero.is_inside(2));
+        assert!(!FillRule::EvenOdd.is_inside(2));
+    }
+}
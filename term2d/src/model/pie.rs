@@ -0,0 +1,90 @@
+use super::arc::Arc;
+use super::ellipse::flatten_arc;
+use super::point::Point;
+use super::rect::Rect;
+
+/// A slice of an ellipse, bounded by the arc from `start` to `end` and the
+/// lines from its end points to the center, see `Arc` for the angles.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Pie {
+    pub pos: Point,
+    pub rx: i32,
+    pub ry: i32,
+    pub start: f32,
+    pub end: f32,
+}
+
+impl Pie {
+    pub fn new(x: i32, y: i32, rx: i32, ry: i32, start: f32, end: f32) -> Self {
+        Self {
+            pos: Point::new(x, y),
+            rx,
+            ry,
+            start,
+            end,
+        }
+    }
+
+    pub fn arc(&self) -> Arc {
+        Arc {
+            pos: self.pos.clone(),
+            rx: self.rx,
+            ry: self.ry,
+            start: self.start,
+            end: self.end,
+        }
+    }
+
+    pub fn boundary(&self) -> Rect {
+        self.arc().ellipse().boundary()
+    }
+
+    pub fn contains(&self, p: &Point) -> bool {
+        let arc = self.arc();
+        *p == self.pos || (arc.ellipse().contains(p) && arc.contains_angle(p))
+    }
+
+    /// The outline as a polygon starting at the center.
+    pub fn flatten(&self, tolerance: f32) -> Vec<(f32, f32)> {
+        let mut points = vec![(self.pos.x as f32, self.pos.y as f32)];
+        points.extend(self.arc().flatten(tolerance));
+        points
+    }
+
+    /// The outline with the arc along the outer edges of the pixels as a
+    /// polygon, e.g. for transformed fills.
+    pub fn flatten_edges(&self, tolerance: f32) -> Vec<(f32, f32)> {
+        let center = (self.pos.x as f32, self.pos.y as f32);
+        let rx = self.rx.max(0) as f32 + 0.5;
+        let ry = self.ry.max(0) as f32 + 0.5;
+        let mut points = vec![center];
+        points.extend(flatten_arc(
+            center,
+            rx,
+            ry,
+            self.start,
+            self.arc().sweep(),
+            tolerance,
+        ));
+        points
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::f32::consts::FRAC_PI_2;
+
+    use super::*;
+
+    #[test]
+    fn it_contains_the_slice() {
+        let pie = Pie::new(0, 0, 4, 4, 0.0, FRAC_PI_2);
+
+        assert!(pie.contains(&Point::new(0, 0)));
+        assert!(pie.contains(&Point::new(2, 2)));
+        assert!(pie.contains(&Point::new(4, 0)));
+        assert!(!pie.contains(&Point::new(-1, 2)));
+        assert!(!pie.contains(&Point::new(2, -1)));
+        assert!(!pie.contains(&Point::new(4, 4)));
+    }
+}
@@ -0,0 +1,338 @@
+use std::fmt::Display;
+use std::fs::read_to_string;
+use std::fs::File;
+use std::io::BufWriter;
+use std::io::Write;
+use std::path::Path;
+use std::time::Duration;
+use std::time::Instant;
+
+use super::event::Event;
+use super::key::KeyKind;
+use super::keymap::key_name;
+use super::keymap::parse_key;
+use super::point::Point;
+
+const HEADER: &str = "# term2d recording";
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct RecordedEvent {
+    /// Time since the start of the recording.
+    pub time: Duration,
+    pub event: Event,
+}
+
+/// The events an app received, in a line based text format:
+///
+/// ```text
+/// # term2d recording
+/// size 80 48
+/// 100 elapse
+/// 132 key ctrl-c
+/// ```
+///
+/// `size` is the canvas size of the initial resize event, every other line
+/// is an event with its time in milliseconds.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Recording {
+    pub size: Point,
+    pub events: Vec<RecordedEvent>,
+}
+
+#[derive(Debug)]
+pub enum RecordingError {
+    Io(std::io::Error),
+    Parse { line: usize, message: String },
+}
+
+impl Display for RecordingError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RecordingError::Io(error) => write!(f, "{error}"),
+            RecordingError::Parse { line, message } => write!(f, "line {line}: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for RecordingError {}
+
+impl From<std::io::Error> for RecordingError {
+    fn from(error: std::io::Error) -> Self {
+        RecordingError::Io(error)
+    }
+}
+
+impl Recording {
+    pub fn parse(text: &str) -> Result<Self, RecordingError> {
+        let mut size = None;
+        let mut events = Vec::new();
+
+        for (i, line) in text.lines().enumerate() {
+            let parse_error = |message: String| RecordingError::Parse {
+                line: i + 1,
+                message,
+            };
+
+            // trailing spaces belong to pasted text
+            let line = line.trim_start().trim_end_matches('\r');
+            if line.trim_end().is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            if let Some(rest) = line.strip_prefix("size ") {
+                size = Some(parse_point(rest).map_err(parse_error)?);
+                continue;
+            }
+
+            if size.is_none() {
+                return Err(parse_error("expected 'size' before the events".to_string()));
+            }
+
+            let (time, event) = line.split_once(' ').unwrap_or((line, ""));
+            let Ok(millis) = time.parse() else {
+                return Err(parse_error(format!("invalid time '{time}'")));
+            };
+
+            events.push(RecordedEvent {
+                time: Duration::from_millis(millis),
+                event: parse_event(event).map_err(parse_error)?,
+            });
+        }
+
+        let Some(size) = size else {
+            return Err(RecordingError::Parse {
+                line: 0,
+                message: "missing 'size'".to_string(),
+            });
+        };
+
+        Ok(Self { size, events })
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, RecordingError> {
+        Self::parse(&read_to_string(path)?)
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), RecordingError> {
+        std::fs::write(path, self.to_string())?;
+        Ok(())
+    }
+}
+
+impl Display for Recording {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{HEADER}")?;
+        writeln!(f, "size {} {}", self.size.width(), self.size.height())?;
+        for RecordedEvent { time, event } in &self.events {
+            writeln!(f, "{} {}", time.as_millis(), format_event(event))?;
+        }
+        Ok(())
+    }
+}
+
+/// Writes events to a file as they happen, so the recording survives a
+/// crashing app.
+pub struct Recorder {
+    start: Instant,
+    writer: BufWriter<File>,
+}
+
+impl Recorder {
+    pub fn create<P: AsRef<Path>>(path: P, size: &Point) -> std::io::Result<Self> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        writeln!(writer, "{HEADER}")?;
+        writeln!(writer, "size {} {}", size.width(), size.height())?;
+        writer.flush()?;
+
+        Ok(Self {
+            start: Instant::now(),
+            writer,
+        })
+    }
+
+    pub fn record(&mut self, event: &Event) -> std::io::Result<()> {
+        let millis = self.start.elapsed().as_millis();
+        writeln!(self.writer, "{millis} {}", format_event(event))?;
+        self.writer.flush()
+    }
+}
+
+pub fn format_event(event: &Event) -> String {
+    match event {
+        Event::Key(key) => {
+            let kind = match key.kind {
+                KeyKind::Press => "",
+                KeyKind::Repeat => " repeat",
+                KeyKind::Release => " release",
+            };
+            format!("key {}{kind}", key_name(key))
+        }
+        Event::Resize(size) => format!("resize {} {}", size.width(), size.height()),
+        Event::Elapse => "elapse".to_string(),
+        Event::Timer(id) => format!("timer {}", u64::from(*id)),
+        Event::Paste(text) => format!("paste {}", text.escape_default()),
+        Event::FocusGained => "focus-gained".to_string(),
+        Event::FocusLost => "focus-lost".to_string(),
+    }
+}
+
+pub fn parse_event(s: &str) -> Result<Event, String> {
+    let (name, args) = s.split_once(' ').unwrap_or((s, ""));
+
+    let event = match name {
+        "key" => {
+            let mut words = args.split_whitespace();
+            let key = parse_key(words.next().unwrap_or_default())?;
+            let kind = match words.next() {
+                None => KeyKind::Press,
+                Some("repeat") => KeyKind::Repeat,
+                Some("release") => KeyKind::Release,
+                Some(kind) => return Err(format!("unknown key kind '{kind}'")),
+            };
+            Event::Key(key.with_kind(kind))
+        }
+        "resize" => Event::Resize(parse_point(args)?),
+        "elapse" => Event::Elapse,
+        "timer" => match args.parse::<u64>() {
+            Ok(id) => Event::Timer(id.into()),
+            Err(_) => return Err(format!("invalid timer id '{args}'")),
+        },
+        "paste" => Event::Paste(unescape(args)?),
+        "focus-gained" => Event::FocusGained,
+        "focus-lost" => Event::FocusLost,
+        _ => return Err(format!("unknown event '{s}'")),
+    };
+
+    Ok(event)
+}
+
+fn parse_point(s: &str) -> Result<Point, String> {
+    let coordinates: Vec<i32> = s
+        .split_whitespace()
+        .map(|c| c.parse().map_err(|_| format!("invalid number '{c}'")))
+        .collect::<Result<_, _>>()?;
+
+    match coordinates[..] {
+        [x, y] => Ok(Point::new(x, y)),
+        _ => Err(format!("expected two numbers, got '{s}'")),
+    }
+}
+
+// reverses str::escape_default
+fn unescape(s: &str) -> Result<String, String> {
+    let mut unescaped = String::new();
+    let mut chars = s.chars();
+
+    while let Some(ch) = chars.next() {
+        if ch != '\\' {
+            unescaped.push(ch);
+            continue;
+        }
+
+        let escaped = match chars.next() {
+            Some('n') => '\n',
+            Some('r') => '\r',
+            Some('t') => '\t',
+            Some(ch @ ('\\' | '\'' | '"')) => ch,
+            Some('u') => {
+                let code: String = chars.by_ref().skip(1).take_while(|&c| c != '}').collect();
+                u32::from_str_radix(&code, 16)
+                    .ok()
+                    .and_then(char::from_u32)
+                    .ok_or(format!("invalid unicode escape '{code}'"))?
+            }
+            _ => return Err(format!("invalid escape in '{s}'")),
+        };
+        unescaped.push(escaped);
+    }
+
+    Ok(unescaped)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::model::key::KeyCode;
+    use crate::model::key::KeyEvent;
+    use crate::model::key::Modifiers;
+
+    fn recording() -> Recording {
+        let ctrl_c = KeyEvent::new(KeyCode::Char('c'), Modifiers::CTRL);
+        let w_release = KeyEvent::from(KeyCode::Char('w')).with_kind(KeyKind::Release);
+
+        let events = vec![
+            Event::Elapse,
+            Event::Key(KeyEvent::from(KeyCode::Char(' '))),
+            Event::Key(w_release),
+            Event::Resize(Point::new(100, 60)),
+            Event::Timer(7.into()),
+            Event::Paste("a \"b\"\n\tc\\ \u{1b}[A".to_string()),
+            Event::FocusLost,
+            Event::FocusGained,
+            Event::Key(ctrl_c),
+        ];
+
+        Recording {
+            size: Point::new(80, 48),
+            events: events
+                .into_iter()
+                .enumerate()
+                .map(|(i, event)| RecordedEvent {
+                    time: Duration::from_millis(100 * i as u64),
+                    event,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn it_formats_a_recording() {
+        let text = recording().to_string();
+        let lines: Vec<&str> = text.lines().collect();
+
+        assert_eq!(lines[0], HEADER);
+        assert_eq!(lines[1], "size 80 48");
+        assert_eq!(lines[2], "0 elapse");
+        assert_eq!(lines[3], "100 key space");
+        assert_eq!(lines[4], "200 key w release");
+        assert_eq!(lines[5], "300 resize 100 60");
+        assert_eq!(lines[6], "400 timer 7");
+        assert_eq!(lines[10], "800 key ctrl-c");
+    }
+
+    #[test]
+    fn it_parses_a_formatted_recording() {
+        let recording = recording();
+        assert_eq!(Recording::parse(&recording.to_string()).unwrap(), recording);
+    }
+
+    #[test]
+    fn it_keeps_spaces_of_pasted_text() {
+        let mut recording = recording();
+        recording.events.truncate(1);
+        for (i, text) in [" a b ", " ", ""].into_iter().enumerate() {
+            recording.events.push(RecordedEvent {
+                time: Duration::from_millis(i as u64),
+                event: Event::Paste(text.to_string()),
+            });
+        }
+
+        let text = recording.to_string().replace('\n', "\r\n");
+        assert_eq!(Recording::parse(&text).unwrap(), recording);
+    }
+
+    #[test]
+    fn it_reports_invalid_lines() {
+        let error = Recording::parse("size 1 2\n10 jump").unwrap_err();
+        assert_eq!(error.to_string(), "line 2: unknown event 'jump'");
+
+        let error = Recording::parse("10 elapse").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "line 1: expected 'size' before the events"
+        );
+
+        let error = Recording::parse("size 1\n").unwrap_err();
+        assert_eq!(error.to_string(), "line 1: expected two numbers, got '1'");
+    }
+}
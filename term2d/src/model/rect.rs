@@ -3,7 +3,7 @@ use std::ops::AddAssign;
 
 use super::point::Point;
 
-#[derive(Clone, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
 pub struct Rect {
     pub pos: Point,
     pub size: Point,
@@ -28,6 +28,44 @@ impl Rect {
 
         true
     }
+
+    /// Splits into a left part `x` wide and the right rest.
+    pub fn split_at_x(&self, x: i32) -> (Rect, Rect) {
+        let x = x.clamp(0, self.size.width());
+        (
+            Rect::new(self.pos.x, self.pos.y, x, self.size.height()),
+            Rect::new(
+                self.pos.x + x,
+                self.pos.y,
+                self.size.width() - x,
+                self.size.height(),
+            ),
+        )
+    }
+
+    /// Splits into a top part `y` high and the bottom rest.
+    pub fn split_at_y(&self, y: i32) -> (Rect, Rect) {
+        let y = y.clamp(0, self.size.height());
+        (
+            Rect::new(self.pos.x, self.pos.y, self.size.width(), y),
+            Rect::new(
+                self.pos.x,
+                self.pos.y + y,
+                self.size.width(),
+                self.size.height() - y,
+            ),
+        )
+    }
+
+    /// The area covered by both rects, its size is 0 if they do not overlap.
+    pub fn intersection(&self, other: &Rect) -> Rect {
+        let x0 = self.pos.x.max(other.pos.x);
+        let y0 = self.pos.y.max(other.pos.y);
+        let x1 = (self.pos.x + self.size.width()).min(other.pos.x + other.size.width());
+        let y1 = (self.pos.y + self.size.height()).min(other.pos.y + other.size.height());
+
+        Rect::new(x0, y0, (x1 - x0).max(0), (y1 - y0).max(0))
+    }
 }
 
 impl From<Point> for Rect {
@@ -81,3 +119,36 @@ impl Add<Point> for Rect {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn it_intersects() {
+        let rect = Rect::new(0, 0, 10, 5);
+
+        assert_eq!(
+            rect.intersection(&Rect::new(5, -2, 10, 4)),
+            Rect::new(5, 0, 5, 2)
+        );
+        assert_eq!(
+            rect.intersection(&Rect::new(10, 0, 3, 3)).size,
+            Point::new(0, 3)
+        );
+    }
+
+    #[test]
+    fn it_splits() {
+        let rect = Rect::new(1, 2, 10, 6);
+
+        assert_eq!(
+            rect.split_at_x(4),
+            (Rect::new(1, 2, 4, 6), Rect::new(5, 2, 6, 6))
+        );
+        assert_eq!(
+            rect.split_at_y(8),
+            (Rect::new(1, 2, 10, 6), Rect::new(1, 8, 10, 0))
+        );
+    }
+}
@@ -34,6 +34,12 @@ impl Rgba {
         Self { r, g, b, a: 255 }
     }
 
+    /// Formats the color as lowercase hex str without alpha, the inverse of
+    /// `from_hex`.
+    pub fn to_hex(&self) -> String {
+        format!("{:02x}{:02x}{:02x}", self.r, self.g, self.b)
+    }
+
     pub const fn transparent() -> Self {
         Self {
             r: 0,
@@ -134,20 +140,30 @@ impl Rgba {
         format!("\x1b[38;2;{r};{g};{b}m")
     }
 
+    /// Draws self over other, see `BlendMode` for other ways to combine
+    /// colors.
     pub fn blend(&self, other: &Rgba) -> Rgba {
         if self.a == 0 {
             return other.clone();
         }
 
-        if self.a == 255 {
+        if self.a == 255 || other.a == 0 {
             return self.clone();
         }
 
+        // the weight of other, which is 255 - a for opaque colors
         let a = self.a as i32;
-        let r = ((a * self.r as i32 + (255 - a) * other.r as i32) / 255) as u8;
-        let g = ((a * self.g as i32 + (255 - a) * other.g as i32) / 255) as u8;
-        let b = ((a * self.b as i32 + (255 - a) * other.b as i32) / 255) as u8;
-        Rgba { r, g, b, a: 255 }
+        let other_a = other.a as i32 * (255 - a) / 255;
+        let out_a = a + other_a;
+        let r = ((a * self.r as i32 + other_a * other.r as i32) / out_a) as u8;
+        let g = ((a * self.g as i32 + other_a * other.g as i32) / out_a) as u8;
+        let b = ((a * self.b as i32 + other_a * other.b as i32) / out_a) as u8;
+        Rgba {
+            r,
+            g,
+            b,
+            a: out_a as u8,
+        }
     }
 
     pub fn fade(&mut self, target: &Rgba, fading_speed: i32) {
@@ -215,7 +231,7 @@ mod test {
             Rgba {
                 r: 103,
                 g: 137,
-                b: 01,
+                b: 1,
                 a: 255
             }
         );
@@ -242,4 +258,10 @@ mod test {
             }
         );
     }
+
+    #[test]
+    fn it_formats_rgba_as_hex_string() {
+        assert_eq!(Rgba::new(1, 35, 239, 0).to_hex(), "0123ef");
+        assert_eq!(Rgba::from_hex("abcdef").to_hex(), "abcdef");
+    }
 }
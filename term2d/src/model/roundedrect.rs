@@ -0,0 +1,156 @@
+use std::f32::consts::FRAC_PI_2;
+
+use super::ellipse::flatten_arc;
+use super::ellipse::Ellipse;
+use super::point::Point;
+use super::rect::Rect;
+
+/// A rect whose corners are quarter circles, the radius is limited to half
+/// of the shorter side.
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct RoundedRect {
+    pub rect: Rect,
+    pub radius: i32,
+}
+
+impl RoundedRect {
+    pub fn new(x: i32, y: i32, width: i32, height: i32, radius: i32) -> Self {
+        Self {
+            rect: Rect::new(x, y, width, height),
+            radius,
+        }
+    }
+
+    /// The radius which fits into the rect.
+    pub fn corner_radius(&self) -> i32 {
+        let shorter = self.rect.size.width().min(self.rect.size.height());
+        self.radius.clamp(0, ((shorter - 1) / 2).max(0))
+    }
+
+    // the centers of the top left and the bottom right corner circles
+    fn corner_centers(&self) -> (Point, Point) {
+        let r = self.corner_radius();
+        let x1 = self.rect.pos.x + self.rect.size.width() - 1;
+        let y1 = self.rect.pos.y + self.rect.size.height() - 1;
+        (
+            Point::new(self.rect.pos.x + r, self.rect.pos.y + r),
+            Point::new(x1 - r, y1 - r),
+        )
+    }
+
+    pub fn contains(&self, p: &Point) -> bool {
+        if !self.rect.contains(p) {
+            return false;
+        }
+
+        // the nearest point of the rect between the corner centers
+        let r = self.corner_radius() as i64;
+        let (min, max) = self.corner_centers();
+        let dx = (p.x - p.x.clamp(min.x, max.x)) as i64;
+        let dy = (p.y - p.y.clamp(min.y, max.y)) as i64;
+
+        dx * dx + dy * dy <= r * r + r
+    }
+
+    /// The pixels of the outline.
+    pub fn outline(&self) -> Vec<Point> {
+        let (min, max) = self.corner_centers();
+        let x0 = self.rect.pos.x;
+        let y0 = self.rect.pos.y;
+        let x1 = x0 + self.rect.size.width() - 1;
+        let y1 = y0 + self.rect.size.height() - 1;
+        let mut points = Vec::new();
+        if x1 < x0 || y1 < y0 {
+            return points;
+        }
+
+        for x in min.x..=max.x {
+            points.push(Point::new(x, y0));
+            points.push(Point::new(x, y1));
+        }
+        for y in min.y..=max.y {
+            points.push(Point::new(x0, y));
+            points.push(Point::new(x1, y));
+        }
+
+        // the quarters of a circle moved to the corners, pixels straight
+        // above, below or beside the center are on the straight sides
+        let r = self.corner_radius();
+        for p in Ellipse::new(0, 0, r, r).outline() {
+            if p.x == 0 || p.y == 0 {
+                continue;
+            }
+            let x = if p.x < 0 { min.x + p.x } else { max.x + p.x };
+            let y = if p.y < 0 { min.y + p.y } else { max.y + p.y };
+            points.push(Point::new(x, y));
+        }
+
+        points.sort();
+        points.dedup();
+        points
+    }
+
+    /// The outline as a polygon within `tolerance` of it, e.g. for
+    /// transformed or stroked rects.
+    pub fn flatten(&self, tolerance: f32) -> Vec<(f32, f32)> {
+        self.flatten_with(0.0, tolerance)
+    }
+
+    /// The outline along the outer edges of the pixels as a polygon, e.g. for
+    /// transformed fills.
+    pub fn flatten_edges(&self, tolerance: f32) -> Vec<(f32, f32)> {
+        self.flatten_with(0.5, tolerance)
+    }
+
+    fn flatten_with(&self, margin: f32, tolerance: f32) -> Vec<(f32, f32)> {
+        let radius = self.corner_radius();
+        let r = radius as f32 + margin;
+        let (min, max) = self.corner_centers();
+        let corners = [
+            (max.x, min.y, -FRAC_PI_2, (1.0, -1.0)),
+            (max.x, max.y, 0.0, (1.0, 1.0)),
+            (min.x, max.y, FRAC_PI_2, (-1.0, 1.0)),
+            (min.x, min.y, 2.0 * FRAC_PI_2, (-1.0, -1.0)),
+        ];
+
+        let mut points = Vec::new();
+        for (x, y, start, (sx, sy)) in corners {
+            let center = (x as f32, y as f32);
+            if radius == 0 {
+                // square corners stay square with the margin
+                points.push((center.0 + sx * margin, center.1 + sy * margin));
+            } else {
+                points.extend(flatten_arc(center, r, r, start, FRAC_PI_2, tolerance));
+            }
+        }
+        points
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn it_rounds_the_corners() {
+        let rect = RoundedRect::new(0, 0, 8, 6, 2);
+
+        assert!(!rect.contains(&Point::new(0, 0)));
+        assert!(rect.contains(&Point::new(1, 1)));
+        assert!(rect.contains(&Point::new(0, 2)));
+        assert!(rect.contains(&Point::new(4, 0)));
+
+        let outline = rect.outline();
+        assert!(!outline.contains(&Point::new(0, 0)));
+        assert!(outline.contains(&Point::new(0, 1)));
+        assert!(outline.contains(&Point::new(1, 0)));
+        assert!(outline.contains(&Point::new(7, 3)));
+        assert!(!outline.contains(&Point::new(3, 3)));
+    }
+
+    #[test]
+    fn it_limits_the_radius() {
+        assert_eq!(RoundedRect::new(0, 0, 8, 5, 10).corner_radius(), 2);
+        assert_eq!(RoundedRect::new(0, 0, 8, 5, -1).corner_radius(), 0);
+    }
+}
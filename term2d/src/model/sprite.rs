@@ -0,0 +1,314 @@
+use std::time::Duration;
+
+use super::image::Image;
+use super::point::Point;
+use super::rect::Rect;
+use super::rgba::Rgba;
+use super::video::MIN_FRAME_DURATION;
+
+/// One image containing many frames, each frame is a rect in the image.
+#[derive(Clone)]
+pub struct SpriteSheet {
+    pub image: Image,
+    pub frames: Vec<Rect>,
+}
+
+impl SpriteSheet {
+    pub fn new(image: Image, frames: Vec<Rect>) -> Self {
+        Self { image, frames }
+    }
+
+    /// Slices the image into frames of `frame_size`, row by row. Frames which
+    /// do not fit completely at the right and bottom edge are dropped.
+    pub fn grid(image: Image, frame_size: &Point) -> Self {
+        let columns = image.size.width() / frame_size.width().max(1);
+        let rows = image.size.height() / frame_size.height().max(1);
+
+        let frames = (0..rows)
+            .flat_map(|row| {
+                (0..columns).map(move |column| {
+                    Rect::new(
+                        column * frame_size.width(),
+                        row * frame_size.height(),
+                        frame_size.width(),
+                        frame_size.height(),
+                    )
+                })
+            })
+            .collect();
+
+        Self { image, frames }
+    }
+
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// The pixel at `p` relative to the frame, None outside of it.
+    pub fn get_pixel(&self, frame: usize, p: &Point) -> Option<&Rgba> {
+        let rect = self.frames.get(frame)?;
+        if !Rect::from(&rect.size).contains(p) {
+            return None;
+        }
+
+        let x = rect.pos.x + p.x;
+        let y = rect.pos.y + p.y;
+        if !Rect::from(&self.image.size).contains(&Point::new(x, y)) {
+            return None;
+        }
+
+        self.image
+            .pixels
+            .get((x + y * self.image.size.width()) as usize)
+    }
+
+    /// Copies a frame into its own image.
+    pub fn frame_image(&self, frame: usize) -> Image {
+        let size = self.frames[frame].size.clone();
+        let mut image = Image::new(&size);
+
+        for y in 0..size.height() {
+            for x in 0..size.width() {
+                if let Some(rgba) = self.get_pixel(frame, &Point::new(x, y)) {
+                    image.pixels[(x + y * size.width()) as usize] = rgba.clone();
+                }
+            }
+        }
+
+        image
+    }
+}
+
+/// A named sequence of sprite sheet frames.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AnimationClip {
+    pub name: String,
+    /// Indices into the sprite sheet frames.
+    pub frames: Vec<usize>,
+    pub durations: Vec<Duration>,
+    /// Looping clips start over, others stay on their last frame.
+    pub looping: bool,
+}
+
+impl AnimationClip {
+    /// A looping clip showing each frame for `frame_duration`.
+    pub fn new(name: &str, frames: Vec<usize>, frame_duration: Duration) -> Self {
+        let durations = vec![frame_duration; frames.len()];
+        Self {
+            name: name.to_string(),
+            frames,
+            durations,
+            looping: true,
+        }
+    }
+
+    pub fn once(self) -> Self {
+        Self {
+            looping: false,
+            ..self
+        }
+    }
+
+    pub fn with_durations(self, durations: Vec<Duration>) -> Self {
+        Self { durations, ..self }
+    }
+
+    fn duration(&self, index: usize) -> Duration {
+        self.durations
+            .get(index)
+            .copied()
+            .unwrap_or_default()
+            .max(MIN_FRAME_DURATION)
+    }
+}
+
+/// Plays one of several clips, e.g. idle, walk and jump of a character.
+#[derive(Clone, Debug)]
+pub struct SpritePlayer {
+    pub clips: Vec<AnimationClip>,
+    clip: usize,
+    // index into the frames of the clip
+    index: usize,
+    frame_time: Duration,
+    finished: bool,
+}
+
+impl SpritePlayer {
+    /// Starts playing the first clip.
+    pub fn new(clips: Vec<AnimationClip>) -> Self {
+        Self {
+            clips,
+            clip: 0,
+            index: 0,
+            frame_time: Duration::ZERO,
+            finished: false,
+        }
+    }
+
+    /// Switches to the clip with the given name, a clip which is already
+    /// playing continues. Returns false if there is no such clip.
+    pub fn play(&mut self, name: &str) -> bool {
+        let Some(clip) = self.clips.iter().position(|clip| clip.name == name) else {
+            return false;
+        };
+
+        if clip != self.clip {
+            self.clip = clip;
+            self.restart();
+        }
+
+        true
+    }
+
+    pub fn restart(&mut self) {
+        self.index = 0;
+        self.frame_time = Duration::ZERO;
+        self.finished = false;
+    }
+
+    /// The playing clip, None if there are no clips.
+    pub fn clip(&self) -> Option<&AnimationClip> {
+        self.clips.get(self.clip)
+    }
+
+    /// The sprite sheet frame to draw, 0 if there are no frames.
+    pub fn frame(&self) -> usize {
+        self.clip()
+            .and_then(|clip| clip.frames.get(self.index))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// True once a clip which does not loop showed its last frame.
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// Moves the clip forward by `elapsed`, returns true if the end of the
+    /// clip was reached, which happens once per loop for looping clips.
+    pub fn advance(&mut self, elapsed: Duration) -> bool {
+        let Some(clip) = self.clips.get(self.clip) else {
+            return false;
+        };
+        if self.finished || clip.frames.is_empty() {
+            return false;
+        }
+
+        let mut ended = false;
+        self.frame_time += elapsed;
+
+        loop {
+            let duration = clip.duration(self.index);
+            if self.frame_time < duration {
+                break;
+            }
+
+            self.frame_time -= duration;
+
+            if self.index + 1 < clip.frames.len() {
+                self.index += 1;
+                continue;
+            }
+
+            ended = true;
+            if clip.looping {
+                self.index = 0;
+            } else {
+                self.finished = true;
+                self.frame_time = Duration::ZERO;
+                break;
+            }
+        }
+
+        ended
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn ms(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    fn sheet() -> SpriteSheet {
+        let mut image = Image::new(&Point::new(5, 4));
+        for (i, rgba) in image.pixels.iter_mut().enumerate() {
+            rgba.r = i as u8;
+        }
+        SpriteSheet::grid(image, &Point::new(2, 2))
+    }
+
+    fn player() -> SpritePlayer {
+        SpritePlayer::new(vec![
+            AnimationClip::new("idle", vec![0, 1], ms(100)),
+            AnimationClip::new("jump", vec![2, 3], ms(50)).once(),
+        ])
+    }
+
+    #[test]
+    fn it_slices_a_grid() {
+        let sheet = sheet();
+
+        assert_eq!(sheet.len(), 4);
+        assert_eq!(sheet.frames[1], Rect::new(2, 0, 2, 2));
+        assert_eq!(sheet.frames[2], Rect::new(0, 2, 2, 2));
+    }
+
+    #[test]
+    fn it_reads_frame_pixels() {
+        let sheet = sheet();
+
+        assert_eq!(sheet.get_pixel(3, &Point::new(1, 1)).unwrap().r, 18);
+        assert_eq!(sheet.get_pixel(3, &Point::new(2, 0)), None);
+
+        let image = sheet.frame_image(1);
+        let reds: Vec<u8> = image.pixels.iter().map(|rgba| rgba.r).collect();
+        assert_eq!(reds, vec![2, 3, 7, 8]);
+    }
+
+    #[test]
+    fn it_loops_a_clip_and_reports_its_end() {
+        let mut player = player();
+
+        assert!(!player.advance(ms(100)));
+        assert_eq!(player.frame(), 1);
+        assert!(player.advance(ms(100)));
+        assert_eq!(player.frame(), 0);
+        assert!(!player.is_finished());
+    }
+
+    #[test]
+    fn it_switches_clips_and_stops_at_the_end() {
+        let mut player = player();
+        player.advance(ms(100));
+
+        assert!(player.play("jump"));
+        assert_eq!(player.frame(), 2);
+        assert!(player.play("jump"));
+        assert!(!player.play("fly"));
+
+        assert!(player.advance(ms(1000)));
+        assert_eq!(player.frame(), 3);
+        assert!(player.is_finished());
+        assert!(!player.advance(ms(1000)));
+
+        player.play("idle");
+        assert_eq!(player.frame(), 0);
+        assert!(!player.is_finished());
+    }
+
+    #[test]
+    fn it_plays_nothing_without_clips() {
+        let mut player = SpritePlayer::new(vec![]);
+
+        assert!(player.clip().is_none());
+        assert!(!player.advance(ms(100)));
+        assert_eq!(player.frame(), 0);
+    }
+}
@@ -0,0 +1,369 @@
+use std::f32::consts::PI;
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum LineCap {
+    /// Ends exactly at the end points.
+    #[default]
+    Butt,
+    Round,
+    /// Extends half of the width beyond the end points.
+    Square,
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum LineJoin {
+    /// Extends the outer edges until they meet, up to the miter limit.
+    #[default]
+    Miter,
+    Round,
+    Bevel,
+}
+
+/// How lines are drawn, see `Canvas::set_stroke`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Stroke {
+    pub width: f32,
+    /// Alternating lengths of dashes and gaps, empty for solid lines.
+    pub dash: Vec<f32>,
+    /// How far into the dash pattern the line starts.
+    pub dash_offset: f32,
+    pub cap: LineCap,
+    pub join: LineJoin,
+    /// Miters longer than this times the width are beveled.
+    pub miter_limit: f32,
+}
+
+impl Default for Stroke {
+    fn default() -> Self {
+        Self::new(1.0)
+    }
+}
+
+impl Stroke {
+    pub fn new(width: f32) -> Self {
+        Self {
+            width,
+            dash: Vec::new(),
+            dash_offset: 0.0,
+            cap: LineCap::default(),
+            join: LineJoin::default(),
+            miter_limit: 4.0,
+        }
+    }
+
+    pub fn dash(self, dash: Vec<f32>, dash_offset: f32) -> Self {
+        Self {
+            dash,
+            dash_offset,
+            ..self
+        }
+    }
+
+    pub fn cap(self, cap: LineCap) -> Self {
+        Self { cap, ..self }
+    }
+
+    pub fn join(self, join: LineJoin) -> Self {
+        Self { join, ..self }
+    }
+
+    /// The polygons covering the stroked line through `points`, closed lines
+    /// also connect the last to the first point. Their union is the stroke.
+    pub fn outline(&self, points: &[(f32, f32)], closed: bool) -> Vec<Vec<(f32, f32)>> {
+        let mut points = points.to_vec();
+        points.dedup();
+        if closed && points.len() > 1 && points.first() == points.last() {
+            points.pop();
+        }
+
+        let mut polygons = Vec::new();
+        for (dash, dash_closed) in self.dashes(&points, closed) {
+            self.outline_solid(&dash, dash_closed, &mut polygons);
+        }
+
+        polygons
+    }
+
+    // splits the line into its dashes, a line without gaps stays closed
+    fn dashes(&self, points: &[(f32, f32)], closed: bool) -> Vec<(Vec<(f32, f32)>, bool)> {
+        let mut pattern = self.dash.clone();
+        if pattern.len() % 2 == 1 {
+            pattern.extend(self.dash.clone());
+        }
+        let period: f32 = pattern.iter().sum();
+        if pattern.is_empty() || period <= 0.0 || pattern.iter().any(|d| *d < 0.0) {
+            return vec![(points.to_vec(), closed)];
+        }
+
+        let mut segments: Vec<_> = points.windows(2).map(|w| (w[0], w[1])).collect();
+        if closed && points.len() > 2 {
+            segments.push((points[points.len() - 1], points[0]));
+        }
+
+        // the current dash, the position in the pattern and the remaining
+        // length of the current pattern entry
+        let mut index = 0;
+        let mut remaining = pattern[0];
+        let mut offset = self.dash_offset.rem_euclid(period);
+        while offset > 0.0 {
+            if offset < remaining {
+                remaining -= offset;
+                break;
+            }
+            offset -= remaining;
+            index = (index + 1) % pattern.len();
+            remaining = pattern[index];
+        }
+
+        let mut dashes = Vec::new();
+        let mut current: Vec<(f32, f32)> = Vec::new();
+        for (a, b) in segments {
+            let length = distance(a, b);
+            let mut position = 0.0;
+
+            while position < length {
+                let step = remaining.min(length - position);
+                let start = lerp(a, b, position / length);
+                let end = lerp(a, b, (position + step) / length);
+
+                if index % 2 == 0 {
+                    if current.is_empty() {
+                        current.push(start);
+                    }
+                    current.push(end);
+                }
+
+                position += step;
+                remaining -= step;
+                if remaining <= 0.0 {
+                    if index % 2 == 0 && !current.is_empty() {
+                        dashes.push((std::mem::take(&mut current), false));
+                    }
+                    index = (index + 1) % pattern.len();
+                    remaining = pattern[index];
+                }
+            }
+        }
+
+        if !current.is_empty() {
+            dashes.push((current, false));
+        }
+
+        dashes
+    }
+
+    fn outline_solid(
+        &self,
+        points: &[(f32, f32)],
+        closed: bool,
+        polygons: &mut Vec<Vec<(f32, f32)>>,
+    ) {
+        let half = self.width / 2.0;
+        if points.is_empty() || half <= 0.0 {
+            return;
+        }
+
+        if points.len() == 1 {
+            // a zero length line is only visible with caps extending it
+            match self.cap {
+                LineCap::Butt => {}
+                LineCap::Round => polygons.push(circle(points[0], half)),
+                LineCap::Square => {
+                    let (x, y) = points[0];
+                    polygons.push(vec![
+                        (x - half, y - half),
+                        (x + half, y - half),
+                        (x + half, y + half),
+                        (x - half, y + half),
+                    ]);
+                }
+            }
+            return;
+        }
+
+        let closed = closed && points.len() > 2;
+        let count = points.len();
+        let segment_count = if closed { count } else { count - 1 };
+
+        for i in 0..segment_count {
+            let a = points[i];
+            let b = points[(i + 1) % count];
+            let n = normal(a, b);
+            polygons.push(vec![
+                offset(a, n, half),
+                offset(b, n, half),
+                offset(b, n, -half),
+                offset(a, n, -half),
+            ]);
+        }
+
+        let joins = if closed { 0..count } else { 1..count - 1 };
+        for i in joins {
+            let previous = points[(i + count - 1) % count];
+            let next = points[(i + 1) % count];
+            self.join_polygon(previous, points[i], next, polygons);
+        }
+
+        if !closed {
+            self.cap_polygon(points[1], points[0], polygons);
+            self.cap_polygon(points[count - 2], points[count - 1], polygons);
+        }
+    }
+
+    // the cap at `end` of the segment coming from `from`
+    fn cap_polygon(&self, from: (f32, f32), end: (f32, f32), polygons: &mut Vec<Vec<(f32, f32)>>) {
+        let half = self.width / 2.0;
+
+        match self.cap {
+            LineCap::Butt => {}
+            LineCap::Round => polygons.push(circle(end, half)),
+            LineCap::Square => {
+                let n = normal(from, end);
+                let d = (n.1, -n.0);
+                let beyond = (end.0 + d.0 * half, end.1 + d.1 * half);
+                polygons.push(vec![
+                    offset(end, n, half),
+                    offset(beyond, n, half),
+                    offset(beyond, n, -half),
+                    offset(end, n, -half),
+                ]);
+            }
+        }
+    }
+
+    fn join_polygon(
+        &self,
+        previous: (f32, f32),
+        vertex: (f32, f32),
+        next: (f32, f32),
+        polygons: &mut Vec<Vec<(f32, f32)>>,
+    ) {
+        let half = self.width / 2.0;
+        let n_in = normal(previous, vertex);
+        let n_out = normal(vertex, next);
+        let cross = n_in.0 * n_out.1 - n_in.1 * n_out.0;
+        if cross.abs() < 1e-6 && n_in.0 * n_out.0 + n_in.1 * n_out.1 > 0.0 {
+            return;
+        }
+
+        if self.join == LineJoin::Round {
+            polygons.push(circle(vertex, half));
+            return;
+        }
+
+        // the outer side of the turn
+        let side = if cross > 0.0 { -half } else { half };
+        let a = offset(vertex, n_in, side);
+        let b = offset(vertex, n_out, side);
+
+        // the tip lies on the bisector `miter`, cos is the cosine of half of
+        // the angle between the normals
+        let miter = (n_in.0 + n_out.0, n_in.1 + n_out.1);
+        let miter_length = (miter.0 * miter.0 + miter.1 * miter.1).sqrt();
+        let dot = miter.0 * n_in.0 + miter.1 * n_in.1;
+        let cos = if miter_length > 0.0 {
+            dot / miter_length
+        } else {
+            0.0
+        };
+
+        if self.join == LineJoin::Bevel || cos <= 0.0 || 1.0 / cos > self.miter_limit {
+            polygons.push(vec![vertex, a, b]);
+            return;
+        }
+
+        let scale = side / dot;
+        let tip = (vertex.0 + miter.0 * scale, vertex.1 + miter.1 * scale);
+        polygons.push(vec![vertex, a, tip, b]);
+    }
+}
+
+fn distance(a: (f32, f32), b: (f32, f32)) -> f32 {
+    ((b.0 - a.0).powi(2) + (b.1 - a.1).powi(2)).sqrt()
+}
+
+fn lerp(a: (f32, f32), b: (f32, f32), t: f32) -> (f32, f32) {
+    (a.0 + (b.0 - a.0) * t, a.1 + (b.1 - a.1) * t)
+}
+
+// the unit normal of the segment from a to b
+fn normal(a: (f32, f32), b: (f32, f32)) -> (f32, f32) {
+    let length = distance(a, b);
+    if length == 0.0 {
+        return (0.0, 0.0);
+    }
+
+    (-(b.1 - a.1) / length, (b.0 - a.0) / length)
+}
+
+fn offset(p: (f32, f32), n: (f32, f32), distance: f32) -> (f32, f32) {
+    (p.0 + n.0 * distance, p.1 + n.1 * distance)
+}
+
+fn circle(center: (f32, f32), radius: f32) -> Vec<(f32, f32)> {
+    let segments = ((radius * 8.0) as usize).clamp(8, 64);
+    (0..segments)
+        .map(|i| {
+            let angle = 2.0 * PI * i as f32 / segments as f32;
+            (
+                center.0 + radius * angle.cos(),
+                center.1 + radius * angle.sin(),
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn it_outlines_a_segment_with_caps() {
+        let line = [(0.0, 0.0), (4.0, 0.0)];
+
+        let outline = Stroke::new(2.0).outline(&line, false);
+        assert_eq!(
+            outline,
+            vec![vec![(0.0, 1.0), (4.0, 1.0), (4.0, -1.0), (0.0, -1.0)]]
+        );
+
+        let outline = Stroke::new(2.0).cap(LineCap::Square).outline(&line, false);
+        assert_eq!(outline.len(), 3);
+        assert!(outline[2].contains(&(5.0, 1.0)));
+    }
+
+    #[test]
+    fn it_joins_on_the_outer_side() {
+        let line = [(0.0, 0.0), (4.0, 0.0), (4.0, 4.0)];
+
+        let outline = Stroke::new(2.0).outline(&line, false);
+        assert_eq!(
+            outline[2],
+            vec![(4.0, 0.0), (4.0, -1.0), (5.0, -1.0), (5.0, 0.0)]
+        );
+
+        let outline = Stroke::new(2.0).join(LineJoin::Bevel).outline(&line, false);
+        assert_eq!(outline[2], vec![(4.0, 0.0), (4.0, -1.0), (5.0, 0.0)]);
+    }
+
+    #[test]
+    fn it_splits_dashes() {
+        let stroke = Stroke::new(1.0).dash(vec![2.0, 1.0], 0.0);
+
+        let dashes = stroke.dashes(&[(0.0, 0.0), (7.0, 0.0)], false);
+        let dashes: Vec<_> = dashes.into_iter().map(|(points, _)| points).collect();
+        assert_eq!(
+            dashes,
+            vec![
+                vec![(0.0, 0.0), (2.0, 0.0)],
+                vec![(3.0, 0.0), (5.0, 0.0)],
+                vec![(6.0, 0.0), (7.0, 0.0)],
+            ]
+        );
+
+        let stroke = stroke.dash(vec![2.0, 1.0], 1.0);
+        let dashes = stroke.dashes(&[(0.0, 0.0), (2.0, 0.0), (2.0, 2.0)], false);
+        assert_eq!(dashes[0].0, vec![(0.0, 0.0), (1.0, 0.0)]);
+        assert_eq!(dashes[1].0, vec![(2.0, 0.0), (2.0, 2.0)]);
+    }
+}
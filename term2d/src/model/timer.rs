@@ -0,0 +1,186 @@
+use std::time::Duration;
+use std::time::Instant;
+
+const MIN_INTERVAL: Duration = Duration::from_millis(1);
+
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct TimerId(u64);
+
+impl From<u64> for TimerId {
+    fn from(id: u64) -> Self {
+        Self(id)
+    }
+}
+
+impl From<TimerId> for u64 {
+    fn from(id: TimerId) -> Self {
+        id.0
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Timer {
+    pub id: TimerId,
+    pub deadline: Instant,
+    pub interval: Option<Duration>,
+}
+
+impl Timer {
+    pub fn is_repeating(&self) -> bool {
+        self.interval.is_some()
+    }
+}
+
+#[derive(Default)]
+pub struct Timers {
+    next_id: u64,
+    timers: Vec<Timer>,
+}
+
+impl Timers {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Schedules a timer which is due `duration` after `now`. Repeating timers
+    /// are rescheduled with the same duration each time they are polled.
+    pub fn add(&mut self, now: Instant, duration: Duration, repeating: bool) -> TimerId {
+        let id = TimerId(self.next_id);
+        self.next_id += 1;
+
+        let interval = if repeating {
+            Some(duration.max(MIN_INTERVAL))
+        } else {
+            None
+        };
+
+        self.timers.push(Timer {
+            id,
+            deadline: now + duration,
+            interval,
+        });
+
+        id
+    }
+
+    /// Removes the timer, returns false if it does not exist (anymore).
+    pub fn cancel(&mut self, id: TimerId) -> bool {
+        let len = self.timers.len();
+        self.timers.retain(|timer| timer.id != id);
+        self.timers.len() != len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.timers.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.timers.len()
+    }
+
+    pub fn next_deadline(&self) -> Option<Instant> {
+        self.timers.iter().map(|timer| timer.deadline).min()
+    }
+
+    /// Returns the ids of all timers due at `now` ordered by their deadline.
+    /// One-shot timers are removed, repeating timers fire at most once per
+    /// poll and are rescheduled.
+    pub fn poll(&mut self, now: Instant) -> Vec<TimerId> {
+        let mut due: Vec<(Instant, TimerId)> = self
+            .timers
+            .iter()
+            .filter(|timer| timer.deadline <= now)
+            .map(|timer| (timer.deadline, timer.id))
+            .collect();
+        due.sort();
+
+        self.timers
+            .retain(|timer| timer.deadline > now || timer.is_repeating());
+
+        for timer in &mut self.timers {
+            let Some(interval) = timer.interval else {
+                continue;
+            };
+
+            if timer.deadline > now {
+                continue;
+            }
+
+            timer.deadline += interval;
+            if timer.deadline <= now {
+                timer.deadline = now + interval;
+            }
+        }
+
+        due.into_iter().map(|(_, id)| id).collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn ms(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    #[test]
+    fn it_fires_a_one_shot_timer_once() {
+        let start = Instant::now();
+        let mut timers = Timers::new();
+        let id = timers.add(start, ms(100), false);
+
+        assert_eq!(timers.poll(start + ms(99)), vec![]);
+        assert_eq!(timers.poll(start + ms(100)), vec![id]);
+        assert_eq!(timers.poll(start + ms(200)), vec![]);
+        assert!(timers.is_empty());
+    }
+
+    #[test]
+    fn it_reschedules_a_repeating_timer() {
+        let start = Instant::now();
+        let mut timers = Timers::new();
+        let id = timers.add(start, ms(100), true);
+
+        assert_eq!(timers.poll(start + ms(100)), vec![id]);
+        assert_eq!(timers.next_deadline(), Some(start + ms(200)));
+        assert_eq!(timers.poll(start + ms(150)), vec![]);
+        assert_eq!(timers.poll(start + ms(200)), vec![id]);
+        assert_eq!(timers.len(), 1);
+    }
+
+    #[test]
+    fn it_skips_missed_intervals_of_a_repeating_timer() {
+        let start = Instant::now();
+        let mut timers = Timers::new();
+        let id = timers.add(start, ms(100), true);
+
+        assert_eq!(timers.poll(start + ms(450)), vec![id]);
+        assert_eq!(timers.next_deadline(), Some(start + ms(550)));
+    }
+
+    #[test]
+    fn it_returns_due_timers_ordered_by_deadline() {
+        let start = Instant::now();
+        let mut timers = Timers::new();
+        let late = timers.add(start, ms(30), false);
+        let early = timers.add(start, ms(10), true);
+        let middle = timers.add(start, ms(20), false);
+
+        assert_eq!(timers.poll(start + ms(30)), vec![early, middle, late]);
+    }
+
+    #[test]
+    fn it_cancels_timers() {
+        let start = Instant::now();
+        let mut timers = Timers::new();
+        let a = timers.add(start, ms(10), false);
+        let b = timers.add(start, ms(10), true);
+
+        assert!(timers.cancel(b));
+        assert!(!timers.cancel(b));
+        assert_eq!(timers.poll(start + ms(10)), vec![a]);
+        assert!(!timers.cancel(a));
+        assert_eq!(timers.next_deadline(), None);
+    }
+}
@@ -0,0 +1,158 @@
+use super::point::Point;
+
+/// An affine transform, maps (x, y) to (a * x + c * y + e, b * x + d * y + f).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Transform {
+    pub a: f32,
+    pub b: f32,
+    pub c: f32,
+    pub d: f32,
+    pub e: f32,
+    pub f: f32,
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+impl Transform {
+    pub const fn identity() -> Self {
+        Self {
+            a: 1.0,
+            b: 0.0,
+            c: 0.0,
+            d: 1.0,
+            e: 0.0,
+            f: 0.0,
+        }
+    }
+
+    pub const fn translation(dx: f32, dy: f32) -> Self {
+        Self {
+            e: dx,
+            f: dy,
+            ..Self::identity()
+        }
+    }
+
+    pub const fn scaling(sx: f32, sy: f32) -> Self {
+        Self {
+            a: sx,
+            d: sy,
+            ..Self::identity()
+        }
+    }
+
+    /// Rotates clockwise on the screen, as y points down.
+    pub fn rotation(angle: f32) -> Self {
+        let (sin, cos) = angle.sin_cos();
+        Self {
+            a: cos,
+            b: sin,
+            c: -sin,
+            d: cos,
+            ..Self::identity()
+        }
+    }
+
+    /// The transform applying `other` first and then self.
+    pub fn multiply(&self, other: &Transform) -> Self {
+        Self {
+            a: self.a * other.a + self.c * other.b,
+            b: self.b * other.a + self.d * other.b,
+            c: self.a * other.c + self.c * other.d,
+            d: self.b * other.c + self.d * other.d,
+            e: self.a * other.e + self.c * other.f + self.e,
+            f: self.b * other.e + self.d * other.f + self.f,
+        }
+    }
+
+    /// Moves the origin of the local coordinates.
+    pub fn translate(&self, dx: f32, dy: f32) -> Self {
+        self.multiply(&Self::translation(dx, dy))
+    }
+
+    pub fn scale(&self, sx: f32, sy: f32) -> Self {
+        self.multiply(&Self::scaling(sx, sy))
+    }
+
+    pub fn rotate(&self, angle: f32) -> Self {
+        self.multiply(&Self::rotation(angle))
+    }
+
+    pub fn is_identity(&self) -> bool {
+        *self == Self::identity()
+    }
+
+    /// The factor by which lengths grow on average, e.g. for radii.
+    pub fn scale_factor(&self) -> f32 {
+        (self.a * self.d - self.b * self.c).abs().sqrt()
+    }
+
+    /// None if the transform collapses everything onto a line or point.
+    pub fn inverse(&self) -> Option<Self> {
+        let det = self.a * self.d - self.b * self.c;
+        if det == 0.0 {
+            return None;
+        }
+
+        Some(Self {
+            a: self.d / det,
+            b: -self.b / det,
+            c: -self.c / det,
+            d: self.a / det,
+            e: (self.c * self.f - self.d * self.e) / det,
+            f: (self.b * self.e - self.a * self.f) / det,
+        })
+    }
+
+    pub fn apply(&self, x: f32, y: f32) -> (f32, f32) {
+        (
+            self.a * x + self.c * y + self.e,
+            self.b * x + self.d * y + self.f,
+        )
+    }
+
+    /// Transforms a point and rounds it to the nearest pixel.
+    pub fn apply_point(&self, p: &Point) -> Point {
+        let (x, y) = self.apply(p.x as f32, p.y as f32);
+        Point::new(x.round() as i32, y.round() as i32)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::f32::consts::FRAC_PI_2;
+
+    use super::*;
+
+    #[test]
+    fn it_composes_in_local_coordinates() {
+        let transform = Transform::identity().translate(10.0, 5.0).scale(2.0, 3.0);
+
+        assert_eq!(transform.apply_point(&Point::new(1, 1)), Point::new(12, 8));
+    }
+
+    #[test]
+    fn it_rotates_clockwise() {
+        let transform = Transform::rotation(FRAC_PI_2);
+
+        assert_eq!(transform.apply_point(&Point::new(1, 0)), Point::new(0, 1));
+        assert!((transform.scale_factor() - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn it_inverts() {
+        let transform = Transform::translation(3.0, -2.0)
+            .rotate(0.7)
+            .scale(2.0, 0.5);
+        let inverse = transform.inverse().unwrap();
+
+        let (x, y) = transform.apply(4.0, 7.0);
+        let (x, y) = inverse.apply(x, y);
+        assert!((x - 4.0).abs() < 1e-4 && (y - 7.0).abs() < 1e-4);
+        assert_eq!(Transform::scaling(0.0, 1.0).inverse(), None);
+    }
+}
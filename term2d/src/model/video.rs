@@ -1,32 +1,97 @@
+#[cfg(feature = "gif")]
+use std::path::Path;
+use std::time::Duration;
+
+#[cfg(feature = "gif")]
+use super::codec;
+#[cfg(feature = "gif")]
+use super::codec::ImageError;
 use super::image::Image;
+use super::image::ScaleFilter;
+use super::point::Point;
+use super::rect::Rect;
+
+// keeps zero frame durations from stalling `advance`
+pub(crate) const MIN_FRAME_DURATION: Duration = Duration::from_millis(1);
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum LoopMode {
+    /// Stops at the last frame, or the first one when playing in reverse.
+    Once,
+    #[default]
+    Loop,
+    /// Changes direction at either end.
+    PingPong,
+}
+
+#[derive(Clone, Debug)]
+pub struct Playback {
+    pub playing: bool,
+    /// Multiplies the elapsed time, e.g. 2.0 plays twice as fast.
+    pub speed: f64,
+    pub loop_mode: LoopMode,
+    /// Plays from the last to the first frame, toggled by ping-pong loops.
+    pub reverse: bool,
+    // time spent in the current frame
+    frame_time: Duration,
+}
+
+impl Default for Playback {
+    fn default() -> Self {
+        Self {
+            playing: true,
+            speed: 1.0,
+            loop_mode: LoopMode::default(),
+            reverse: false,
+            frame_time: Duration::ZERO,
+        }
+    }
+}
 
 #[derive(Clone)]
 pub struct Video {
     pub frame: usize,
     pub images: Vec<Image>,
+    /// How long each image is shown.
+    pub durations: Vec<Duration>,
+    pub playback: Playback,
 }
 
 impl From<Vec<(u32, u32, Vec<u8>)>> for Video {
     fn from(raw_video: Vec<(u32, u32, Vec<u8>)>) -> Self {
-        let mut video = Self {
-            frame: 0,
-            images: Vec::new(),
-        };
+        let images: Vec<Image> = raw_video.into_iter().map(Image::from).collect();
+        let durations = vec![Self::DEFAULT_FRAME_DURATION; images.len()];
+        Self::new(images, durations)
+    }
+}
 
-        for frame in raw_video {
-            let image = Image::from(frame);
-            video.images.push(image);
+impl Video {
+    pub const DEFAULT_FRAME_DURATION: Duration = Duration::from_millis(100);
+
+    /// Missing durations are filled with `DEFAULT_FRAME_DURATION`.
+    pub fn new(images: Vec<Image>, mut durations: Vec<Duration>) -> Self {
+        durations.resize(images.len(), Self::DEFAULT_FRAME_DURATION);
+
+        Self {
+            frame: 0,
+            images,
+            durations,
+            playback: Playback::default(),
         }
+    }
 
-        video
+    /// Loads all frames of a GIF with their delays.
+    #[cfg(feature = "gif")]
+    pub fn load_gif<P: AsRef<Path>>(path: P) -> Result<Self, ImageError> {
+        codec::decode_gif(&std::fs::read(path)?)
     }
-}
 
-impl Video {
     pub fn mirror_y(&self) -> Self {
         let mut mirrored_video = Self {
             frame: 0,
             images: Vec::new(),
+            durations: self.durations.clone(),
+            playback: self.playback.clone(),
         };
 
         for image in &self.images {
@@ -36,7 +101,318 @@ impl Video {
         mirrored_video
     }
 
+    /// Applies `f` to every frame, the playback state is kept.
+    pub fn map_images<F: FnMut(&Image) -> Image>(&self, f: F) -> Self {
+        Self {
+            images: self.images.iter().map(f).collect(),
+            ..self.clone()
+        }
+    }
+
+    pub fn crop(&self, rect: &Rect) -> Self {
+        self.map_images(|image| image.crop(rect))
+    }
+
+    pub fn scale(&self, size: &Point, filter: ScaleFilter) -> Self {
+        self.map_images(|image| image.scale(size, filter))
+    }
+
+    pub fn flip_horizontal(&self) -> Self {
+        self.map_images(Image::flip_horizontal)
+    }
+
+    pub fn flip_vertical(&self) -> Self {
+        self.map_images(Image::flip_vertical)
+    }
+
+    pub fn rotate90(&self) -> Self {
+        self.map_images(Image::rotate90)
+    }
+
+    pub fn rotate180(&self) -> Self {
+        self.map_images(Image::rotate180)
+    }
+
+    pub fn rotate270(&self) -> Self {
+        self.map_images(Image::rotate270)
+    }
+
+    pub fn rotate(&self, angle: f32) -> Self {
+        self.map_images(|image| image.rotate(angle))
+    }
+
     pub fn next_frame(&mut self) {
         self.frame = (self.frame + 1) % self.images.len();
     }
+
+    pub fn play(&mut self) {
+        self.playback.playing = true;
+    }
+
+    pub fn pause(&mut self) {
+        self.playback.playing = false;
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.playback.playing
+    }
+
+    pub fn total_duration(&self) -> Duration {
+        self.durations.iter().sum()
+    }
+
+    /// The time from the start of the video to the current position.
+    pub fn position(&self) -> Duration {
+        self.durations[..self.frame].iter().sum::<Duration>() + self.playback.frame_time
+    }
+
+    /// Moves the playback by `elapsed` scaled by the speed, possibly over
+    /// several frames. Does nothing while paused.
+    pub fn advance(&mut self, elapsed: Duration) {
+        if !self.playback.playing || self.images.is_empty() {
+            return;
+        }
+
+        // huge speeds saturate, and whole periods are skipped instead of stepped
+        let elapsed =
+            Duration::try_from_secs_f64(elapsed.as_secs_f64() * self.playback.speed.max(0.0))
+                .unwrap_or(Duration::MAX);
+        let period = self.period();
+        let elapsed = match self.playback.loop_mode {
+            LoopMode::Once => elapsed.min(period),
+            _ => Duration::from_nanos((elapsed.as_nanos() % period.as_nanos()) as u64),
+        };
+        self.playback.frame_time = self.playback.frame_time.saturating_add(elapsed);
+
+        loop {
+            let duration = self.durations[self.frame].max(MIN_FRAME_DURATION);
+            if self.playback.frame_time < duration {
+                break;
+            }
+
+            self.playback.frame_time -= duration;
+            if !self.step() {
+                self.playback.playing = false;
+                self.playback.frame_time = Duration::ZERO;
+                break;
+            }
+        }
+    }
+
+    /// Jumps to the start of a frame, the index is clamped.
+    pub fn seek_frame(&mut self, frame: usize) {
+        self.frame = frame.min(self.images.len().saturating_sub(1));
+        self.playback.frame_time = Duration::ZERO;
+    }
+
+    /// Jumps to the frame shown at `position`. Looping videos wrap around,
+    /// others stop at the last frame.
+    pub fn seek(&mut self, position: Duration) {
+        let total = self.total_duration();
+        let mut position = match self.playback.loop_mode {
+            LoopMode::Loop if !total.is_zero() => {
+                Duration::from_nanos((position.as_nanos() % total.as_nanos()) as u64)
+            }
+            _ => position,
+        };
+
+        for (frame, duration) in self.durations.iter().enumerate() {
+            if position < *duration {
+                self.frame = frame;
+                self.playback.frame_time = position;
+                return;
+            }
+            position -= *duration;
+        }
+
+        self.seek_frame(self.images.len());
+    }
+
+    // the playback time after which looping playback repeats itself
+    fn period(&self) -> Duration {
+        let durations: Vec<Duration> = self
+            .durations
+            .iter()
+            .map(|duration| (*duration).max(MIN_FRAME_DURATION))
+            .collect();
+        let sum = durations.iter().fold(Duration::ZERO, |sum, duration| {
+            sum.saturating_add(*duration)
+        });
+
+        match self.playback.loop_mode {
+            LoopMode::PingPong if durations.len() > 1 => sum
+                .saturating_mul(2)
+                .saturating_sub(durations[0])
+                .saturating_sub(durations[durations.len() - 1]),
+            _ => sum,
+        }
+    }
+
+    // moves to the next frame in playing direction, false if the video ended
+    fn step(&mut self) -> bool {
+        let last = self.images.len() - 1;
+        let at_end = if self.playback.reverse {
+            self.frame == 0
+        } else {
+            self.frame == last
+        };
+
+        if at_end {
+            match self.playback.loop_mode {
+                LoopMode::Once => return false,
+                LoopMode::Loop => {
+                    self.frame = if self.playback.reverse { last } else { 0 };
+                    return true;
+                }
+                LoopMode::PingPong if last == 0 => return true,
+                LoopMode::PingPong => self.playback.reverse = !self.playback.reverse,
+            }
+        }
+
+        if self.playback.reverse {
+            self.frame -= 1;
+        } else {
+            self.frame += 1;
+        }
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn ms(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    fn video(frames: usize) -> Video {
+        let image = Image::new(&Point::new(1, 1));
+        let durations = (1..=frames as u64).map(|i| ms(100 * i)).collect();
+        Video::new(vec![image; frames], durations)
+    }
+
+    #[test]
+    fn it_advances_by_frame_durations() {
+        let mut video = video(3);
+
+        video.advance(ms(99));
+        assert_eq!(video.frame, 0);
+        video.advance(ms(1));
+        assert_eq!(video.frame, 1);
+        video.advance(ms(550));
+        assert_eq!(video.frame, 0);
+        assert_eq!(video.position(), ms(50));
+    }
+
+    #[test]
+    fn it_scales_time_by_speed_and_ignores_it_while_paused() {
+        let mut video = video(3);
+        video.playback.speed = 2.0;
+
+        video.advance(ms(50));
+        assert_eq!(video.frame, 1);
+
+        video.pause();
+        video.advance(ms(1000));
+        assert_eq!(video.frame, 1);
+    }
+
+    #[test]
+    fn it_skips_whole_periods_at_huge_speeds() {
+        let mut fast = video(3);
+        fast.playback.speed = f64::INFINITY;
+        fast.advance(ms(1));
+        assert!(fast.is_playing());
+
+        let mut looping = video(3);
+        let mut skipped = video(3);
+        looping.advance(ms(120));
+        skipped.advance(ms(120 + 600 * 1000));
+        assert_eq!(skipped.position(), looping.position());
+
+        let mut bouncing = video(3);
+        let mut skipped = video(3);
+        bouncing.playback.loop_mode = LoopMode::PingPong;
+        skipped.playback.loop_mode = LoopMode::PingPong;
+        bouncing.advance(ms(650));
+        skipped.advance(ms(650 + 800 * 1000));
+        assert_eq!(skipped.frame, bouncing.frame);
+        assert_eq!(skipped.playback.reverse, bouncing.playback.reverse);
+
+        let mut once = video(3);
+        once.playback.loop_mode = LoopMode::Once;
+        once.playback.speed = 1e300;
+        once.advance(ms(1));
+        assert_eq!(once.frame, 2);
+        assert!(!once.is_playing());
+    }
+
+    #[test]
+    fn it_stops_at_the_end_when_playing_once() {
+        let mut video = video(3);
+        video.playback.loop_mode = LoopMode::Once;
+
+        video.advance(ms(10_000));
+        assert_eq!(video.frame, 2);
+        assert!(!video.is_playing());
+    }
+
+    #[test]
+    fn it_plays_in_reverse() {
+        let mut video = video(3);
+        video.playback.reverse = true;
+
+        video.advance(ms(100));
+        assert_eq!(video.frame, 2);
+        video.advance(ms(300));
+        assert_eq!(video.frame, 1);
+    }
+
+    #[test]
+    fn it_bounces_in_ping_pong_mode() {
+        let mut video = video(3);
+        video.playback.loop_mode = LoopMode::PingPong;
+        let mut frames = Vec::new();
+
+        for _ in 0..6 {
+            let duration = video.durations[video.frame];
+            video.advance(duration);
+            frames.push(video.frame);
+        }
+
+        assert_eq!(frames, vec![1, 2, 1, 0, 1, 2]);
+    }
+
+    #[test]
+    fn it_seeks_by_time_and_frame() {
+        let mut video = video(3);
+
+        video.seek(ms(350));
+        assert_eq!(video.frame, 2);
+        assert_eq!(video.position(), ms(350));
+
+        video.seek(ms(650));
+        assert_eq!(video.frame, 0);
+
+        video.seek_frame(7);
+        assert_eq!(video.frame, 2);
+        assert_eq!(video.position(), ms(300));
+    }
+
+    #[test]
+    fn it_transforms_all_frames_and_keeps_the_playback() {
+        let mut video = video(2);
+        video.seek_frame(1);
+
+        let scaled = video.scale(&Point::new(2, 3), ScaleFilter::Nearest);
+
+        assert_eq!(scaled.frame, 1);
+        assert!(scaled
+            .images
+            .iter()
+            .all(|image| image.size == Point::new(2, 3)));
+        assert_eq!(scaled.rotate90().images[0].size, Point::new(3, 2));
+    }
 }
@@ -1,2 +1,5 @@
+pub mod asciicast;
 pub mod canvas;
+pub mod help;
 pub mod screen;
+pub mod viewport;
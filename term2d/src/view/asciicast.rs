@@ -0,0 +1,121 @@
+use std::env;
+use std::fs::File;
+use std::io::BufWriter;
+use std::io::Write;
+use std::path::Path;
+use std::time::Duration;
+use std::time::Instant;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+use crate::model::point::Point;
+
+/// Writes terminal output in the asciicast v2 format, see
+/// https://docs.asciinema.org/manual/asciicast/v2/
+pub struct AsciicastWriter<W: Write = BufWriter<File>> {
+    start: Instant,
+    writer: W,
+}
+
+impl AsciicastWriter {
+    pub fn create<P: AsRef<Path>>(path: P, size: &Point) -> std::io::Result<Self> {
+        Self::new(BufWriter::new(File::create(path)?), size)
+    }
+}
+
+impl<W: Write> AsciicastWriter<W> {
+    /// Writes the header, `size` is the terminal size in columns and rows.
+    pub fn new(mut writer: W, size: &Point) -> std::io::Result<Self> {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        let term = env::var("TERM").unwrap_or_default();
+
+        writeln!(
+            writer,
+            "{{\"version\": 2, \"width\": {}, \"height\": {}, \"timestamp\": {timestamp}, \"env\": {{\"TERM\": {}}}}}",
+            size.width(),
+            size.height(),
+            json_string(&term),
+        )?;
+
+        Ok(Self {
+            start: Instant::now(),
+            writer,
+        })
+    }
+
+    pub fn output(&mut self, bytes: &[u8]) -> std::io::Result<()> {
+        let data = String::from_utf8_lossy(bytes);
+        self.write_event(self.start.elapsed(), "o", &data)
+    }
+
+    pub fn resize(&mut self, size: &Point) -> std::io::Result<()> {
+        let data = format!("{}x{}", size.width(), size.height());
+        self.write_event(self.start.elapsed(), "r", &data)
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    fn write_event(&mut self, time: Duration, code: &str, data: &str) -> std::io::Result<()> {
+        writeln!(
+            self.writer,
+            "[{:.6}, \"{code}\", {}]",
+            time.as_secs_f64(),
+            json_string(data),
+        )
+    }
+}
+
+fn json_string(s: &str) -> String {
+    let mut json = String::with_capacity(s.len() + 2);
+    json.push('"');
+
+    for ch in s.chars() {
+        match ch {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            ch if ch.is_control() => json.push_str(&format!("\\u{:04x}", ch as u32)),
+            ch => json.push(ch),
+        }
+    }
+
+    json.push('"');
+    json
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn it_escapes_json_strings() {
+        assert_eq!(
+            json_string("\x1b[1;1H\"a\\b\"\r\n▀"),
+            "\"\\u001b[1;1H\\\"a\\\\b\\\"\\r\\n▀\""
+        );
+    }
+
+    #[test]
+    fn it_writes_header_and_events() {
+        let mut cast = AsciicastWriter::new(Vec::new(), &Point::new(80, 24)).unwrap();
+        cast.write_event(Duration::from_millis(1500), "o", "\x1b[0mhi")
+            .unwrap();
+        cast.write_event(Duration::from_millis(2250), "r", "100x30")
+            .unwrap();
+
+        let text = String::from_utf8(cast.into_inner()).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+
+        assert!(lines[0].starts_with("{\"version\": 2, \"width\": 80, \"height\": 24, "));
+        assert!(lines[0].contains("\"env\": {\"TERM\": "));
+        assert_eq!(lines[1], "[1.500000, \"o\", \"\\u001b[0mhi\"]");
+        assert_eq!(lines[2], "[2.250000, \"r\", \"100x30\"]");
+    }
+}
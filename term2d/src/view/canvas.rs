@@ -1,19 +1,42 @@
+use crate::model::ansiart::AnsiArt;
+use crate::model::arc::Arc;
+use crate::model::blend::BlendMode;
+use crate::model::camera::Camera;
 use crate::model::circle::Circle;
 use crate::model::color::Color;
+use crate::model::ellipse::Ellipse;
+use crate::model::filter::Filter;
 use crate::model::image::Image;
+use crate::model::imagedraw::ImageDraw;
+use crate::model::path::winding_number;
+use crate::model::path::FillRule;
+use crate::model::path::Path;
+use crate::model::pie::Pie;
 use crate::model::point::Point;
 use crate::model::polygon::Polygon;
 use crate::model::rect::Rect;
 use crate::model::rgba::Rgba;
+use crate::model::roundedrect::RoundedRect;
+use crate::model::sprite::SpritePlayer;
+use crate::model::sprite::SpriteSheet;
+use crate::model::stroke::Stroke;
+use crate::model::transform::Transform;
 use crate::model::video::Video;
 
+use super::screen::Pixel;
 use super::screen::RawTerminalScreen;
+use super::screen::Screen;
+use super::viewport::Viewport;
+use layer::Layer;
 
 pub mod fullblock;
 pub mod halfblock;
+pub mod layer;
 
 pub trait Canvas: Sized {
     fn init(&mut self, screen: RawTerminalScreen);
+    fn get_screen(&self) -> &RawTerminalScreen;
+    fn get_screen_mut(&mut self) -> &mut RawTerminalScreen;
     fn get_size(&self) -> &Point;
     fn resize(&mut self) -> &Point;
     fn clear(&mut self);
@@ -22,7 +45,208 @@ pub trait Canvas: Sized {
     fn draw_text(&mut self, p: &Point, color: &Color, text: &str);
     fn display(&mut self);
 
+    fn get_blend_mode(&self) -> BlendMode {
+        self.get_screen().get_blend_mode()
+    }
+
+    /// Changes how all following drawing is combined with the canvas.
+    fn set_blend_mode(&mut self, mode: BlendMode) {
+        self.get_screen_mut().set_blend_mode(mode);
+    }
+
+    /// Calls `draw` with `mode` and restores the previous blend mode
+    /// afterwards.
+    fn with_blend_mode<F: FnOnce(&mut Self)>(&mut self, mode: BlendMode, draw: F) {
+        let previous = self.get_blend_mode();
+        self.set_blend_mode(mode);
+        draw(self);
+        self.set_blend_mode(previous);
+    }
+
+    /// Restricts all drawing to `rect` until the matching `pop_clip`. Nested
+    /// clips are intersected with the enclosing ones.
+    fn push_clip(&mut self, rect: Rect) {
+        let clip = self.get_clip().intersection(&rect);
+        self.get_screen_mut().push_clip(clip);
+    }
+
+    fn pop_clip(&mut self) -> Option<Rect> {
+        self.get_screen_mut().pop_clip()
+    }
+
+    /// The area drawing is restricted to, the whole canvas without clips.
+    fn get_clip(&self) -> Rect {
+        let canvas = Rect::from(self.get_size());
+        match self.get_screen().get_clip() {
+            Some(clip) => clip.intersection(&canvas),
+            None => canvas,
+        }
+    }
+
+    /// True if drawing at `p` is inside of the canvas and the current clip.
+    fn is_visible(&self, p: &Point) -> bool {
+        self.get_clip().contains(p)
+    }
+
+    /// The transform applied to shapes, images, sprites and layers. Single
+    /// pixels, chars, text and viewports are drawn in canvas coordinates,
+    /// the cells of ANSI art and layer text move with the transform but keep
+    /// their size.
+    fn get_transform(&self) -> Transform {
+        *self.get_screen().get_transform()
+    }
+
+    fn set_transform(&mut self, transform: Transform) {
+        self.get_screen_mut().set_transform(transform);
+    }
+
+    /// Saves the current transform, to be restored by `pop_transform`.
+    fn push_transform(&mut self) {
+        self.get_screen_mut().push_transform();
+    }
+
+    fn pop_transform(&mut self) {
+        self.get_screen_mut().pop_transform();
+    }
+
+    fn translate(&mut self, dx: f32, dy: f32) {
+        let transform = self.get_transform().translate(dx, dy);
+        self.set_transform(transform);
+    }
+
+    fn scale(&mut self, sx: f32, sy: f32) {
+        let transform = self.get_transform().scale(sx, sy);
+        self.set_transform(transform);
+    }
+
+    /// Rotates clockwise by `angle` radians around the local origin.
+    fn rotate(&mut self, angle: f32) {
+        let transform = self.get_transform().rotate(angle);
+        self.set_transform(transform);
+    }
+
+    /// The stroke of lines, polylines, polygons and rect outlines, None for
+    /// thin lines.
+    fn get_stroke(&self) -> Option<Stroke> {
+        self.get_screen().get_stroke().cloned()
+    }
+
+    fn set_stroke(&mut self, stroke: Option<Stroke>) {
+        self.get_screen_mut().set_stroke(stroke);
+    }
+
+    /// Calls `draw` with `stroke` and restores the previous stroke
+    /// afterwards.
+    fn with_stroke<F: FnOnce(&mut Self)>(&mut self, stroke: Stroke, draw: F) {
+        let previous = self.get_stroke();
+        self.set_stroke(Some(stroke));
+        draw(self);
+        self.set_stroke(previous);
+    }
+
+    /// Calls `draw` with everything but single pixels, chars and text
+    /// positioned in the world seen by the camera.
+    fn with_camera<F: FnOnce(&mut Self)>(&mut self, camera: &Camera, draw: F) {
+        self.push_transform();
+        let transform = self.get_transform().multiply(&camera.transform());
+        self.set_transform(transform);
+        draw(self);
+        self.pop_transform();
+    }
+
+    /// Calls `draw` without transform, for shapes which were already
+    /// transformed.
+    fn untransformed<F: FnOnce(&mut Self)>(&mut self, draw: F) {
+        let transform = self.get_transform();
+        self.set_transform(Transform::identity());
+        draw(self);
+        self.set_transform(transform);
+    }
+
+    /// The number of canvas pixels covered by one character cell.
+    fn get_char_size(&self) -> Point {
+        Point::new(1, 1)
+    }
+
+    /// Copies the framebuffer into an image with one pixel per canvas pixel,
+    /// e.g. for screenshots. Characters are rendered as approximate blocks.
+    fn to_image(&self) -> Image {
+        let screen = self.get_screen();
+        let screen_size = screen.get_size();
+        let char_size = self.get_char_size();
+        let mut image = Image::new(&Point::new(
+            screen_size.width() * char_size.width(),
+            screen_size.height() * char_size.height(),
+        ));
+
+        for (index, rgba) in image.pixels.iter_mut().enumerate() {
+            let x = index as i32 % image.size.width() / char_size.width();
+            let y = index as i32 / image.size.width() / char_size.height();
+            let cell = (x + y * screen_size.width()) as usize;
+            *rgba = screen.get_pixel(cell).block_rgba();
+        }
+
+        image
+    }
+
+    /// Applies a filter to everything drawn so far. Per pixel filters change
+    /// the fg and bg colors of each cell, so text is kept. Other filters work
+    /// on `to_image` and write the result back into the pixel cells, text
+    /// cells are left alone.
+    fn apply_filter(&mut self, filter: &Filter) {
+        if filter.is_per_pixel() {
+            let screen = self.get_screen_mut();
+            let size = screen.get_size();
+            for index in 0..(size.width() * size.height()) as usize {
+                let pixel = screen.get_pixel(index);
+                let pixel = Pixel {
+                    ch: pixel.ch,
+                    color: Color {
+                        fg: filter.apply_rgba(&pixel.color.fg),
+                        bg: filter.apply_rgba(&pixel.color.bg),
+                    },
+                };
+                screen.set_pixel(index, &pixel);
+            }
+            return;
+        }
+
+        let image = self.to_image().filter(filter);
+        let char_size = self.get_char_size();
+        let cells = self.get_screen().get_size().width();
+
+        // the pixels are replaced as they are, without the transform, blend
+        // mode and clip of the current drawing
+        let previous_blend_mode = self.get_blend_mode();
+        self.set_blend_mode(BlendMode::Copy);
+        self.get_screen_mut().push_clip(Rect::from(&image.size));
+        self.untransformed(|canvas| {
+            for y in 0..image.size.height() {
+                for x in 0..image.size.width() {
+                    let cell = x / char_size.width() + y / char_size.height() * cells;
+                    if canvas.get_screen().get_pixel(cell as usize).is_block() {
+                        let rgba = &image.pixels[(x + y * image.size.width()) as usize];
+                        canvas.draw_pixel(&Point::new(x, y), rgba);
+                    }
+                }
+            }
+        });
+        self.get_screen_mut().pop_clip();
+        self.set_blend_mode(previous_blend_mode);
+    }
+
     fn draw_rect(&mut self, r: &Rect, c: &Rgba) {
+        if !self.get_transform().is_identity() || self.get_stroke().is_some() {
+            // the outline runs through the centers of the border pixels
+            let x0 = r.pos.x;
+            let x1 = x0 + r.size.width() - 1;
+            let y0 = r.pos.y;
+            let y1 = y0 + r.size.height() - 1;
+            let corners = [(x0, y0), (x1, y0), (x1, y1), (x0, y1)].map(|(x, y)| Point::new(x, y));
+            self.draw_polygon(&Polygon::new(corners.to_vec()), c);
+            return;
+        }
+
         let x0 = r.pos.x;
         let x1 = x0 + r.size.width();
         let y0 = r.pos.y;
@@ -40,6 +264,33 @@ pub trait Canvas: Sized {
     }
 
     fn draw_rect_fill(&mut self, r: &Rect, c: &Rgba) {
+        let transform = self.get_transform();
+        if !transform.is_identity() {
+            // fills the pixels whose centers map back into the rect, integer
+            // coordinates are pixel centers as for paths
+            let bounds = transformed_bounds(&transform, r, &self.get_clip());
+            let Some(inverse) = transform.inverse() else {
+                return;
+            };
+            let area = (
+                r.pos.x as f32 - 0.5,
+                r.pos.y as f32 - 0.5,
+                (r.pos.x + r.size.width()) as f32 - 0.5,
+                (r.pos.y + r.size.height()) as f32 - 0.5,
+            );
+
+            for y in bounds.pos.y..bounds.pos.y + bounds.size.height() {
+                for x in bounds.pos.x..bounds.pos.x + bounds.size.width() {
+                    let (u, v) = inverse.apply(x as f32, y as f32);
+                    if u >= area.0 && v >= area.1 && u < area.2 && v < area.3 {
+                        self.draw_pixel(&Point::new(x, y), c);
+                    }
+                }
+            }
+            return;
+        }
+
+        let r = r.intersection(&self.get_clip());
         let x0 = r.pos.x;
         let x1 = x0 + r.size.width();
         let y0 = r.pos.y;
@@ -53,6 +304,19 @@ pub trait Canvas: Sized {
     }
 
     fn draw_line(&mut self, p1: &Point, p2: &Point, c: &Rgba) {
+        if self.get_stroke().is_some() {
+            draw_outline(self, &[(to_points(&[p1.clone(), p2.clone()]), false)], c);
+            return;
+        }
+
+        let transform = self.get_transform();
+        if !transform.is_identity() {
+            let p1 = transform.apply_point(p1);
+            let p2 = transform.apply_point(p2);
+            self.untransformed(|canvas| canvas.draw_line(&p1, &p2, c));
+            return;
+        }
+
         let mut x = p1.x;
         let mut y = p1.y;
         let dx = (p2.x - p1.x).abs();
@@ -80,7 +344,83 @@ pub trait Canvas: Sized {
         }
     }
 
+    /// Draws an anti-aliased line between sub-pixel positions, integer
+    /// coordinates are pixel centers. Partially covered pixels are blended
+    /// with reduced alpha.
+    fn draw_line_aa(&mut self, p1: (f32, f32), p2: (f32, f32), rgba: &Rgba) {
+        let transform = self.get_transform();
+        let (mut x0, mut y0) = transform.apply(p1.0, p1.1);
+        let (mut x1, mut y1) = transform.apply(p2.0, p2.1);
+
+        let steep = (y1 - y0).abs() > (x1 - x0).abs();
+        if steep {
+            std::mem::swap(&mut x0, &mut y0);
+            std::mem::swap(&mut x1, &mut y1);
+        }
+        if x0 > x1 {
+            std::mem::swap(&mut x0, &mut x1);
+            std::mem::swap(&mut y0, &mut y1);
+        }
+
+        let dx = x1 - x0;
+        let gradient = if dx == 0.0 { 1.0 } else { (y1 - y0) / dx };
+        let mut plot = |x: i32, y: i32, coverage: f32| {
+            let p = if steep {
+                Point::new(y, x)
+            } else {
+                Point::new(x, y)
+            };
+            draw_pixel_coverage(self, &p, rgba, coverage);
+        };
+
+        // the end points only cover the part of their pixel on the line,
+        // `fract` would be negative left of and above the canvas
+        let fract = |x: f32| x - x.floor();
+        let mut ends = [(x0, y0, 1.0 - fract(x0 + 0.5)), (x1, y1, fract(x1 + 0.5))];
+        for (x, y, gap) in ends.iter_mut() {
+            let x_end = x.round();
+            let y_end = *y + gradient * (x_end - *x);
+            let coverage = y_end - y_end.floor();
+            plot(x_end as i32, y_end.floor() as i32, (1.0 - coverage) * *gap);
+            plot(x_end as i32, y_end.floor() as i32 + 1, coverage * *gap);
+            *x = x_end;
+            *y = y_end;
+        }
+
+        let mut y = ends[0].1 + gradient;
+        for x in ends[0].0 as i32 + 1..ends[1].0 as i32 {
+            let coverage = y - y.floor();
+            plot(x, y.floor() as i32, 1.0 - coverage);
+            plot(x, y.floor() as i32 + 1, coverage);
+            y += gradient;
+        }
+    }
+
+    /// Draws an anti-aliased ellipse outline with radii `rx` and `ry`, e.g.
+    /// to make circles round on fullblock canvases with `ry = rx / 2`.
+    fn draw_ellipse_aa(&mut self, center: (f32, f32), rx: f32, ry: f32, rgba: &Rgba) {
+        draw_ellipse_coverage(self, center, rx, ry, rgba, |distance| 1.0 - distance.abs());
+    }
+
+    fn draw_ellipse_fill_aa(&mut self, center: (f32, f32), rx: f32, ry: f32, rgba: &Rgba) {
+        draw_ellipse_coverage(self, center, rx, ry, rgba, |distance| 0.5 - distance);
+    }
+
+    fn draw_circle_aa(&mut self, center: (f32, f32), radius: f32, rgba: &Rgba) {
+        self.draw_ellipse_aa(center, radius, radius, rgba);
+    }
+
+    fn draw_circle_fill_aa(&mut self, center: (f32, f32), radius: f32, rgba: &Rgba) {
+        self.draw_ellipse_fill_aa(center, radius, radius, rgba);
+    }
+
     fn draw_circle(&mut self, circle: &Circle, rgba: &Rgba) {
+        if !self.get_transform().is_identity() {
+            // e.g. non-uniform scaling turns circles into ellipses
+            self.draw_ellipse(&circle_ellipse(circle), rgba);
+            return;
+        }
+
         let cx = circle.pos.x;
         let cy = circle.pos.y;
         let mut x = circle.radius;
@@ -107,6 +447,11 @@ pub trait Canvas: Sized {
     }
 
     fn draw_circle_fill(&mut self, circle: &Circle, rgba: &Rgba) {
+        if !self.get_transform().is_identity() {
+            self.draw_ellipse_fill(&circle_ellipse(circle), rgba);
+            return;
+        }
+
         let cx = circle.pos.x;
         let cy = circle.pos.y;
         let radius = circle.radius;
@@ -117,8 +462,8 @@ pub trait Canvas: Sized {
 
         for x in min_x..max_x {
             for y in min_y..max_y {
-                let dx = (x as i32 - cx as i32).abs();
-                let dy = (y as i32 - cy as i32).abs();
+                let dx = (x - cx).abs();
+                let dy = (y - cy).abs();
                 let distance = dx * dx + dy * dy;
 
                 if distance < radius * radius {
@@ -128,7 +473,136 @@ pub trait Canvas: Sized {
         }
     }
 
+    fn draw_ellipse(&mut self, ellipse: &Ellipse, rgba: &Rgba) {
+        if self.get_stroke().is_some() || !self.get_transform().is_identity() {
+            let tolerance = flatten_tolerance(self);
+            draw_outline(self, &[(ellipse.flatten(tolerance), true)], rgba);
+            return;
+        }
+
+        for p in ellipse.outline() {
+            self.draw_pixel(&p, rgba);
+        }
+    }
+
+    fn draw_ellipse_fill(&mut self, ellipse: &Ellipse, rgba: &Rgba) {
+        if !self.get_transform().is_identity() {
+            let tolerance = flatten_tolerance(self);
+            fill_outline(
+                self,
+                &[ellipse.flatten_edges(tolerance)],
+                FillRule::NonZero,
+                rgba,
+            );
+            return;
+        }
+
+        draw_inside(self, &ellipse.boundary(), rgba, |p| ellipse.contains(p));
+    }
+
+    fn draw_arc(&mut self, arc: &Arc, rgba: &Rgba) {
+        if self.get_stroke().is_some() || !self.get_transform().is_identity() {
+            let tolerance = flatten_tolerance(self);
+            draw_outline(self, &[(arc.flatten(tolerance), false)], rgba);
+            return;
+        }
+
+        for p in arc.outline() {
+            self.draw_pixel(&p, rgba);
+        }
+    }
+
+    /// Draws the arc of the pie and the lines from its ends to the center.
+    fn draw_pie(&mut self, pie: &Pie, rgba: &Rgba) {
+        if self.get_stroke().is_some() || !self.get_transform().is_identity() {
+            let tolerance = flatten_tolerance(self);
+            draw_outline(self, &[(pie.flatten(tolerance), true)], rgba);
+            return;
+        }
+
+        let arc = pie.arc();
+        let (start, end) = arc.end_points();
+        let start = Point::new(start.0.round() as i32, start.1.round() as i32);
+        let end = Point::new(end.0.round() as i32, end.1.round() as i32);
+        self.draw_arc(&arc, rgba);
+        self.draw_line(&pie.pos, &start, rgba);
+        self.draw_line(&pie.pos, &end, rgba);
+    }
+
+    fn draw_pie_fill(&mut self, pie: &Pie, rgba: &Rgba) {
+        if !self.get_transform().is_identity() {
+            let tolerance = flatten_tolerance(self);
+            fill_outline(
+                self,
+                &[pie.flatten_edges(tolerance)],
+                FillRule::NonZero,
+                rgba,
+            );
+            return;
+        }
+
+        draw_inside(self, &pie.boundary(), rgba, |p| pie.contains(p));
+    }
+
+    fn draw_rounded_rect(&mut self, rect: &RoundedRect, rgba: &Rgba) {
+        if self.get_stroke().is_some() || !self.get_transform().is_identity() {
+            let tolerance = flatten_tolerance(self);
+            draw_outline(self, &[(rect.flatten(tolerance), true)], rgba);
+            return;
+        }
+
+        for p in rect.outline() {
+            self.draw_pixel(&p, rgba);
+        }
+    }
+
+    fn draw_rounded_rect_fill(&mut self, rect: &RoundedRect, rgba: &Rgba) {
+        if !self.get_transform().is_identity() {
+            let tolerance = flatten_tolerance(self);
+            fill_outline(
+                self,
+                &[rect.flatten_edges(tolerance)],
+                FillRule::NonZero,
+                rgba,
+            );
+            return;
+        }
+
+        draw_inside(self, &rect.rect, rgba, |p| rect.contains(p));
+    }
+
+    /// Draws the subpaths with thin lines or the stroke, curves are
+    /// flattened to lines within a quarter pixel.
+    fn draw_path(&mut self, path: &Path, rgba: &Rgba) {
+        let tolerance = flatten_tolerance(self);
+        draw_outline(self, &path.flatten(tolerance), rgba);
+    }
+
+    /// Fills the inside of the path as decided by `rule`, open subpaths are
+    /// closed.
+    fn draw_path_fill(&mut self, path: &Path, rule: FillRule, rgba: &Rgba) {
+        let tolerance = flatten_tolerance(self);
+        let polygons: Vec<_> = path
+            .flatten(tolerance)
+            .into_iter()
+            .map(|(points, _)| points)
+            .collect();
+        fill_outline(self, &polygons, rule, rgba);
+    }
+
     fn draw_polygon(&mut self, polygon: &Polygon, rgba: &Rgba) {
+        if self.get_stroke().is_some() {
+            draw_outline(self, &[(to_points(polygon.vertices()), true)], rgba);
+            return;
+        }
+
+        let transform = self.get_transform();
+        if !transform.is_identity() {
+            let polygon = transform_polygon(&transform, polygon);
+            self.untransformed(|canvas| canvas.draw_polygon(&polygon, rgba));
+            return;
+        }
+
         let vertices = polygon.vertices();
         for i in 0..vertices.len() - 1 {
             self.draw_line(&vertices[i], &vertices[i + 1], rgba);
@@ -136,7 +610,27 @@ pub trait Canvas: Sized {
         self.draw_line(&vertices[vertices.len() - 1], &vertices[0], rgba);
     }
 
+    /// Draws connected lines through `points`, unlike `draw_polygon` the last
+    /// point is not connected to the first.
+    fn draw_polyline(&mut self, points: &[Point], rgba: &Rgba) {
+        if self.get_stroke().is_some() {
+            draw_outline(self, &[(to_points(points), false)], rgba);
+            return;
+        }
+
+        for pair in points.windows(2) {
+            self.draw_line(&pair[0], &pair[1], rgba);
+        }
+    }
+
     fn draw_polygon_fill(&mut self, polygon: &Polygon, rgba: &Rgba) {
+        let transform = self.get_transform();
+        if !transform.is_identity() {
+            let polygon = transform_polygon(&transform, polygon);
+            self.untransformed(|canvas| canvas.draw_polygon_fill(&polygon, rgba));
+            return;
+        }
+
         let boundary = polygon.boundary();
         let min_x = boundary.pos.x;
         let max_x = min_x + boundary.size.width();
@@ -154,8 +648,40 @@ pub trait Canvas: Sized {
     }
 
     fn draw_image(&mut self, p: &Point, image: &Image) {
-        for y in 0..image.size.height() {
-            for x in 0..image.size.width() {
+        let transform = self.get_transform();
+        if !transform.is_identity() {
+            // samples the image at the pixel centers mapped back into it
+            let area = Rect {
+                pos: p.clone(),
+                size: image.size.clone(),
+            };
+            let bounds = transformed_bounds(&transform, &area, &self.get_clip());
+            let Some(inverse) = transform.inverse() else {
+                return;
+            };
+
+            for y in bounds.pos.y..bounds.pos.y + bounds.size.height() {
+                for x in bounds.pos.x..bounds.pos.x + bounds.size.width() {
+                    let (u, v) = inverse.apply(x as f32, y as f32);
+                    let src = Point::new(
+                        (u - p.x as f32 + 0.5).floor() as i32,
+                        (v - p.y as f32 + 0.5).floor() as i32,
+                    );
+                    if let Some(rgba) = image.get_pixel(&src) {
+                        self.draw_pixel(&Point::new(x, y), rgba);
+                    }
+                }
+            }
+            return;
+        }
+
+        let area = Rect {
+            pos: p.clone(),
+            size: image.size.clone(),
+        };
+        let visible = area.intersection(&self.get_clip());
+        for y in visible.pos.y - p.y..visible.pos.y - p.y + visible.size.height() {
+            for x in visible.pos.x - p.x..visible.pos.x - p.x + visible.size.width() {
                 let index = (x + y * image.size.width()) as usize;
                 let rgba = &image.pixels[index];
                 self.draw_pixel(&Point::new(p.x + x, p.y + y), rgba);
@@ -163,8 +689,926 @@ pub trait Canvas: Sized {
         }
     }
 
+    /// Draws the cells of the art as characters, `p` is its top left corner
+    /// in canvas pixels. The cells move with the transform but keep their
+    /// size.
+    fn draw_ansi_art(&mut self, p: &Point, art: &AnsiArt) {
+        let char_size = self.get_char_size();
+        let transform = self.get_transform();
+
+        for y in 0..art.size.height() {
+            for x in 0..art.size.width() {
+                let cell = &art.cells[(x + y * art.size.width()) as usize];
+                let cell_p = Point::new(p.x + x * char_size.width(), p.y + y * char_size.height());
+                self.draw_char(&transform.apply_point(&cell_p), &cell.color, cell.ch);
+            }
+        }
+    }
+
+    /// Draws the image scaled, rotated, flipped and tinted as described by
+    /// `draw`. Every covered canvas pixel samples the image, so there are no
+    /// holes.
+    fn draw_image_ex(&mut self, image: &Image, draw: &ImageDraw) {
+        let sample = |p: &Point| {
+            let src = draw.source_point(p, &image.size)?;
+            let rgba = draw.apply_tint(image.get_pixel(&src)?);
+            (rgba.a > 0).then_some(rgba)
+        };
+
+        let transform = self.get_transform();
+        if !transform.is_identity() {
+            // samples the destination at the pixel centers mapped back into it
+            let bounds = transformed_bounds(&transform, &draw.bounds(), &self.get_clip());
+            let Some(inverse) = transform.inverse() else {
+                return;
+            };
+
+            for y in bounds.pos.y..bounds.pos.y + bounds.size.height() {
+                for x in bounds.pos.x..bounds.pos.x + bounds.size.width() {
+                    let (u, v) = inverse.apply(x as f32, y as f32);
+                    let p = Point::new((u + 0.5).floor() as i32, (v + 0.5).floor() as i32);
+                    if let Some(rgba) = sample(&p) {
+                        self.draw_pixel(&Point::new(x, y), &rgba);
+                    }
+                }
+            }
+            return;
+        }
+
+        let bounds = draw.bounds().intersection(&self.get_clip());
+        for y in bounds.pos.y..bounds.pos.y + bounds.size.height() {
+            for x in bounds.pos.x..bounds.pos.x + bounds.size.width() {
+                let p = Point::new(x, y);
+                if let Some(rgba) = sample(&p) {
+                    self.draw_pixel(&p, &rgba);
+                }
+            }
+        }
+    }
+
+    /// Composites a layer at its offset. Pixels are blended with the layer's
+    /// opacity and blend mode and transformed like images, text cells of the
+    /// layer are drawn as characters which only move with the transform.
+    fn draw_layer<C: Canvas>(&mut self, layer: &Layer<C>) {
+        let previous_blend_mode = self.get_blend_mode();
+        self.set_blend_mode(layer.blend_mode);
+
+        let image = layer.to_image();
+        let screen = layer.get_screen();
+        let cells = screen.get_size();
+        let char_size = layer.get_char_size();
+        let with_opacity = |rgba: &Rgba| Rgba {
+            a: ((rgba.a as u32 * layer.opacity as u32 + 127) / 255) as u8,
+            ..rgba.clone()
+        };
+
+        let transform = self.get_transform();
+        if !transform.is_identity() {
+            // the pixels of text cells are transparent, the text follows below
+            let pixels = Image::from_fn(&image.size, |x, y| {
+                let cell = x / char_size.width() + y / char_size.height() * cells.width();
+                if screen.get_pixel(cell as usize).is_block() {
+                    with_opacity(&image.pixels[(x + y * image.size.width()) as usize])
+                } else {
+                    Rgba::new(0, 0, 0, 0)
+                }
+            });
+            self.draw_image(&layer.offset, &pixels);
+        }
+
+        for cy in 0..cells.height() {
+            for cx in 0..cells.width() {
+                let pixel = screen.get_pixel((cx + cy * cells.width()) as usize);
+                let x = cx * char_size.width();
+                let y = cy * char_size.height();
+
+                if !pixel.is_block() {
+                    let color = Color {
+                        bg: with_opacity(&pixel.color.bg),
+                        fg: with_opacity(&pixel.color.fg),
+                    };
+                    let p = Point::new(layer.offset.x + x, layer.offset.y + y);
+                    self.draw_char(&transform.apply_point(&p), &color, pixel.ch);
+                    continue;
+                }
+                if !transform.is_identity() {
+                    continue;
+                }
+
+                for py in y..y + char_size.height() {
+                    for px in x..x + char_size.width() {
+                        let rgba =
+                            with_opacity(&image.pixels[(px + py * image.size.width()) as usize]);
+                        let p = Point::new(layer.offset.x + px, layer.offset.y + py);
+                        self.draw_pixel(&p, &rgba);
+                    }
+                }
+            }
+        }
+
+        self.set_blend_mode(previous_blend_mode);
+    }
+
+    /// Copies the cells of a viewport onto the screen, independent of the
+    /// canvas types of both. The transform does not apply, the viewport has
+    /// its own camera. Only cells completely inside of the clip are copied.
+    fn draw_viewport<C: Canvas>(&mut self, viewport: &Viewport<C>) {
+        let clip = self.get_clip();
+        let char_size = self.get_char_size();
+        let x0 = (clip.pos.x + char_size.width() - 1) / char_size.width();
+        let y0 = (clip.pos.y + char_size.height() - 1) / char_size.height();
+        let x1 = (clip.pos.x + clip.size.width()) / char_size.width();
+        let y1 = (clip.pos.y + clip.size.height()) / char_size.height();
+        let cells = Rect::new(x0, y0, (x1 - x0).max(0), (y1 - y0).max(0));
+
+        viewport.blit(self.get_screen_mut(), &cells);
+    }
+
     fn draw_video(&mut self, p: &Point, video: &Video) {
         let image = &video.images[video.frame];
         self.draw_image(p, image);
     }
+
+    fn draw_sprite(&mut self, p: &Point, sheet: &SpriteSheet, frame: usize) {
+        let Some(rect) = sheet.frames.get(frame) else {
+            return;
+        };
+        if !self.get_transform().is_identity() {
+            self.draw_image(p, &sheet.frame_image(frame));
+            return;
+        }
+
+        for y in 0..rect.size.height() {
+            for x in 0..rect.size.width() {
+                if let Some(rgba) = sheet.get_pixel(frame, &Point::new(x, y)) {
+                    self.draw_pixel(&Point::new(p.x + x, p.y + y), rgba);
+                }
+            }
+        }
+    }
+
+    /// Draws the current frame of the player's clip.
+    fn draw_animation(&mut self, p: &Point, sheet: &SpriteSheet, player: &SpritePlayer) {
+        self.draw_sprite(p, sheet, player.frame());
+    }
+}
+
+// draws a pixel with its alpha reduced to the covered part of the pixel
+fn draw_pixel_coverage<C: Canvas>(canvas: &mut C, p: &Point, rgba: &Rgba, coverage: f32) {
+    let alpha = (rgba.a as f32 * coverage.clamp(0.0, 1.0)).round() as u8;
+    if alpha == 0 {
+        return;
+    }
+
+    canvas.draw_pixel(
+        p,
+        &Rgba {
+            a: alpha,
+            ..rgba.clone()
+        },
+    );
+}
+
+// `coverage` maps the approximate distance of a pixel center to the ellipse,
+// negative inside, to the covered part of the pixel. The pixels are mapped
+// back to the untransformed ellipse, so it rotates and shears along.
+fn draw_ellipse_coverage<C: Canvas, F: Fn(f32) -> f32>(
+    canvas: &mut C,
+    center: (f32, f32),
+    rx: f32,
+    ry: f32,
+    rgba: &Rgba,
+    coverage: F,
+) {
+    let transform = canvas.get_transform();
+    let Some(inverse) = transform.inverse() else {
+        return;
+    };
+    let scale = transform.scale_factor();
+    let rx = rx.max(0.5 / scale);
+    let ry = ry.max(0.5 / scale);
+
+    let margin = 2.0 / scale;
+    let (cx, cy) = center;
+    let corners = [(-1.0, -1.0), (1.0, -1.0), (-1.0, 1.0), (1.0, 1.0)]
+        .map(|(sx, sy)| transform.apply(cx + sx * (rx + margin), cy + sy * (ry + margin)));
+    let bounds = clipped_bounds(corners.into_iter(), &canvas.get_clip());
+
+    for y in bounds.pos.y..bounds.pos.y + bounds.size.height() {
+        for x in bounds.pos.x..bounds.pos.x + bounds.size.width() {
+            // the implicit function divided by its gradient on the canvas
+            // approximates the distance to the outline in canvas pixels
+            let (u, v) = inverse.apply(x as f32, y as f32);
+            let dx = u - cx;
+            let dy = v - cy;
+            let f = (dx / rx).powi(2) + (dy / ry).powi(2) - 1.0;
+            let fu = 2.0 * dx / (rx * rx);
+            let fv = 2.0 * dy / (ry * ry);
+            let gx = fu * inverse.a + fv * inverse.b;
+            let gy = fu * inverse.c + fv * inverse.d;
+            let gradient = (gx * gx + gy * gy).sqrt();
+            let distance = if gradient > 0.0 {
+                f / gradient
+            } else {
+                -rx.min(ry) * scale
+            };
+
+            draw_pixel_coverage(canvas, &Point::new(x, y), rgba, coverage(distance));
+        }
+    }
+}
+
+fn to_points(points: &[Point]) -> Vec<(f32, f32)> {
+    points.iter().map(|p| (p.x as f32, p.y as f32)).collect()
+}
+
+// draws the transformed lines through the points of the subpaths, with the
+// stroke if there is one, point coordinates are pixel centers
+fn draw_outline<C: Canvas>(canvas: &mut C, subpaths: &[(Vec<(f32, f32)>, bool)], rgba: &Rgba) {
+    let transform = canvas.get_transform();
+    let subpaths = subpaths.iter().map(|(points, closed)| {
+        let points: Vec<_> = points.iter().map(|p| transform.apply(p.0, p.1)).collect();
+        (points, *closed)
+    });
+
+    if let Some(stroke) = canvas.get_stroke() {
+        // the stroke is applied on the canvas, so its lengths are scaled
+        let scale = transform.scale_factor();
+        let stroke = Stroke {
+            width: stroke.width * scale,
+            dash: stroke.dash.iter().map(|length| length * scale).collect(),
+            dash_offset: stroke.dash_offset * scale,
+            ..stroke
+        };
+        let polygons: Vec<_> = subpaths
+            .flat_map(|(points, closed)| stroke.outline(&points, closed))
+            .collect();
+        fill_union(canvas, &polygons, rgba);
+        return;
+    }
+
+    for (points, closed) in subpaths {
+        let mut points: Vec<_> = points
+            .iter()
+            .map(|(x, y)| Point::new(x.round() as i32, y.round() as i32))
+            .collect();
+        if closed && points.len() > 2 {
+            points.push(points[0].clone());
+        }
+        points.dedup();
+        canvas.untransformed(|canvas| {
+            for pair in points.windows(2) {
+                canvas.draw_line(&pair[0], &pair[1], rgba);
+            }
+        });
+    }
+}
+
+// a quarter canvas pixel in the units of the transformed shapes, curves are
+// flattened to lines within it
+fn flatten_tolerance<C: Canvas>(canvas: &C) -> f32 {
+    0.25 / canvas.get_transform().scale_factor().max(1e-6)
+}
+
+// fills the transformed polygons, each pixel is drawn once so overlapping
+// polygons are not blended twice
+fn fill_outline<C: Canvas>(
+    canvas: &mut C,
+    polygons: &[Vec<(f32, f32)>],
+    rule: FillRule,
+    rgba: &Rgba,
+) {
+    let transform = canvas.get_transform();
+    let polygons: Vec<Vec<_>> = polygons
+        .iter()
+        .map(|polygon| polygon.iter().map(|p| transform.apply(p.0, p.1)).collect())
+        .collect();
+
+    draw_inside(
+        canvas,
+        &polygon_bounds(&polygons, &canvas.get_clip()),
+        rgba,
+        |p| {
+            let p = (p.x as f32, p.y as f32);
+            rule.is_inside(
+                polygons
+                    .iter()
+                    .map(|polygon| winding_number(polygon, p))
+                    .sum(),
+            )
+        },
+    );
+}
+
+// fills the union of the polygons, each polygon is only tested within its
+// own bounds and each pixel is drawn once, so overlaps are not blended twice
+fn fill_union<C: Canvas>(canvas: &mut C, polygons: &[Vec<(f32, f32)>], rgba: &Rgba) {
+    let bounds = polygon_bounds(polygons, &canvas.get_clip());
+    let width = bounds.size.width();
+    let mut mask = vec![false; (width * bounds.size.height()) as usize];
+
+    for polygon in polygons {
+        let area = polygon_bounds(std::slice::from_ref(polygon), &bounds);
+        for y in area.pos.y..area.pos.y + area.size.height() {
+            for x in area.pos.x..area.pos.x + area.size.width() {
+                let index = (x - bounds.pos.x + (y - bounds.pos.y) * width) as usize;
+                if !mask[index] && winding_number(polygon, (x as f32, y as f32)) != 0 {
+                    mask[index] = true;
+                }
+            }
+        }
+    }
+
+    for (i, covered) in mask.into_iter().enumerate() {
+        if covered {
+            let p = Point::new(i as i32 % width, i as i32 / width);
+            canvas.draw_pixel(&(&bounds.pos + &p), rgba);
+        }
+    }
+}
+
+fn draw_inside<C: Canvas, F: Fn(&Point) -> bool>(
+    canvas: &mut C,
+    boundary: &Rect,
+    rgba: &Rgba,
+    contains: F,
+) {
+    let boundary = boundary.intersection(&canvas.get_clip());
+    for y in boundary.pos.y..boundary.pos.y + boundary.size.height() {
+        for x in boundary.pos.x..boundary.pos.x + boundary.size.width() {
+            let p = Point::new(x, y);
+            if contains(&p) {
+                canvas.draw_pixel(&p, rgba);
+            }
+        }
+    }
+}
+
+// the pixels of `clip` whose centers may be inside of the polygons
+fn polygon_bounds(polygons: &[Vec<(f32, f32)>], clip: &Rect) -> Rect {
+    let points = polygons.iter().flatten();
+    clipped_bounds(points.flat_map(|p| [*p, (p.0 + 1.0, p.1 + 1.0)]), clip)
+}
+
+// the canvas pixels which the transformed rect may cover, its pixels reach
+// half a pixel beyond their centers
+fn transformed_bounds(transform: &Transform, r: &Rect, clip: &Rect) -> Rect {
+    let x0 = r.pos.x as f32 - 0.5;
+    let y0 = r.pos.y as f32 - 0.5;
+    let x1 = x0 + r.size.width() as f32;
+    let y1 = y0 + r.size.height() as f32;
+    let corners = [(x0, y0), (x1, y0), (x0, y1), (x1, y1)].map(|(x, y)| transform.apply(x, y));
+
+    clipped_bounds(corners.into_iter(), clip)
+}
+
+// the pixels of `clip` overlapping the bounding box of the points, the
+// floats are clamped first so huge coordinates do not overflow
+fn clipped_bounds<I: Iterator<Item = (f32, f32)> + Clone>(points: I, clip: &Rect) -> Rect {
+    let x0 = clip.pos.x as f32;
+    let y0 = clip.pos.y as f32;
+    let x1 = x0 + clip.size.width() as f32;
+    let y1 = y0 + clip.size.height() as f32;
+
+    let min_x = points
+        .clone()
+        .map(|p| p.0)
+        .fold(f32::MAX, f32::min)
+        .floor()
+        .max(x0);
+    let max_x = points
+        .clone()
+        .map(|p| p.0)
+        .fold(f32::MIN, f32::max)
+        .ceil()
+        .min(x1);
+    let min_y = points
+        .clone()
+        .map(|p| p.1)
+        .fold(f32::MAX, f32::min)
+        .floor()
+        .max(y0);
+    let max_y = points.map(|p| p.1).fold(f32::MIN, f32::max).ceil().min(y1);
+
+    if !(min_x < max_x && min_y < max_y) {
+        return Rect::new(clip.pos.x, clip.pos.y, 0, 0);
+    }
+    Rect::new(
+        min_x as i32,
+        min_y as i32,
+        (max_x - min_x) as i32,
+        (max_y - min_y) as i32,
+    )
+}
+
+fn circle_ellipse(circle: &Circle) -> Ellipse {
+    Ellipse::new(circle.pos.x, circle.pos.y, circle.radius, circle.radius)
+}
+
+fn transform_polygon(transform: &Transform, polygon: &Polygon) -> Polygon {
+    let vertices = polygon
+        .vertices()
+        .iter()
+        .map(|vertex| transform.apply_point(vertex))
+        .collect();
+    Polygon::new(vertices)
+}
+
+#[cfg(test)]
+mod test {
+    use super::fullblock::FullblockCanvas;
+    use super::*;
+    use crate::model::rect::Rect;
+
+    fn canvas(size: &Point) -> FullblockCanvas {
+        let mut canvas = FullblockCanvas::new();
+        canvas.init(RawTerminalScreen::headless(size));
+        canvas
+    }
+
+    #[test]
+    fn it_draws_a_scaled_image_without_holes() {
+        let image = Image::from_fn(&Point::new(2, 1), |x, _| {
+            [Rgba::red(), Rgba::blue()][x as usize].clone()
+        });
+        let mut canvas = canvas(&Point::new(5, 3));
+
+        canvas.draw_image_ex(&image, &ImageDraw::new(Rect::new(1, 1, 4, 2)));
+
+        let pixels = canvas.to_image().pixels;
+        assert_eq!(pixels[5 + 1], Rgba::red());
+        assert_eq!(pixels[5 + 2], Rgba::red());
+        assert_eq!(pixels[2 * 5 + 3], Rgba::blue());
+        assert_eq!(pixels[2 * 5 + 4], Rgba::blue());
+        assert_eq!(pixels[5], Rgba::black());
+    }
+
+    #[test]
+    fn it_draws_a_rotated_half_transparent_image() {
+        let image = Image::from_fn(&Point::new(2, 1), |_, _| Rgba::white());
+        let mut canvas = canvas(&Point::new(3, 3));
+        let draw = ImageDraw::new(Rect::new(1, 1, 2, 1))
+            .pivot(Point::new(0, 0))
+            .rotate(std::f32::consts::FRAC_PI_2)
+            .alpha(128);
+
+        canvas.draw_image_ex(&image, &draw);
+
+        let pixels = canvas.to_image().pixels;
+        let gray = Rgba::new(128, 128, 128, 255);
+        assert_eq!(pixels[3], gray);
+        assert_eq!(pixels[2 * 3], gray);
+        assert_eq!(pixels[3 + 1], Rgba::black());
+    }
+
+    #[test]
+    fn it_filters_the_framebuffer() {
+        let mut canvas = canvas(&Point::new(3, 1));
+        canvas.draw_pixel(&Point::new(1, 0), &Rgba::white());
+
+        canvas.apply_filter(&Filter::Invert);
+        let pixels = canvas.to_image().pixels;
+        assert_eq!(pixels, vec![Rgba::white(), Rgba::black(), Rgba::white()]);
+
+        canvas.apply_filter(&Filter::BoxBlur(1));
+        let gray = Rgba::new(170, 170, 170, 255);
+        assert_eq!(canvas.to_image().pixels[1], gray);
+    }
+
+    #[test]
+    fn it_filters_regardless_of_the_drawing_state() {
+        let draw = |canvas: &mut FullblockCanvas| {
+            canvas.draw_pixel(&Point::new(1, 0), &Rgba::white());
+            canvas.draw_char(&Point::new(2, 0), &Color::text(), 'a');
+        };
+        let mut expected = canvas(&Point::new(3, 1));
+        draw(&mut expected);
+        expected.apply_filter(&Filter::BoxBlur(1));
+        let mut canvas = canvas(&Point::new(3, 1));
+        draw(&mut canvas);
+
+        canvas.translate(1.0, 0.0);
+        canvas.push_clip(Rect::new(0, 0, 1, 1));
+        canvas.with_blend_mode(BlendMode::Add, |canvas| {
+            canvas.apply_filter(&Filter::BoxBlur(1));
+        });
+
+        assert_eq!(canvas.to_image().pixels, expected.to_image().pixels);
+        assert_eq!(canvas.get_screen().get_pixel(2).ch, 'a');
+        assert_eq!(canvas.get_clip(), Rect::new(0, 0, 1, 1));
+    }
+
+    #[test]
+    fn it_draws_with_a_blend_mode() {
+        let mut canvas = canvas(&Point::new(2, 1));
+        canvas.draw_rect_fill(&Rect::new(0, 0, 2, 1), &Rgba::new(100, 0, 0, 255));
+
+        canvas.with_blend_mode(BlendMode::Add, |canvas| {
+            canvas.draw_pixel(&Point::new(0, 0), &Rgba::new(100, 100, 0, 255));
+        });
+        canvas.draw_pixel(&Point::new(1, 0), &Rgba::new(100, 100, 0, 255));
+
+        let pixels = canvas.to_image().pixels;
+        assert_eq!(pixels[0], Rgba::new(200, 100, 0, 255));
+        assert_eq!(pixels[1], Rgba::new(100, 100, 0, 255));
+        assert_eq!(canvas.get_blend_mode(), BlendMode::SourceOver);
+    }
+
+    #[test]
+    fn it_ignores_out_of_bounds_drawing() {
+        let mut canvas = canvas(&Point::new(2, 2));
+
+        canvas.draw_pixel(&Point::new(2, 0), &Rgba::red());
+        canvas.draw_pixel(&Point::new(-1, 1), &Rgba::red());
+        canvas.draw_text(&Point::new(1, 5), &Color::text(), "xyz");
+        canvas.draw_text(&Point::new(1, 1), &Color::text(), "xyz");
+
+        let screen = canvas.get_screen();
+        assert!(canvas.to_image().pixels.iter().all(|p| *p != Rgba::red()));
+        assert_eq!(screen.get_pixel(2).ch, ' ');
+        assert_eq!(screen.get_pixel(3).ch, 'x');
+    }
+
+    #[test]
+    fn it_clips_to_nested_rects() {
+        let mut canvas = canvas(&Point::new(4, 4));
+
+        canvas.push_clip(Rect::new(1, 1, 3, 3));
+        canvas.push_clip(Rect::new(0, 0, 3, 3));
+        assert_eq!(canvas.get_clip(), Rect::new(1, 1, 2, 2));
+        canvas.draw_rect_fill(&Rect::new(0, 0, 4, 4), &Rgba::red());
+        canvas.draw_text(&Point::new(0, 2), &Color::text(), "abcd");
+
+        assert_eq!(canvas.pop_clip(), Some(Rect::new(1, 1, 2, 2)));
+        assert_eq!(canvas.get_clip(), Rect::new(1, 1, 3, 3));
+        canvas.pop_clip();
+        assert_eq!(canvas.get_clip(), Rect::new(0, 0, 4, 4));
+
+        let image = canvas.to_image();
+        let red: Vec<usize> = (0..16)
+            .filter(|i| image.pixels[*i] == Rgba::red())
+            .collect();
+        assert_eq!(red, vec![5, 6]);
+        let text: String = (8..12)
+            .map(|i| canvas.get_screen().get_pixel(i).ch)
+            .collect();
+        assert_eq!(text, " bc ");
+    }
+
+    #[test]
+    fn it_transforms_shapes_and_images() {
+        let mut canvas = canvas(&Point::new(6, 6));
+        let image = Image::from_fn(&Point::new(2, 1), |x, _| {
+            [Rgba::red(), Rgba::blue()][x as usize].clone()
+        });
+
+        canvas.push_transform();
+        canvas.translate(2.0, 1.0);
+        canvas.scale(2.0, 2.0);
+        canvas.draw_rect_fill(&Rect::new(0, 0, 1, 1), &Rgba::green());
+        canvas.draw_image(&Point::new(0, 1), &image);
+        canvas.pop_transform();
+        canvas.draw_line(&Point::new(0, 5), &Point::new(1, 5), &Rgba::white());
+
+        let pixels = canvas.to_image().pixels;
+        let at = |x: usize, y: usize| pixels[x + 6 * y].clone();
+        // pixel centers are scaled around the origin, so the scaled pixels
+        // reach half a scaled pixel beyond the translation
+        assert_eq!(at(1, 0), Rgba::green());
+        assert_eq!(at(2, 1), Rgba::green());
+        assert_eq!(at(3, 0), Rgba::black());
+        assert_eq!(at(2, 3), Rgba::red());
+        assert_eq!(at(3, 3), Rgba::blue());
+        assert_eq!(at(4, 2), Rgba::blue());
+        assert_eq!(at(5, 2), Rgba::black());
+        assert_eq!(at(1, 5), Rgba::white());
+        assert!(canvas.get_transform().is_identity());
+    }
+
+    #[test]
+    fn it_rotates_lines_around_the_origin() {
+        let mut canvas = canvas(&Point::new(5, 5));
+
+        canvas.translate(2.0, 2.0);
+        canvas.rotate(std::f32::consts::FRAC_PI_2);
+        canvas.draw_line(&Point::new(0, 0), &Point::new(2, 0), &Rgba::red());
+
+        let pixels = canvas.to_image().pixels;
+        let red: Vec<usize> = (0..25).filter(|i| pixels[*i] == Rgba::red()).collect();
+        assert_eq!(red, vec![2 * 5 + 2, 3 * 5 + 2, 4 * 5 + 2]);
+    }
+
+    #[test]
+    fn it_draws_through_a_camera() {
+        let mut canvas = canvas(&Point::new(4, 4));
+        let mut camera = Camera::new(canvas.get_size());
+        camera.look_at(&Point::new(100, 100));
+
+        canvas.with_camera(&camera, |canvas| {
+            canvas.draw_rect_fill(&Rect::new(100, 100, 1, 1), &Rgba::red());
+        });
+
+        let pixels = canvas.to_image().pixels;
+        assert_eq!(pixels[2 + 2 * 4], Rgba::red());
+        assert!(canvas.get_transform().is_identity());
+    }
+
+    #[test]
+    fn it_draws_anti_aliased_lines() {
+        let mut canvas = canvas(&Point::new(5, 3));
+
+        canvas.draw_line_aa((0.0, 1.0), (4.0, 1.0), &Rgba::white());
+        let pixels = canvas.to_image().pixels;
+        assert!((6..9).all(|i| pixels[i] == Rgba::white()));
+        // end points at pixel centers cover half of their pixel
+        assert_eq!(pixels[5], Rgba::new(128, 128, 128, 255));
+        assert_eq!(pixels[9], Rgba::new(128, 128, 128, 255));
+        assert!((0..5).chain(10..15).all(|i| pixels[i] == Rgba::black()));
+
+        canvas.clear();
+        canvas.draw_line_aa((-2.75, 1.0), (2.0, 1.0), &Rgba::white());
+        let pixels = canvas.to_image().pixels;
+        assert!((5..7).all(|i| pixels[i] == Rgba::white()));
+        assert_eq!(pixels[7], Rgba::new(128, 128, 128, 255));
+
+        canvas.clear();
+        canvas.draw_line_aa((0.0, 0.5), (4.0, 0.5), &Rgba::white());
+        let pixels = canvas.to_image().pixels;
+        assert_eq!(pixels[2], Rgba::new(128, 128, 128, 255));
+        assert_eq!(pixels[5 + 2], Rgba::new(128, 128, 128, 255));
+    }
+
+    #[test]
+    fn it_draws_anti_aliased_circles() {
+        let mut canvas = canvas(&Point::new(9, 9));
+
+        canvas.draw_circle_aa((4.0, 4.0), 3.0, &Rgba::white());
+        let pixels = canvas.to_image().pixels;
+        assert_eq!(pixels[4 + 9], Rgba::white());
+        assert_eq!(pixels[4 + 4 * 9], Rgba::black());
+        let partial = &pixels[2 + 2 * 9];
+        assert!(partial.r > 0 && partial.r < 255);
+
+        canvas.draw_circle_fill_aa((4.0, 4.0), 3.0, &Rgba::red());
+        let pixels = canvas.to_image().pixels;
+        assert_eq!(pixels[4 + 4 * 9], Rgba::red());
+        assert_eq!(pixels[0], Rgba::black());
+    }
+
+    #[test]
+    fn it_strokes_thick_joined_lines() {
+        let mut canvas = canvas(&Point::new(8, 8));
+        let stroke = Stroke::new(3.0);
+
+        canvas.with_stroke(stroke, |canvas| {
+            let points = [Point::new(1, 1), Point::new(5, 1), Point::new(5, 6)];
+            canvas.draw_polyline(&points, &Rgba::red());
+        });
+
+        let pixels = canvas.to_image().pixels;
+        let red: Vec<(usize, usize)> = (0..64)
+            .filter(|i| pixels[*i] == Rgba::red())
+            .map(|i| (i % 8, i / 8))
+            .collect();
+        // the miter fills the outer corner, the butt caps end at the points
+        assert!(red.contains(&(6, 0)));
+        assert!(red.contains(&(1, 2)) && !red.contains(&(0, 1)));
+        assert!(red.contains(&(4, 5)) && !red.contains(&(5, 7)));
+        assert!(!red.contains(&(3, 4)));
+        assert!(canvas.get_stroke().is_none());
+    }
+
+    #[test]
+    fn it_strokes_dashed_lines() {
+        let mut canvas = canvas(&Point::new(8, 1));
+        let stroke = Stroke::new(1.0).dash(vec![2.0, 2.0], 0.0);
+
+        canvas.with_stroke(stroke, |canvas| {
+            canvas.draw_line(&Point::new(0, 0), &Point::new(8, 0), &Rgba::red());
+        });
+
+        let pixels = canvas.to_image().pixels;
+        let red: Vec<bool> = pixels.iter().map(|p| *p == Rgba::red()).collect();
+        assert_eq!(
+            red,
+            vec![true, true, false, false, true, true, false, false]
+        );
+    }
+
+    #[test]
+    fn it_draws_ellipses_arcs_and_pies() {
+        let mut canvas = canvas(&Point::new(9, 5));
+
+        canvas.draw_ellipse_fill(&Ellipse::new(4, 2, 4, 2), &Rgba::blue());
+        canvas.draw_ellipse(&Ellipse::new(4, 2, 4, 2), &Rgba::white());
+        let pixels = canvas.to_image().pixels;
+        assert_eq!(pixels[0], Rgba::black());
+        assert_eq!(pixels[2 * 9], Rgba::white());
+        assert_eq!(pixels[4], Rgba::white());
+        assert_eq!(pixels[4 + 2 * 9], Rgba::blue());
+
+        canvas.clear();
+        let quarter = std::f32::consts::FRAC_PI_2;
+        canvas.draw_pie_fill(&Pie::new(4, 2, 4, 2, 0.0, quarter), &Rgba::red());
+        canvas.draw_arc(
+            &Arc::new(4, 2, 4, 2, quarter, 2.0 * quarter),
+            &Rgba::green(),
+        );
+        let pixels = canvas.to_image().pixels;
+        assert_eq!(pixels[6 + 3 * 9], Rgba::red());
+        assert_eq!(pixels[2 + 3 * 9], Rgba::black());
+        assert_eq!(pixels[2 * 9], Rgba::green());
+        assert_eq!(pixels[8 + 2 * 9], Rgba::red());
+        assert_eq!(pixels[4], Rgba::black());
+    }
+
+    #[test]
+    fn it_draws_rounded_rects() {
+        let mut canvas = canvas(&Point::new(6, 6));
+        let rect = RoundedRect::new(0, 0, 6, 6, 2);
+
+        canvas.draw_rounded_rect_fill(&rect, &Rgba::red());
+        let pixels = canvas.to_image().pixels;
+        assert_eq!(pixels[0], Rgba::black());
+        assert_eq!(pixels[2], Rgba::red());
+        assert_eq!(pixels[3 * 6], Rgba::red());
+
+        canvas.clear();
+        canvas.translate(1.0, 1.0);
+        canvas.draw_rounded_rect_fill(&RoundedRect::new(0, 0, 5, 5, 0), &Rgba::red());
+        let pixels = canvas.to_image().pixels;
+        let red = pixels.iter().filter(|p| **p == Rgba::red()).count();
+        assert_eq!(red, 25);
+        assert_eq!(pixels[5 + 5 * 6], Rgba::red());
+    }
+
+    #[test]
+    fn it_fills_paths_with_a_fill_rule() {
+        let mut canvas = canvas(&Point::new(8, 8));
+        let path = Path::parse("M 0 0 H 7 V 7 H 0 Z M 2 2 H 5 V 5 H 2 Z").unwrap();
+
+        canvas.draw_path_fill(&path, FillRule::NonZero, &Rgba::red());
+        assert_eq!(canvas.to_image().pixels[3 + 3 * 8], Rgba::red());
+
+        canvas.clear();
+        canvas.draw_path_fill(&path, FillRule::EvenOdd, &Rgba::red());
+        let pixels = canvas.to_image().pixels;
+        assert_eq!(pixels[3 + 3 * 8], Rgba::black());
+        assert_eq!(pixels[1 + 3 * 8], Rgba::red());
+    }
+
+    #[test]
+    fn it_strokes_curved_paths() {
+        let mut canvas = canvas(&Point::new(9, 5));
+        let path = Path::new().move_to(0.0, 4.0).quad_to(4.0, -4.0, 8.0, 4.0);
+
+        canvas.draw_path(&path, &Rgba::white());
+        let pixels = canvas.to_image().pixels;
+        assert_eq!(pixels[4 * 9], Rgba::white());
+        assert_eq!(pixels[4], Rgba::white());
+        assert_eq!(pixels[8 + 4 * 9], Rgba::white());
+        assert_eq!(pixels[4 + 4 * 9], Rgba::black());
+
+        canvas.clear();
+        canvas.with_stroke(Stroke::new(3.0), |canvas| {
+            canvas.draw_path(&path, &Rgba::white())
+        });
+        let pixels = canvas.to_image().pixels;
+        assert_eq!(pixels[4 + 9], Rgba::white());
+        assert_eq!(pixels[4 + 3 * 9], Rgba::black());
+    }
+
+    #[test]
+    fn it_only_visits_visible_pixels() {
+        let mut canvas = canvas(&Point::new(4, 4));
+        let world = Rect::new(-1_000_000_000, -1_000_000_000, 2_000_000_000, 2_000_000_000);
+
+        canvas.push_clip(Rect::new(1, 1, 2, 2));
+        canvas.scale(2.0, 2.0);
+        canvas.draw_rect_fill(&world, &Rgba::red());
+        canvas.set_transform(Transform::identity());
+        canvas.draw_rect_fill(&world, &Rgba::red());
+        canvas.draw_image(
+            &Point::new(-2, -2),
+            &Image::from_fn(&Point::new(8, 8), |_, _| Rgba::blue()),
+        );
+
+        let pixels = canvas.to_image().pixels;
+        let blue: Vec<usize> = (0..16).filter(|i| pixels[*i] == Rgba::blue()).collect();
+        assert_eq!(blue, vec![5, 6, 9, 10]);
+    }
+
+    #[test]
+    fn it_scales_circles_into_ellipses() {
+        let mut canvas = canvas(&Point::new(11, 7));
+
+        canvas.translate(5.0, 3.0);
+        canvas.scale(2.0, 1.0);
+        canvas.draw_circle(&Circle::new(0, 0, 2), &Rgba::white());
+
+        let pixels = canvas.to_image().pixels;
+        let at = |x: usize, y: usize| pixels[x + 11 * y].clone();
+        assert_eq!(at(1, 3), Rgba::white());
+        assert_eq!(at(9, 3), Rgba::white());
+        assert_eq!(at(5, 1), Rgba::white());
+        assert_eq!(at(5, 5), Rgba::white());
+        assert_eq!(at(5, 0), Rgba::black());
+    }
+
+    #[test]
+    fn it_rotates_anti_aliased_ellipses() {
+        let mut canvas = canvas(&Point::new(9, 9));
+
+        canvas.translate(4.0, 4.0);
+        canvas.rotate(std::f32::consts::FRAC_PI_2);
+        canvas.draw_ellipse_fill_aa((0.0, 0.0), 3.0, 1.0, &Rgba::white());
+
+        let pixels = canvas.to_image().pixels;
+        assert_eq!(pixels[4 + 2 * 9], Rgba::white());
+        assert_eq!(pixels[4 + 6 * 9], Rgba::white());
+        assert_eq!(pixels[2 + 4 * 9], Rgba::black());
+        assert_eq!(pixels[6 + 4 * 9], Rgba::black());
+    }
+
+    #[test]
+    fn it_scales_dashes_with_the_stroke() {
+        let mut canvas = canvas(&Point::new(8, 1));
+        let stroke = Stroke::new(0.5).dash(vec![1.0, 1.0], 0.0);
+
+        canvas.scale(2.0, 2.0);
+        canvas.with_stroke(stroke, |canvas| {
+            canvas.draw_line(&Point::new(0, 0), &Point::new(4, 0), &Rgba::red());
+        });
+
+        let pixels = canvas.to_image().pixels;
+        let red: Vec<bool> = pixels.iter().map(|p| *p == Rgba::red()).collect();
+        assert_eq!(
+            red,
+            vec![true, true, false, false, true, true, false, false]
+        );
+    }
+
+    #[test]
+    fn it_fills_translated_shapes_up_to_their_edges() {
+        let mut canvas = canvas(&Point::new(12, 12));
+        let red = |canvas: &FullblockCanvas, x: usize, y: usize| {
+            canvas.to_image().pixels[x + y * 12] == Rgba::red()
+        };
+
+        canvas.translate(1.0, 1.0);
+        canvas.draw_ellipse_fill(&Ellipse::new(4, 4, 4, 3), &Rgba::red());
+        assert!(red(&canvas, 1, 5) && red(&canvas, 9, 5));
+        assert!(red(&canvas, 5, 2) && red(&canvas, 5, 8));
+        assert!(!red(&canvas, 9, 8));
+
+        canvas.clear();
+        let half = std::f32::consts::PI;
+        canvas.draw_pie_fill(&Pie::new(4, 4, 4, 4, 0.0, half), &Rgba::red());
+        assert!(red(&canvas, 9, 5) && red(&canvas, 5, 9) && red(&canvas, 1, 5));
+        assert!(!red(&canvas, 5, 1));
+    }
+
+    #[test]
+    fn it_fills_paths_with_huge_coordinates() {
+        let mut canvas = canvas(&Point::new(4, 4));
+        let path = Path::parse("M -1e30 -1e30 L 1e30 -1e30 L 1e30 1e30 Z").unwrap();
+
+        canvas.draw_path_fill(&path, FillRule::NonZero, &Rgba::red());
+        assert_eq!(canvas.to_image().pixels[3], Rgba::red());
+    }
+
+    #[test]
+    fn it_draws_sprites_through_a_camera() {
+        let mut canvas = canvas(&Point::new(4, 4));
+        let image = Image::from_fn(&Point::new(2, 1), |x, _| {
+            [Rgba::red(), Rgba::green()][x as usize].clone()
+        });
+        let sheet = SpriteSheet::grid(image, &Point::new(1, 1));
+        let mut camera = Camera::new(canvas.get_size());
+        camera.look_at(&Point::new(100, 100));
+
+        canvas.with_camera(&camera, |canvas| {
+            canvas.draw_sprite(&Point::new(100, 100), &sheet, 1);
+        });
+
+        let pixels = canvas.to_image().pixels;
+        assert_eq!(pixels[2 + 2 * 4], Rgba::green());
+        assert_eq!(pixels.iter().filter(|p| **p == Rgba::green()).count(), 1);
+    }
+
+    #[test]
+    fn it_scales_rects_and_rounded_rects_alike() {
+        let mut rect = canvas(&Point::new(8, 8));
+        let mut rounded_rect = canvas(&Point::new(8, 8));
+
+        for canvas in [&mut rect, &mut rounded_rect] {
+            canvas.translate(1.0, 1.0);
+            canvas.scale(2.0, 2.0);
+        }
+        rect.draw_rect_fill(&Rect::new(0, 0, 3, 2), &Rgba::red());
+        rounded_rect.draw_rounded_rect_fill(&RoundedRect::new(0, 0, 3, 2, 0), &Rgba::red());
+
+        assert_eq!(rect.to_image().pixels, rounded_rect.to_image().pixels);
+        assert_eq!(rect.to_image().pixels[0], Rgba::red());
+    }
 }
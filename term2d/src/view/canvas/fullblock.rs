@@ -1,7 +1,6 @@
 use super::Canvas;
 use crate::model::color::Color;
 use crate::model::point::Point;
-use crate::model::rect::Rect;
 use crate::model::rgba::Rgba;
 use crate::view::screen::RawTerminalScreen;
 use crate::view::screen::Screen;
@@ -11,6 +10,12 @@ pub struct FullblockCanvas {
     size: Point,
 }
 
+impl Default for FullblockCanvas {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl FullblockCanvas {
     pub fn new() -> Self {
         Self {
@@ -26,6 +31,14 @@ impl Canvas for FullblockCanvas {
         self.screen = Some(screen);
     }
 
+    fn get_screen(&self) -> &RawTerminalScreen {
+        self.screen.as_ref().unwrap()
+    }
+
+    fn get_screen_mut(&mut self) -> &mut RawTerminalScreen {
+        self.screen.as_mut().unwrap()
+    }
+
     fn get_size(&self) -> &Point {
         &self.size
     }
@@ -40,21 +53,25 @@ impl Canvas for FullblockCanvas {
     }
 
     fn draw_pixel(&mut self, p: &Point, rgb: &Rgba) {
-        let screen = self.screen.as_mut().unwrap();
-
-        if !Rect::from(&self.size).contains(p) {
+        if !self.is_visible(p) {
             return;
         }
 
-        screen.draw_pixel(p, rgb);
+        self.screen.as_mut().unwrap().draw_pixel(p, rgb);
     }
 
     fn draw_char(&mut self, p: &Point, color: &Color, ch: char) {
+        if !self.is_visible(p) {
+            return;
+        }
+
         self.screen.as_mut().unwrap().draw_char(p, color, ch);
     }
 
     fn draw_text(&mut self, p: &Point, color: &Color, text: &str) {
-        self.screen.as_mut().unwrap().draw_text(p, color, text);
+        for (i, ch) in text.chars().enumerate() {
+            self.draw_char(&Point::new(p.x + i as i32, p.y), color, ch);
+        }
     }
 
     fn display(&mut self) {
@@ -1,18 +1,26 @@
 use super::Canvas;
 use crate::model::color::Color;
+use crate::model::image::Image;
 use crate::model::point::Point;
-use crate::model::rect::Rect;
 use crate::model::rgba::Rgba;
+use crate::view::screen::Pixel;
 use crate::view::screen::RawTerminalScreen;
 use crate::view::screen::Screen;
 
 const HALF_BLOCK: char = '▀';
+const LOWER_HALF_BLOCK: char = '▄';
 
 pub struct HalfblockCanvas {
     screen: Option<RawTerminalScreen>,
     size: Point,
 }
 
+impl Default for HalfblockCanvas {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl HalfblockCanvas {
     pub fn new() -> Self {
         Self {
@@ -29,6 +37,14 @@ impl Canvas for HalfblockCanvas {
         self.screen = Some(screen);
     }
 
+    fn get_screen(&self) -> &RawTerminalScreen {
+        self.screen.as_ref().unwrap()
+    }
+
+    fn get_screen_mut(&mut self) -> &mut RawTerminalScreen {
+        self.screen.as_mut().unwrap()
+    }
+
     fn get_size(&self) -> &Point {
         &self.size
     }
@@ -39,53 +55,128 @@ impl Canvas for HalfblockCanvas {
         &self.size
     }
 
+    fn get_char_size(&self) -> Point {
+        Point::new(1, 2)
+    }
+
     fn clear(&mut self) {
         self.screen.as_mut().unwrap().clear();
     }
 
     fn draw_pixel(&mut self, p: &Point, rgb: &Rgba) {
+        if !self.is_visible(p) {
+            return;
+        }
+
         let screen = self.screen.as_mut().unwrap();
         let x = p.x;
         let y = p.y / 2;
 
-        if !Rect::from(&self.size).contains(p) {
-            return;
-        }
-
+        // only the drawn half is blended, the other half keeps its color
         let old_color = screen.get_color(&Point::new(x, y));
+        let blend_mode = screen.get_blend_mode();
 
         let new_color = if p.y % 2 == 0 {
             Color {
+                fg: blend_mode.blend(rgb, &old_color.fg),
                 bg: old_color.bg,
-                fg: rgb.clone(),
             }
         } else {
             Color {
-                bg: rgb.clone(),
                 fg: old_color.fg,
+                bg: blend_mode.blend(rgb, &old_color.bg),
             }
         };
 
-        screen.draw_char(&Point::new(x, y), &new_color, HALF_BLOCK);
+        let index = (x + y * screen.get_size().width()) as usize;
+        screen.set_pixel(
+            index,
+            &Pixel {
+                ch: HALF_BLOCK,
+                color: new_color,
+            },
+        );
     }
 
     fn draw_char(&mut self, p: &Point, color: &Color, ch: char) {
+        if !self.is_visible(p) {
+            return;
+        }
+
         let scaled_point = &Point::new(p.x, p.y / 2);
         self.screen
             .as_mut()
             .unwrap()
-            .draw_char(&scaled_point, color, ch);
+            .draw_char(scaled_point, color, ch);
     }
 
     fn draw_text(&mut self, p: &Point, color: &Color, text: &str) {
-        let scaled_point = &Point::new(p.x, p.y / 2);
-        self.screen
-            .as_mut()
-            .unwrap()
-            .draw_text(scaled_point, color, text);
+        for (i, ch) in text.chars().enumerate() {
+            self.draw_char(&Point::new(p.x + i as i32, p.y), color, ch);
+        }
     }
 
     fn display(&mut self) {
         self.screen.as_mut().unwrap().display();
     }
+
+    fn to_image(&self) -> Image {
+        let screen = self.screen.as_ref().unwrap();
+        let screen_size = screen.get_size();
+        let mut image = Image::new(&Point::new(screen_size.width(), 2 * screen_size.height()));
+
+        let width = image.size.width();
+        for y in 0..screen_size.height() {
+            for x in 0..screen_size.width() {
+                let pixel = screen.get_pixel((x + y * width) as usize);
+                let (top, bottom) = match pixel.ch {
+                    HALF_BLOCK => (pixel.color.fg.clone(), pixel.color.bg.clone()),
+                    LOWER_HALF_BLOCK => (pixel.color.bg.clone(), pixel.color.fg.clone()),
+                    _ => (pixel.block_rgba(), pixel.block_rgba()),
+                };
+
+                image.pixels[(x + 2 * y * width) as usize] = top;
+                image.pixels[(x + (2 * y + 1) * width) as usize] = bottom;
+            }
+        }
+
+        image
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn it_reconstructs_two_pixels_per_cell() {
+        let mut canvas = HalfblockCanvas::new();
+        canvas.init(RawTerminalScreen::headless(&Point::new(2, 1)));
+        canvas.draw_pixel(&Point::new(0, 0), &Rgba::red());
+        canvas.draw_pixel(&Point::new(0, 1), &Rgba::blue());
+        canvas.draw_pixel(&Point::new(1, 1), &Rgba::green());
+
+        let image = canvas.to_image();
+
+        assert_eq!(image.size, Point::new(2, 2));
+        assert_eq!(image.pixels[0], Rgba::red());
+        assert_eq!(image.pixels[2], Rgba::blue());
+        assert_eq!(image.pixels[3], Rgba::green());
+    }
+
+    #[test]
+    fn it_renders_text_cells_as_blocks() {
+        let mut canvas = HalfblockCanvas::new();
+        canvas.init(RawTerminalScreen::headless(&Point::new(1, 1)));
+        let color = Color {
+            bg: Rgba::black(),
+            fg: Rgba::white(),
+        };
+        canvas.draw_char(&Point::new(0, 0), &color, 'x');
+
+        let image = canvas.to_image();
+
+        assert_eq!(image.pixels[0], image.pixels[1]);
+        assert_eq!(image.pixels[0], Rgba::new(128, 128, 128, 255));
+    }
 }
@@ -0,0 +1,242 @@
+use super::Canvas;
+use crate::model::blend::BlendMode;
+use crate::model::color::Color;
+use crate::model::image::Image;
+use crate::model::point::Point;
+use crate::model::rgba::Rgba;
+use crate::view::screen::Pixel;
+use crate::view::screen::RawTerminalScreen;
+use crate::view::screen::Screen;
+
+/// An offscreen canvas which is composited onto another canvas with
+/// `Canvas::draw_layer`. It starts transparent and keeps its content between
+/// frames, so static parts of a scene only need to be drawn once.
+pub struct Layer<C: Canvas> {
+    canvas: C,
+    /// The position of the layer on the target canvas in canvas pixels.
+    pub offset: Point,
+    /// Multiplies the alpha of every pixel, 255 is fully opaque.
+    pub opacity: u8,
+    /// How the layer is combined with the target canvas, drawing into the
+    /// layer uses the blend mode of its own canvas.
+    pub blend_mode: BlendMode,
+    valid: bool,
+}
+
+impl<C: Canvas> Layer<C> {
+    /// Creates a layer of `size` canvas pixels, `canvas` determines how the
+    /// pixels map to characters and should match the target canvas.
+    pub fn new(mut canvas: C, size: &Point) -> Self {
+        let cells = cells(&canvas.get_char_size(), size);
+        canvas.init(RawTerminalScreen::headless(&cells));
+
+        let mut layer = Self {
+            canvas,
+            offset: Point::new(0, 0),
+            opacity: 255,
+            blend_mode: BlendMode::default(),
+            valid: false,
+        };
+        layer.clear();
+        layer
+    }
+
+    pub fn with_offset(self, offset: Point) -> Self {
+        Self { offset, ..self }
+    }
+
+    pub fn with_opacity(self, opacity: u8) -> Self {
+        Self { opacity, ..self }
+    }
+
+    pub fn with_blend_mode(self, blend_mode: BlendMode) -> Self {
+        Self { blend_mode, ..self }
+    }
+
+    /// Changes the size of the layer, the content is cleared.
+    pub fn set_size(&mut self, size: &Point) {
+        let cells = cells(&self.canvas.get_char_size(), size);
+        if cells == *self.get_screen().get_size() {
+            return;
+        }
+
+        self.init(RawTerminalScreen::headless(&cells));
+        self.valid = false;
+    }
+
+    /// Marks the content as outdated, the next `update` redraws it.
+    pub fn invalidate(&mut self) {
+        self.valid = false;
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.valid
+    }
+
+    /// Clears the layer and calls `draw` if the layer was invalidated or
+    /// never drawn, otherwise the cached content is kept.
+    pub fn update<F: FnOnce(&mut Self)>(&mut self, draw: F) {
+        if self.valid {
+            return;
+        }
+
+        self.clear();
+        draw(self);
+        self.valid = true;
+    }
+}
+
+// the number of cells covering `size` canvas pixels
+fn cells(char_size: &Point, size: &Point) -> Point {
+    Point::new(
+        (size.width() + char_size.width() - 1) / char_size.width(),
+        (size.height() + char_size.height() - 1) / char_size.height(),
+    )
+}
+
+impl<C: Canvas> Canvas for Layer<C> {
+    fn init(&mut self, screen: RawTerminalScreen) {
+        self.canvas.init(screen);
+        self.clear();
+    }
+
+    fn get_screen(&self) -> &RawTerminalScreen {
+        self.canvas.get_screen()
+    }
+
+    fn get_screen_mut(&mut self) -> &mut RawTerminalScreen {
+        self.canvas.get_screen_mut()
+    }
+
+    fn get_size(&self) -> &Point {
+        self.canvas.get_size()
+    }
+
+    /// Layers are not connected to the terminal, their size only changes
+    /// with `set_size`.
+    fn resize(&mut self) -> &Point {
+        self.canvas.get_size()
+    }
+
+    fn clear(&mut self) {
+        let screen = self.canvas.get_screen_mut();
+        let size = screen.get_size();
+        let transparent = Pixel {
+            ch: ' ',
+            color: Color {
+                bg: Rgba::transparent(),
+                fg: Rgba::transparent(),
+            },
+        };
+
+        for index in 0..(size.width() * size.height()).max(0) as usize {
+            screen.set_pixel(index, &transparent);
+        }
+    }
+
+    fn draw_pixel(&mut self, p: &Point, rgb: &Rgba) {
+        self.canvas.draw_pixel(p, rgb);
+    }
+
+    fn draw_char(&mut self, p: &Point, color: &Color, ch: char) {
+        self.canvas.draw_char(p, color, ch);
+    }
+
+    fn draw_text(&mut self, p: &Point, color: &Color, text: &str) {
+        self.canvas.draw_text(p, color, text);
+    }
+
+    /// Layers are displayed by drawing them onto a canvas.
+    fn display(&mut self) {}
+
+    fn get_char_size(&self) -> Point {
+        self.canvas.get_char_size()
+    }
+
+    fn to_image(&self) -> Image {
+        self.canvas.to_image()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::fullblock::FullblockCanvas;
+    use super::super::halfblock::HalfblockCanvas;
+    use super::*;
+    use crate::model::rect::Rect;
+
+    fn target(size: &Point) -> HalfblockCanvas {
+        let mut canvas = HalfblockCanvas::new();
+        canvas.init(RawTerminalScreen::headless(size));
+        canvas
+    }
+
+    #[test]
+    fn it_starts_transparent() {
+        let layer = Layer::new(HalfblockCanvas::new(), &Point::new(3, 3));
+
+        assert_eq!(layer.get_screen().get_size(), &Point::new(3, 2));
+        assert!(layer.to_image().pixels.iter().all(|p| p.a == 0));
+    }
+
+    #[test]
+    fn it_accepts_a_negative_size() {
+        let mut layer = Layer::new(HalfblockCanvas::new(), &Point::new(-3, 4));
+        layer.draw_pixel(&Point::new(0, 0), &Rgba::red());
+        layer.set_size(&Point::new(-3, -4));
+        let mut canvas = target(&Point::new(3, 2));
+
+        canvas.draw_layer(&layer);
+
+        assert!(canvas.to_image().pixels.iter().all(|p| *p == Rgba::black()));
+    }
+
+    #[test]
+    fn it_composites_pixels_at_the_offset() {
+        let mut layer =
+            Layer::new(HalfblockCanvas::new(), &Point::new(2, 2)).with_offset(Point::new(1, 1));
+        layer.draw_pixel(&Point::new(0, 0), &Rgba::red());
+        layer.draw_pixel(&Point::new(1, 1), &Rgba::blue());
+        let mut canvas = target(&Point::new(3, 2));
+        canvas.draw_rect_fill(&Rect::from(canvas.get_size()), &Rgba::white());
+
+        canvas.draw_layer(&layer);
+
+        let image = canvas.to_image();
+        assert_eq!(image.pixels[3 + 1], Rgba::red());
+        assert_eq!(image.pixels[2 * 3 + 2], Rgba::blue());
+        assert_eq!(image.pixels[2 * 3 + 1], Rgba::white());
+        assert_eq!(image.pixels[3 + 2], Rgba::white());
+    }
+
+    #[test]
+    fn it_composites_text_and_opacity() {
+        let mut layer = Layer::new(FullblockCanvas::new(), &Point::new(2, 1)).with_opacity(128);
+        layer.draw_pixel(&Point::new(0, 0), &Rgba::white());
+        layer.draw_char(&Point::new(1, 0), &Color::text(), 'x');
+        let mut canvas = FullblockCanvas::new();
+        canvas.init(RawTerminalScreen::headless(&Point::new(2, 1)));
+
+        canvas.draw_layer(&layer);
+
+        let screen = canvas.get_screen();
+        assert_eq!(screen.get_pixel(0).color.bg, Rgba::new(128, 128, 128, 255));
+        assert_eq!(screen.get_pixel(1).ch, 'x');
+    }
+
+    #[test]
+    fn it_caches_until_invalidated() {
+        let mut layer = Layer::new(FullblockCanvas::new(), &Point::new(1, 1));
+        let mut draws = 0;
+
+        layer.update(|_| draws += 1);
+        layer.update(|_| draws += 1);
+        assert_eq!(draws, 1);
+
+        layer.invalidate();
+        layer.update(|_| draws += 1);
+        layer.set_size(&Point::new(2, 1));
+        layer.update(|_| draws += 1);
+        assert_eq!(draws, 3);
+    }
+}
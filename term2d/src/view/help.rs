@@ -0,0 +1,44 @@
+use crate::model::color::Color;
+use crate::model::keymap::keys_name;
+use crate::model::keymap::KeyMap;
+use crate::model::point::Point;
+use crate::model::rgba::Rgba;
+
+use super::canvas::Canvas;
+
+/// Draws a box listing the active bindings of the keymap with their
+/// descriptions, `p` is the top left corner in canvas coordinates.
+pub fn draw_keymap_help<A: Clone, C: Canvas>(canvas: &mut C, p: &Point, keymap: &KeyMap<A>) {
+    let color = Color {
+        bg: Rgba::new(32, 32, 32, 255),
+        fg: Rgba::new(200, 200, 200, 255),
+    };
+    let title_color = Color {
+        bg: Rgba::new(64, 64, 64, 255),
+        fg: Rgba::white(),
+    };
+
+    let bindings = keymap.active_bindings();
+    let names: Vec<String> = bindings.iter().map(|b| keys_name(&b.keys)).collect();
+    let name_width = names.iter().map(|n| n.chars().count()).max().unwrap_or(0);
+
+    let title = format!(" keys: {} ", keymap.mode());
+    let mut lines = Vec::new();
+    for (name, binding) in names.iter().zip(&bindings) {
+        lines.push(format!(" {name:<name_width$}  {} ", binding.description));
+    }
+
+    let width = lines
+        .iter()
+        .chain(std::iter::once(&title))
+        .map(|line| line.chars().count())
+        .max()
+        .unwrap_or(0);
+
+    let row_height = canvas.get_char_size().height();
+    canvas.draw_text(p, &title_color, &format!("{title:<width$}"));
+    for (i, line) in lines.iter().enumerate() {
+        let row = Point::new(p.x, p.y + (i as i32 + 1) * row_height);
+        canvas.draw_text(&row, &color, &format!("{line:<width$}"));
+    }
+}
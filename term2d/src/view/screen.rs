@@ -1,14 +1,21 @@
 use std::io::stdout;
 use std::io::Stdout;
 use std::io::Write;
+use std::path::Path;
 
 use termion::raw::IntoRawMode;
 use termion::raw::RawTerminal;
 
 use crate::model::ansiesc::cursor_goto;
+use crate::model::ansiesc::COLOR_RESET;
+use crate::model::blend::BlendMode;
 use crate::model::color::Color;
 use crate::model::point::Point;
+use crate::model::rect::Rect;
 use crate::model::rgba::Rgba;
+use crate::model::stroke::Stroke;
+use crate::model::transform::Transform;
+use crate::view::asciicast::AsciicastWriter;
 
 #[derive(Debug, Clone)]
 pub struct Pixel {
@@ -25,6 +32,28 @@ impl Default for Pixel {
     }
 }
 
+impl Pixel {
+    /// The color of the cell as a single block, characters other than full
+    /// blocks and spaces are approximated by mixing fg and bg.
+    pub fn block_rgba(&self) -> Rgba {
+        match self.ch {
+            ' ' => self.color.bg.clone(),
+            '█' => self.color.fg.clone(),
+            _ => Rgba {
+                a: 128,
+                ..self.color.fg.clone()
+            }
+            .blend(&self.color.bg),
+        }
+    }
+
+    /// True for characters which only show colors, i.e. spaces, full and
+    /// half blocks, as opposed to text.
+    pub fn is_block(&self) -> bool {
+        matches!(self.ch, ' ' | '█' | '▀' | '▄')
+    }
+}
+
 impl From<char> for Pixel {
     fn from(ch: char) -> Self {
         Self {
@@ -35,10 +64,20 @@ impl From<char> for Pixel {
 }
 
 pub struct RawTerminalScreen {
+    asciicast: Option<AsciicastWriter>,
+    blend_mode: BlendMode,
+    // in canvas coordinates, each rect is inside of the previous one
+    clip_stack: Vec<Rect>,
     drop_strings: Vec<String>,
-    main_display: RawTerminal<Stdout>,
+    // resize keeps the size instead of following the terminal
+    fixed_size: bool,
+    // None for headless screens
+    main_display: Option<RawTerminal<Stdout>>,
     pixel_buffer: Vec<Pixel>,
     size: Point,
+    stroke: Option<Stroke>,
+    transform: Transform,
+    transform_stack: Vec<Transform>,
 }
 
 pub trait Screen {
@@ -48,12 +87,24 @@ pub trait Screen {
     fn get_size(&self) -> &Point;
     fn resize(&mut self) -> &Point;
     fn clear(&mut self);
+    /// The blend mode used by `draw_pixel` and `draw_char`, screens without
+    /// blend modes draw source over.
+    fn get_blend_mode(&self) -> BlendMode {
+        BlendMode::default()
+    }
+
+    fn set_blend_mode(&mut self, _mode: BlendMode) {}
 
+    /// Points outside of the screen are ignored.
     fn draw_pixel(&mut self, p: &Point, rgba: &Rgba) {
+        if !Rect::from(self.get_size()).contains(p) {
+            return;
+        }
+
         let index = (self.get_size().width() * p.y + p.x) as usize;
 
         let old_rgba = &self.get_pixel(index).color.bg;
-        let new_rgba = rgba.blend(old_rgba);
+        let new_rgba = self.get_blend_mode().blend(rgba, old_rgba);
 
         let new_color = Color {
             fg: Rgba::black(),
@@ -69,14 +120,20 @@ pub trait Screen {
         );
     }
 
+    /// Points outside of the screen are ignored.
     fn draw_char(&mut self, p: &Point, color: &Color, ch: char) {
+        if !Rect::from(self.get_size()).contains(p) {
+            return;
+        }
+
         let index = (self.get_size().width() * p.y + p.x) as usize;
         let pixel = &self.get_pixel(index);
+        let blend_mode = self.get_blend_mode();
 
         let old_bg = &pixel.color.bg;
-        let new_bg = color.bg.blend(old_bg);
+        let new_bg = blend_mode.blend(&color.bg, old_bg);
         let old_fg = &pixel.color.fg;
-        let new_fg = color.fg.blend(old_fg);
+        let new_fg = blend_mode.blend(&color.fg, old_fg);
 
         let new_color = Color {
             bg: new_bg,
@@ -98,48 +155,117 @@ pub trait Screen {
         }
     }
 
+    /// The color at `p`, the default color outside of the screen.
     fn get_color(&self, p: &Point) -> Color {
+        if !Rect::from(self.get_size()).contains(p) {
+            return Color::default();
+        }
+
         let index = (self.get_size().width() * p.y + p.x) as usize;
         self.get_pixel(index).color.clone()
     }
 
     fn display(&mut self) {
         let mut s = String::new();
-        let Point {
-            x: width,
-            y: height,
-        } = *self.get_size();
-
-        for y in 0..height {
-            let row = y + 1;
-            s.push_str(&cursor_goto(1, row));
-
-            let mut i = (width * y) as usize;
-            let i_max = (width * (y + 1)) as usize;
-            let mut last_color = &self.get_pixel(i).color;
-            s.push_str(&format!("{last_color}"));
-
-            while i < i_max {
-                let Pixel { color, ch } = &self.get_pixel(i);
-
-                if color.bg != last_color.bg && color.fg != last_color.fg {
-                    s.push_str(&format!("{color}"));
-                } else if color.bg != last_color.bg && color.fg == last_color.fg {
-                    s.push_str(&color.bg.bg_ansi());
-                } else if color.bg == last_color.bg && color.fg != last_color.fg {
-                    s.push_str(&color.fg.fg_ansi());
+
+        for y in 0..self.get_size().height() {
+            s.push_str(&cursor_goto(1, y + 1));
+            s.push_str(&ansi_row(self, y));
+        }
+
+        self.flush_pixels(s.as_bytes());
+    }
+
+    /// Renders the cells as ANSI-escaped text, e.g. to be saved and printed
+    /// with `cat` later.
+    fn to_ansi(&self) -> String {
+        let mut s = String::new();
+
+        for y in 0..self.get_size().height() {
+            s.push_str(&ansi_row(self, y));
+            s.push_str(COLOR_RESET);
+            s.push('\n');
+        }
+
+        s
+    }
+
+    /// Renders the cells as a standalone HTML `<pre>` element with inline
+    /// styles.
+    fn to_html(&self) -> String {
+        let width = self.get_size().width();
+        let mut s = String::from("<pre style=\"font-family: monospace; line-height: 1;\">");
+
+        for y in 0..self.get_size().height() {
+            if y > 0 {
+                s.push('\n');
+            }
+
+            let mut x = 0;
+            while x < width {
+                let color = &self.get_pixel((width * y + x) as usize).color;
+                s.push_str(&format!(
+                    "<span style=\"color: #{}; background-color: #{};\">",
+                    color.fg.to_hex(),
+                    color.bg.to_hex()
+                ));
+
+                while x < width {
+                    let pixel = self.get_pixel((width * y + x) as usize);
+                    if pixel.color != *color {
+                        break;
+                    }
+
+                    match pixel.ch {
+                        '&' => s.push_str("&amp;"),
+                        '<' => s.push_str("&lt;"),
+                        '>' => s.push_str("&gt;"),
+                        ch => s.push(ch),
+                    }
+                    x += 1;
                 }
 
-                last_color = color;
-                s.push(*ch);
-                i += 1;
+                s.push_str("</span>");
             }
         }
 
-        self.flush_pixels(s.as_bytes());
+        s.push_str("</pre>\n");
+        s
     }
 }
 
+// Escapes one row, only the color components which change are emitted.
+fn ansi_row<S: Screen + ?Sized>(screen: &S, y: i32) -> String {
+    let mut s = String::new();
+    let width = screen.get_size().width();
+    if width <= 0 {
+        return s;
+    }
+
+    let mut i = (width * y) as usize;
+    let i_max = (width * (y + 1)) as usize;
+    let mut last_color = &screen.get_pixel(i).color;
+    s.push_str(&String::from(last_color));
+
+    while i < i_max {
+        let Pixel { color, ch } = &screen.get_pixel(i);
+
+        if color.bg != last_color.bg && color.fg != last_color.fg {
+            s.push_str(&String::from(color));
+        } else if color.bg != last_color.bg && color.fg == last_color.fg {
+            s.push_str(&color.bg.bg_ansi());
+        } else if color.bg == last_color.bg && color.fg != last_color.fg {
+            s.push_str(&color.fg.fg_ansi());
+        }
+
+        last_color = color;
+        s.push(*ch);
+        i += 1;
+    }
+
+    s
+}
+
 impl Screen for RawTerminalScreen {
     fn get_pixel(&self, index: usize) -> &Pixel {
         &self.pixel_buffer[index]
@@ -150,8 +276,16 @@ impl Screen for RawTerminalScreen {
     }
 
     fn flush_pixels(&mut self, raw_pixels: &[u8]) {
-        self.main_display.write_all(raw_pixels).unwrap();
-        self.main_display.flush().unwrap();
+        if let Some(asciicast) = self.asciicast.as_mut() {
+            let _ = asciicast.output(raw_pixels);
+        }
+
+        let Some(main_display) = self.main_display.as_mut() else {
+            return;
+        };
+
+        main_display.write_all(raw_pixels).unwrap();
+        main_display.flush().unwrap();
     }
 
     fn get_size(&self) -> &Point {
@@ -159,50 +293,179 @@ impl Screen for RawTerminalScreen {
     }
 
     fn resize(&mut self) -> &Point {
+        if self.is_headless() || self.fixed_size {
+            return &self.size;
+        }
+
         let (cols, rows) = termion::terminal_size().unwrap();
-        self.size = Point::new(cols as i32, rows as i32);
+        self.set_size(Point::new(cols as i32, rows as i32));
         &self.size
     }
 
     fn clear(&mut self) {
-        let buffer_size = (self.size.width() * self.size.height()) as usize;
+        let buffer_size = (self.size.width() * self.size.height()).max(0) as usize;
         self.pixel_buffer = vec![Pixel::default(); buffer_size];
     }
+
+    fn get_blend_mode(&self) -> BlendMode {
+        self.blend_mode
+    }
+
+    fn set_blend_mode(&mut self, mode: BlendMode) {
+        self.blend_mode = mode;
+    }
 }
 
 impl Drop for RawTerminalScreen {
     fn drop(&mut self) {
-        write!(self.main_display, "{}", self.drop_strings.join("")).unwrap();
-        self.main_display.flush().unwrap();
+        let drop_string = self.drop_strings.join("");
+
+        if let Some(asciicast) = self.asciicast.as_mut() {
+            let _ = asciicast.output(drop_string.as_bytes());
+        }
+
+        let Some(main_display) = self.main_display.as_mut() else {
+            return;
+        };
+
+        write!(main_display, "{drop_string}").unwrap();
+        main_display.flush().unwrap();
     }
 }
 
 impl RawTerminalScreen {
-    pub fn new(drop_strings: Vec<String>) -> Self {
-        let mut main_display = stdout().into_raw_mode().unwrap();
+    /// Takes over the terminal, `asciicast` records everything written to it
+    /// from the start, see `record_asciicast`.
+    pub fn new(drop_strings: Vec<String>, asciicast: Option<&Path>) -> std::io::Result<Self> {
+        let main_display = stdout().into_raw_mode().unwrap();
+
+        let (cols, rows) = termion::terminal_size().unwrap();
+        let size = Point::new(cols as i32, rows as i32);
+        let buffer_size = (cols * rows) as usize;
 
-        write!(
-            main_display,
+        let pixel_buffer = vec![Pixel::from(' '); buffer_size];
+        let asciicast = match asciicast {
+            Some(path) => Some(AsciicastWriter::create(path, &size)?),
+            None => None,
+        };
+
+        let mut screen = Self {
+            asciicast,
+            blend_mode: BlendMode::default(),
+            clip_stack: Vec::new(),
+            drop_strings,
+            fixed_size: false,
+            main_display: Some(main_display),
+            pixel_buffer,
+            size,
+            stroke: None,
+            transform: Transform::identity(),
+            transform_stack: Vec::new(),
+        };
+
+        let setup = format!(
             "{}{}{}",
             termion::cursor::Hide,
             termion::cursor::Goto(1, 1),
             termion::clear::All,
-        )
-        .unwrap();
+        );
+        screen.flush_pixels(setup.as_bytes());
+        Ok(screen)
+    }
 
-        main_display.flush().unwrap();
+    /// A screen which is not connected to the terminal, displaying it does
+    /// nothing. Useful to replay recordings and for tests.
+    pub fn headless(size: &Point) -> Self {
+        let buffer_size = (size.width() * size.height()).max(0) as usize;
 
-        let (cols, rows) = termion::terminal_size().unwrap();
-        let buffer_size = (cols * rows) as usize;
+        Self {
+            asciicast: None,
+            blend_mode: BlendMode::default(),
+            clip_stack: Vec::new(),
+            drop_strings: Vec::new(),
+            fixed_size: false,
+            main_display: None,
+            pixel_buffer: vec![Pixel::from(' '); buffer_size],
+            size: size.clone(),
+            stroke: None,
+            transform: Transform::identity(),
+            transform_stack: Vec::new(),
+        }
+    }
 
-        let pixel_buffer = vec![Pixel::from(' '); buffer_size];
+    pub fn is_headless(&self) -> bool {
+        self.main_display.is_none()
+    }
 
-        Self {
-            drop_strings,
-            main_display,
-            pixel_buffer,
-            size: Point::new(cols as i32, rows as i32),
+    /// Gives the screen a size which `resize` keeps instead of following the
+    /// terminal, e.g. the recorded size of a replay. Terminal screens cut off
+    /// what does not fit into the terminal.
+    pub fn set_fixed_size(&mut self, size: &Point) {
+        self.fixed_size = true;
+        self.set_size(size.clone());
+        self.clear();
+    }
+
+    /// Starts recording everything written to the screen into an asciicast
+    /// v2 file, which can be played with `asciinema play`.
+    pub fn record_asciicast<P: AsRef<Path>>(&mut self, path: P) -> std::io::Result<()> {
+        self.asciicast = Some(AsciicastWriter::create(path, &self.size)?);
+        Ok(())
+    }
+
+    /// Stores a clip rect for the canvas drawing on the screen, see
+    /// `Canvas::push_clip`.
+    pub fn push_clip(&mut self, rect: Rect) {
+        self.clip_stack.push(rect);
+    }
+
+    pub fn pop_clip(&mut self) -> Option<Rect> {
+        self.clip_stack.pop()
+    }
+
+    pub fn get_clip(&self) -> Option<&Rect> {
+        self.clip_stack.last()
+    }
+
+    /// The stroke of the canvas drawing on the screen, see
+    /// `Canvas::set_stroke`.
+    pub fn get_stroke(&self) -> Option<&Stroke> {
+        self.stroke.as_ref()
+    }
+
+    pub fn set_stroke(&mut self, stroke: Option<Stroke>) {
+        self.stroke = stroke;
+    }
+
+    /// The transform of the canvas drawing on the screen, see
+    /// `Canvas::push_transform`.
+    pub fn get_transform(&self) -> &Transform {
+        &self.transform
+    }
+
+    pub fn set_transform(&mut self, transform: Transform) {
+        self.transform = transform;
+    }
+
+    pub fn push_transform(&mut self) {
+        self.transform_stack.push(self.transform);
+    }
+
+    pub fn pop_transform(&mut self) {
+        if let Some(transform) = self.transform_stack.pop() {
+            self.transform = transform;
+        }
+    }
+
+    fn set_size(&mut self, size: Point) {
+        if size == self.size {
+            return;
+        }
+
+        if let Some(asciicast) = self.asciicast.as_mut() {
+            let _ = asciicast.resize(&size);
         }
+        self.size = size;
     }
 }
 
@@ -439,4 +702,57 @@ mod test {
 
         assert_eq!(s, t);
     }
+
+    #[test]
+    fn it_renders_ansi_text() {
+        let mut screen = TestScreen::new();
+        screen.size = Point::new(2, 2);
+        screen.clear();
+        screen.draw_pixel(&Point::new(1, 0), &Rgba::green());
+
+        let black_black = String::from(&Color::default());
+        let green_bg = Rgba::green().bg_ansi();
+
+        assert_eq!(
+            screen.to_ansi(),
+            format!("{black_black} {green_bg} {COLOR_RESET}\n{black_black}  {COLOR_RESET}\n")
+        );
+    }
+
+    #[test]
+    fn it_renders_a_screen_without_columns() {
+        let mut screen = TestScreen::new();
+        screen.size = Point::new(0, 2);
+        screen.clear();
+
+        assert_eq!(screen.to_ansi(), format!("{COLOR_RESET}\n{COLOR_RESET}\n"));
+        screen.display();
+    }
+
+    #[test]
+    fn it_renders_html() {
+        let mut screen = TestScreen::new();
+        screen.size = Point::new(3, 1);
+        screen.clear();
+        screen.draw_text(&Point::new(0, 0), &Color::text(), "<&");
+
+        let html = screen.to_html();
+
+        assert_eq!(
+            html,
+            "<pre style=\"font-family: monospace; line-height: 1;\">\
+             <span style=\"color: #c8c8c8; background-color: #000000;\">&lt;&amp;</span>\
+             <span style=\"color: #000000; background-color: #000000;\"> </span>\
+             </pre>\n"
+        );
+    }
+
+    #[test]
+    fn it_keeps_a_fixed_size() {
+        let mut screen = RawTerminalScreen::headless(&Point::new(2, 2));
+
+        screen.set_fixed_size(&Point::new(3, 1));
+        assert_eq!(screen.resize(), &Point::new(3, 1));
+        assert_eq!(screen.to_ansi().lines().count(), 1);
+    }
 }
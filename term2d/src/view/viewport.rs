@@ -0,0 +1,167 @@
+use crate::model::camera::Camera;
+use crate::model::point::Point;
+use crate::model::rect::Rect;
+use crate::view::canvas::Canvas;
+use crate::view::screen::RawTerminalScreen;
+use crate::view::screen::Screen;
+
+/// A part of the terminal with its own canvas, e.g. a halfblock map next to a
+/// text log. Its cells are copied onto the screen by `Canvas::draw_viewport`,
+/// so all viewports are shown with one `display`.
+pub struct Viewport<C: Canvas> {
+    /// The area on the screen in character cells.
+    pub rect: Rect,
+    /// Positions everything drawn with `draw` in a world, if set.
+    pub camera: Option<Camera>,
+    canvas: C,
+}
+
+impl<C: Canvas> Viewport<C> {
+    pub fn new(mut canvas: C, rect: Rect) -> Self {
+        canvas.init(RawTerminalScreen::headless(&rect.size));
+
+        Self {
+            rect,
+            camera: None,
+            canvas,
+        }
+    }
+
+    /// Sets a camera whose viewport is the canvas of the viewport.
+    pub fn with_camera(self, mut camera: Camera) -> Self {
+        camera.set_viewport(self.canvas.get_size());
+        Self {
+            camera: Some(camera),
+            ..self
+        }
+    }
+
+    pub fn canvas(&self) -> &C {
+        &self.canvas
+    }
+
+    pub fn canvas_mut(&mut self) -> &mut C {
+        &mut self.canvas
+    }
+
+    /// Moves or resizes the viewport, the content is cleared on resize.
+    pub fn set_rect(&mut self, rect: Rect) {
+        if rect.size != self.rect.size {
+            self.canvas.init(RawTerminalScreen::headless(&rect.size));
+            if let Some(camera) = self.camera.as_mut() {
+                camera.set_viewport(self.canvas.get_size());
+            }
+        }
+
+        self.rect = rect;
+    }
+
+    /// Calls `draw` with the canvas of the viewport, through the camera if
+    /// there is one.
+    pub fn draw<F: FnOnce(&mut C)>(&mut self, draw: F) {
+        match &self.camera {
+            Some(camera) => self.canvas.with_camera(camera, draw),
+            None => draw(&mut self.canvas),
+        }
+    }
+
+    /// Copies the cells onto `screen` at the position of the viewport, cells
+    /// outside of the screen or of `clip`, both in cells, are skipped.
+    pub fn blit<S: Screen>(&self, screen: &mut S, clip: &Rect) {
+        let source = self.canvas.get_screen();
+        let size = source.get_size();
+        let screen_rect = Rect::from(screen.get_size());
+        let visible = screen_rect.intersection(clip);
+
+        for y in 0..size.height() {
+            for x in 0..size.width() {
+                let p = Point::new(self.rect.pos.x + x, self.rect.pos.y + y);
+                if !visible.contains(&p) {
+                    continue;
+                }
+
+                let pixel = source.get_pixel((x + y * size.width()) as usize);
+                let index = (p.x + p.y * screen_rect.size.width()) as usize;
+                screen.set_pixel(index, pixel);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::model::color::Color;
+    use crate::model::rgba::Rgba;
+    use crate::view::canvas::fullblock::FullblockCanvas;
+    use crate::view::canvas::halfblock::HalfblockCanvas;
+
+    #[test]
+    fn it_combines_viewports_of_different_canvases() {
+        let mut main = FullblockCanvas::new();
+        main.init(RawTerminalScreen::headless(&Point::new(4, 2)));
+        let (left, right) = Rect::from(main.get_size()).split_at_x(2);
+        let mut map = Viewport::new(HalfblockCanvas::new(), left);
+        let mut log = Viewport::new(FullblockCanvas::new(), right);
+
+        map.draw(|canvas| canvas.draw_pixel(&Point::new(1, 3), &Rgba::red()));
+        log.draw(|canvas| canvas.draw_text(&Point::new(0, 1), &Color::text(), "hello"));
+        main.draw_viewport(&map);
+        main.draw_viewport(&log);
+
+        let screen = main.get_screen();
+        assert_eq!(screen.get_pixel(4 + 1).color.bg, Rgba::red());
+        assert_eq!(screen.get_pixel(4 + 2).ch, 'h');
+        assert_eq!(screen.get_pixel(4 + 3).ch, 'e');
+    }
+
+    #[test]
+    fn it_copies_only_cells_inside_of_the_clip() {
+        let mut main = HalfblockCanvas::new();
+        main.init(RawTerminalScreen::headless(&Point::new(3, 3)));
+        let mut viewport = Viewport::new(FullblockCanvas::new(), Rect::new(0, 0, 3, 3));
+        viewport.draw(|canvas| canvas.draw_rect_fill(&Rect::new(0, 0, 3, 3), &Rgba::red()));
+
+        main.push_clip(Rect::new(1, 1, 2, 4));
+        main.draw_viewport(&viewport);
+
+        let screen = main.get_screen();
+        for y in 0..3 {
+            for x in 0..3 {
+                let expected = if x >= 1 && y == 1 {
+                    Rgba::red()
+                } else {
+                    Rgba::black()
+                };
+                assert_eq!(screen.get_pixel((x + y * 3) as usize).color.bg, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn it_accepts_a_negative_size() {
+        let mut viewport = Viewport::new(FullblockCanvas::new(), Rect::new(0, 0, -2, 3));
+        viewport.draw(|canvas| canvas.draw_pixel(&Point::new(0, 0), &Rgba::red()));
+        viewport.set_rect(Rect::new(0, 0, -3, -3));
+
+        let mut main = FullblockCanvas::new();
+        main.init(RawTerminalScreen::headless(&Point::new(2, 2)));
+        main.draw_viewport(&viewport);
+    }
+
+    #[test]
+    fn it_draws_through_its_camera() {
+        let mut camera = Camera::new(&Point::new(1, 1));
+        camera.look_at(&Point::new(50, 50));
+        let mut viewport =
+            Viewport::new(FullblockCanvas::new(), Rect::new(0, 0, 3, 3)).with_camera(camera);
+
+        viewport.draw(|canvas| {
+            canvas.draw_rect_fill(&Rect::new(50, 50, 1, 1), &Rgba::blue());
+        });
+
+        let screen = viewport.canvas().get_screen();
+        assert_eq!(screen.get_pixel(4).color.bg, Rgba::blue());
+        assert!(viewport.canvas().get_transform().is_identity());
+    }
+}